@@ -1,3 +1,5 @@
+pub mod parse_error;
 pub mod tree;
+#[cfg(test)]
 mod tree_test;
 pub mod tree_types;
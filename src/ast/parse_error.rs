@@ -0,0 +1,55 @@
+#![allow(dead_code)]
+
+use std::{error::Error, fmt};
+
+use crate::{lexer::token::Token, types::span::Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// Any token other than the expected one.
+    Unexpected,
+    /// An assignment or update whose target is not a variable, the token is
+    /// its operator.
+    InvalidAssignmentTarget,
+    /// `break` or `continue` outside of a loop.
+    LoopControlOutsideLoop,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// Offending token, `None` when the input ended unexpectedly.
+    pub token: Option<Box<Token<'static>>>,
+    pub span: Span,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, token: Option<&Token>, span: Span, expected: &str) -> Self {
+        let found = match token {
+            Some(token) => format!("'{}'", token.literal),
+            None => "end of input".to_owned(),
+        };
+
+        Self {
+            kind,
+            token: token.map(|t| Box::new(t.clone().into_owned())),
+            span: token.map_or(span, |t| t.span),
+            expected: expected.to_owned(),
+            found,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: expected {}, found {}.",
+            self.span, self.expected, self.found
+        )
+    }
+}
+
+impl Error for ParseError {}
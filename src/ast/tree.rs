@@ -1,33 +1,160 @@
 #![allow(dead_code, unused_variables)]
 
-use std::error::Error;
+use std::collections::VecDeque;
 
-use super::tree_types::*;
+use super::{
+    parse_error::{ParseError, ParseErrorKind},
+    tree_types::*,
+};
 use crate::{
     lexer::token::Token,
-    types::{token_type::*, token_value::TokenValue},
+    types::{span::Span, token_type::*, token_value::TokenValue},
 };
 
+type ExpresionResult = Result<Expresion, ParseError>;
+type StatementResult = Result<Statement, ParseError>;
+
 #[derive(Debug, Clone)]
 pub struct AbstractSyntaxTree {
-    statements: Vec<Expresion>,
+    statements: Vec<Statement>,
 }
 
-#[derive(Debug, Clone)]
-struct TempState {
-    current: usize,
-    statements: Vec<Expresion>,
-    tokens: Vec<Token>,
+/// Parser state, pulling tokens from the stream one at a time.
+struct TempState<'a> {
+    tokens: Box<dyn Iterator<Item = Token<'a>> + 'a>,
+    /// The next tokens of the stream, at most two for `check_next`.
+    lookahead: VecDeque<Token<'a>>,
+    previous: Option<Token<'a>>,
+    /// Number of tokens consumed so far.
+    consumed: usize,
+    statements: Vec<Statement>,
+    errors: Vec<ParseError>,
+    /// Number of loops around the current statement, within the current function.
+    loop_depth: usize,
 }
 
-impl TempState {
-    fn new(tokens: &[Token]) -> Self {
-        Self {
-            current: 0,
+impl<'a> TempState<'a> {
+    fn new(tokens: impl Iterator<Item = Token<'a>> + 'a) -> Self {
+        let mut state = Self {
+            tokens: Box::new(tokens),
+            lookahead: VecDeque::with_capacity(2),
+            previous: None,
+            consumed: 0,
             statements: vec![],
-            tokens: tokens.into(),
+            errors: vec![],
+            loop_depth: 0,
+        };
+        state.fill();
+        state
+    }
+
+    fn fill(&mut self) {
+        while self.lookahead.len() < 2 {
+            match self.tokens.next() {
+                Some(token) => self.lookahead.push_back(token),
+                None => break,
+            }
+        }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.lookahead.is_empty()
+    }
+
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.lookahead.front()
+    }
+
+    fn previous(&self) -> &Token<'a> {
+        self.previous
+            .as_ref()
+            .expect("a token is consumed before looking back")
+    }
+
+    fn check(&self, token_type: TokenType) -> bool {
+        self.peek().is_some_and(|t| t.token_type == token_type)
+    }
+
+    fn advance(&mut self) -> &Token<'a> {
+        if let Some(token) = self.lookahead.pop_front() {
+            self.previous = Some(token);
+            self.consumed += 1;
+            self.fill();
+        }
+        self.previous()
+    }
+
+    fn matches(&mut self, types: &[TokenType]) -> bool {
+        if types.iter().any(|t| self.check(*t)) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn check_next(&self, token_type: TokenType) -> bool {
+        self.lookahead
+            .get(1)
+            .is_some_and(|t| t.token_type == token_type)
+    }
+
+    fn consume(&mut self, token_type: TokenType, expected: &str) -> Result<Token<'a>, ParseError> {
+        if self.check(token_type) {
+            return Ok(self.advance().clone());
+        }
+
+        Err(self.error(expected))
+    }
+
+    /// Skips tokens until a likely statement boundary, so parsing can resume
+    /// after an error. `start` is the number of tokens consumed when the
+    /// failed declaration began: a boundary the error was found at is kept,
+    /// unless keeping it would parse the same tokens again.
+    fn synchronize(&mut self, start: usize) {
+        if self.consumed == start || !self.at_boundary() {
+            self.advance();
+        }
+
+        while !self.is_at_end() {
+            if self.previous().token_type == TokenType::Semicolon || self.at_boundary() {
+                return;
+            }
+            self.advance();
         }
     }
+
+    /// Whether the next token closes a block or starts a statement.
+    fn at_boundary(&self) -> bool {
+        match self.peek().map(|t| t.token_type) {
+            Some(
+                TokenType::RightBrace
+                | TokenType::LeftBrace
+                | TokenType::Fun
+                | TokenType::Var
+                | TokenType::Class
+                | TokenType::If
+                | TokenType::While
+                | TokenType::For
+                | TokenType::Return
+                | TokenType::Break
+                | TokenType::Continue
+                | TokenType::Print,
+            ) => true,
+            Some(token_type) => token_type.is_type(),
+            None => false,
+        }
+    }
+
+    /// Error at the current token, or at the end of input.
+    fn error(&self, expected: &str) -> ParseError {
+        let end = self
+            .lookahead
+            .back()
+            .or(self.previous.as_ref())
+            .map_or(Span::new(0, 0, 1, 1), |t| t.span.after(&t.literal));
+        ParseError::new(ParseErrorKind::Unexpected, self.peek(), end, expected)
+    }
 }
 
 impl AbstractSyntaxTree {
@@ -35,39 +162,658 @@ impl AbstractSyntaxTree {
         Self { statements: vec![] }
     }
 
-    pub fn accept_tokens(&mut self, tokens: &[Token]) -> Result<(), Box<dyn Error>> {
-        let mut state = TempState::new(tokens);
+    pub fn accept_tokens(&mut self, tokens: &[Token]) -> Result<(), Vec<ParseError>> {
+        self.accept(tokens.iter().cloned())
+    }
+
+    /// Parses tokens as they are pulled from `tokens`, so the whole token
+    /// list never has to be in memory at once.
+    pub fn accept<'a, I>(&mut self, tokens: I) -> Result<(), Vec<ParseError>>
+    where
+        I: IntoIterator<Item = Token<'a>>,
+        I::IntoIter: 'a,
+    {
+        let mut state = TempState::new(tokens.into_iter());
 
-        while state.current < tokens.len() {
-            self.match_tokens(&tokens[state.current], &mut state)
+        while !state.is_at_end() {
+            if let Some(statement) = self.recovering_declaration(&mut state) {
+                state.statements.push(statement);
+            }
         }
 
         self.statements.clear();
         self.statements = state.statements;
-        Ok(())
+
+        if state.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(state.errors)
+        }
     }
-    
-
-    fn match_tokens(&self, token: &Token, state: &mut TempState) {
-        match token.token_type {
-            TokenType::Unknown => panic!("Unknown token type."),
-            TokenType::Integer => {
-                match token.value {
-                    TokenValue::Int(value) => {
-                        let value = Expresion::Integer(value);
-                        state.current += 1;
-                        state.statements.push(value);
+
+    /// Parses a declaration, recording the error and resynchronizing on failure.
+    fn recovering_declaration(&self, state: &mut TempState) -> Option<Statement> {
+        let start = state.consumed;
+        match self.declaration(state) {
+            Ok(statement) => Some(statement),
+            Err(error) => {
+                state.errors.push(error);
+                state.synchronize(start);
+                None
+            }
+        }
+    }
+
+    fn declaration(&self, state: &mut TempState) -> StatementResult {
+        if state.matches(&[TokenType::Fun]) {
+            return self.function(state, None);
+        }
+
+        if state.matches(&[TokenType::Var]) {
+            return self.variable_declaration(state, None);
+        }
+
+        if let Some(data_type) = state.peek().and_then(|t| Self::data_type(t.token_type)) {
+            if state.check_next(TokenType::Identifier) {
+                state.advance();
+                return if data_type == DataType::Void || state.check_next(TokenType::LeftParen) {
+                    self.function(state, Some(data_type))
+                } else {
+                    self.variable_declaration(state, Some(data_type))
+                };
+            }
+        }
+
+        self.statement(state)
+    }
+
+    /// `data_type` is `None` for `var` declarations.
+    fn variable_declaration(
+        &self,
+        state: &mut TempState,
+        data_type: Option<DataType>,
+    ) -> StatementResult {
+        let token = state.consume(TokenType::Identifier, "variable name")?;
+        let data_type = match data_type {
+            None if state.matches(&[TokenType::Colon]) => Some(self.type_annotation(state)?),
+            data_type => data_type,
+        };
+
+        let initializer = if state.matches(&[TokenType::Equal]) {
+            Some(self.expression(state)?)
+        } else {
+            None
+        };
+
+        self.end_of_statement(state)?;
+        Ok(Statement::Variable {
+            data_type,
+            name: token.literal.to_string(),
+            initializer,
+            span: token.span,
+        })
+    }
+
+    fn function(&self, state: &mut TempState, return_type: Option<DataType>) -> StatementResult {
+        let token = state.consume(TokenType::Identifier, "function name")?;
+        state.consume(TokenType::LeftParen, "'(' after function name")?;
+
+        let mut parameters = vec![];
+        if !state.check(TokenType::RightParen) {
+            loop {
+                let data_type = match state.peek().and_then(|t| Self::data_type(t.token_type)) {
+                    Some(data_type) => {
+                        state.advance();
+                        Some(data_type)
+                    }
+                    None => None,
+                };
+                let token = state.consume(TokenType::Identifier, "parameter name")?;
+                let data_type = match data_type {
+                    None if state.matches(&[TokenType::Colon]) => {
+                        Some(self.type_annotation(state)?)
                     }
-                    _ => panic!("Impossible int value."),
+                    data_type => data_type,
                 };
+                parameters.push(Parameter {
+                    data_type,
+                    name: token.literal.to_string(),
+                    span: token.span,
+                });
+
+                if !state.matches(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        state.consume(TokenType::RightParen, "')' after parameters")?;
+
+        let return_type = match return_type {
+            None if state.matches(&[TokenType::Arrow]) => Some(self.type_annotation(state)?),
+            return_type => return_type,
+        };
+
+        state.consume(TokenType::LeftBrace, "'{' before function body")?;
+        let loop_depth = std::mem::take(&mut state.loop_depth);
+        let body = self.block(state);
+        state.loop_depth = loop_depth;
+        let body = body?;
+
+        Ok(Statement::Function {
+            name: token.literal.to_string(),
+            parameters,
+            return_type,
+            body,
+            span: token.span,
+        })
+    }
+
+    fn statement(&self, state: &mut TempState) -> StatementResult {
+        if state.matches(&[TokenType::Print]) {
+            let span = state.previous().span;
+            let value = self.expression(state)?;
+            self.end_of_statement(state)?;
+            return Ok(Statement::Print(value, span));
+        }
+
+        if state.matches(&[TokenType::Return]) {
+            let span = state.previous().span;
+            let value = if state.check(TokenType::Semicolon) || state.is_at_end() {
+                None
+            } else {
+                Some(self.expression(state)?)
+            };
+            self.end_of_statement(state)?;
+            return Ok(Statement::Return(value, span));
+        }
+
+        if state.check(TokenType::Break) || state.check(TokenType::Continue) {
+            return self.loop_control(state);
+        }
+
+        if state.matches(&[TokenType::LeftBrace]) {
+            let span = state.previous().span;
+            return Ok(Statement::Block(self.block(state)?, span));
+        }
+
+        if state.matches(&[TokenType::If]) {
+            return self.if_statement(state);
+        }
+
+        if state.matches(&[TokenType::While]) {
+            let span = state.previous().span;
+            state.consume(TokenType::LeftParen, "'(' after 'while'")?;
+            let condition = self.expression(state)?;
+            state.consume(TokenType::RightParen, "')' after condition")?;
+            let body = Box::new(self.loop_body(state)?);
+            return Ok(Statement::While {
+                condition,
+                body,
+                span,
+            });
+        }
+
+        if state.matches(&[TokenType::For]) {
+            return self.for_statement(state);
+        }
+
+        let expression = self.expression(state)?;
+        self.end_of_statement(state)?;
+        let span = expression.span();
+        Ok(Statement::Expression(expression, span))
+    }
+
+    fn block(&self, state: &mut TempState) -> Result<Vec<Statement>, ParseError> {
+        let mut statements = vec![];
+
+        while !state.check(TokenType::RightBrace) && !state.is_at_end() {
+            if let Some(statement) = self.recovering_declaration(state) {
+                statements.push(statement);
+            }
+        }
+
+        state.consume(TokenType::RightBrace, "'}' after block")?;
+        Ok(statements)
+    }
+
+    fn if_statement(&self, state: &mut TempState) -> StatementResult {
+        let span = state.previous().span;
+        state.consume(TokenType::LeftParen, "'(' after 'if'")?;
+        let condition = self.expression(state)?;
+        state.consume(TokenType::RightParen, "')' after condition")?;
+
+        let then_branch = Box::new(self.statement(state)?);
+        let else_branch = if state.matches(&[TokenType::Else]) {
+            Some(Box::new(self.statement(state)?))
+        } else {
+            None
+        };
+
+        Ok(Statement::If {
+            condition,
+            then_branch,
+            else_branch,
+            span,
+        })
+    }
+
+    fn for_statement(&self, state: &mut TempState) -> StatementResult {
+        let span = state.previous().span;
+        state.consume(TokenType::LeftParen, "'(' after 'for'")?;
+
+        let initializer = if state.matches(&[TokenType::Semicolon]) {
+            None
+        } else {
+            Some(Box::new(self.declaration(state)?))
+        };
+
+        let condition = if state.check(TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.expression(state)?)
+        };
+        state.consume(TokenType::Semicolon, "';' after loop condition")?;
+
+        let increment = if state.check(TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression(state)?)
+        };
+        state.consume(TokenType::RightParen, "')' after for clauses")?;
+
+        let body = Box::new(self.loop_body(state)?);
+
+        Ok(Statement::For {
+            initializer,
+            condition,
+            increment,
+            body,
+            span,
+        })
+    }
+
+    fn loop_body(&self, state: &mut TempState) -> StatementResult {
+        state.loop_depth += 1;
+        let body = self.statement(state);
+        state.loop_depth -= 1;
+        body
+    }
+
+    /// `break` or `continue`, only allowed inside a loop.
+    fn loop_control(&self, state: &mut TempState) -> StatementResult {
+        let token = state.advance().clone();
+
+        if state.loop_depth == 0 {
+            let expected = format!("an enclosing loop for '{}'", token.literal);
+            return Err(ParseError::new(
+                ParseErrorKind::LoopControlOutsideLoop,
+                Some(&token),
+                token.span,
+                &expected,
+            ));
+        }
+        self.end_of_statement(state)?;
+
+        if token.token_type == TokenType::Break {
+            Ok(Statement::Break(token.span))
+        } else {
+            Ok(Statement::Continue(token.span))
+        }
+    }
+
+    /// Statements end with `;`, except for the last one in the input.
+    fn end_of_statement(&self, state: &mut TempState) -> Result<(), ParseError> {
+        if state.is_at_end() {
+            return Ok(());
+        }
+
+        state.consume(TokenType::Semicolon, "';' after statement")?;
+        Ok(())
+    }
+
+    fn expression(&self, state: &mut TempState) -> ExpresionResult {
+        self.assignment(state)
+    }
+
+    /// `=` and the compound assignments, which are parsed as `x = x op value`.
+    fn assignment(&self, state: &mut TempState) -> ExpresionResult {
+        let expression = self.ternary(state)?;
+
+        if state.matches(&[
+            TokenType::Equal,
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+        ]) {
+            let equals = state.previous().clone();
+            let value = self.assignment(state)?;
+
+            return match expression {
+                Expresion::Variable(name, span) => {
+                    let value = match Self::compound_operator(equals.token_type) {
+                        Some(operator) => Expresion::Binary(
+                            Box::new(Expresion::Variable(name.clone(), span)),
+                            operator,
+                            Box::new(value),
+                            equals.span,
+                        ),
+                        None => value,
+                    };
+                    Ok(Expresion::Assign(name, Box::new(value), span))
+                }
+                _ => Err(ParseError::new(
+                    ParseErrorKind::InvalidAssignmentTarget,
+                    Some(&equals),
+                    equals.span,
+                    &format!("a variable before '{}'", equals.literal),
+                )),
+            };
+        }
+
+        Ok(expression)
+    }
+
+    fn ternary(&self, state: &mut TempState) -> ExpresionResult {
+        let condition = self.or(state)?;
+
+        if !state.matches(&[TokenType::Question]) {
+            return Ok(condition);
+        }
+        let span = state.previous().span;
+
+        let then_branch = self.expression(state)?;
+        state.consume(TokenType::Colon, "':' in conditional expression")?;
+        let else_branch = self.ternary(state)?;
+
+        Ok(Expresion::Ternary(
+            Box::new(condition),
+            Box::new(then_branch),
+            Box::new(else_branch),
+            span,
+        ))
+    }
+
+    fn or(&self, state: &mut TempState) -> ExpresionResult {
+        self.binary(state, &[TokenType::Or], Self::and)
+    }
+
+    fn and(&self, state: &mut TempState) -> ExpresionResult {
+        self.binary(state, &[TokenType::And], Self::equality)
+    }
+
+    fn equality(&self, state: &mut TempState) -> ExpresionResult {
+        self.binary(
+            state,
+            &[TokenType::BangEqual, TokenType::EqualEqual],
+            Self::comparison,
+        )
+    }
+
+    fn comparison(&self, state: &mut TempState) -> ExpresionResult {
+        self.binary(
+            state,
+            &[
+                TokenType::Greater,
+                TokenType::GreaterEqual,
+                TokenType::Less,
+                TokenType::LessEqual,
+            ],
+            Self::bitwise_or,
+        )
+    }
+
+    // Bitwise operators bind tighter than comparisons, so `a & 1 == 0`
+    // compares the result of `a & 1`.
+    fn bitwise_or(&self, state: &mut TempState) -> ExpresionResult {
+        self.binary(state, &[TokenType::Pipe], Self::bitwise_xor)
+    }
+
+    fn bitwise_xor(&self, state: &mut TempState) -> ExpresionResult {
+        self.binary(state, &[TokenType::Caret], Self::bitwise_and)
+    }
+
+    fn bitwise_and(&self, state: &mut TempState) -> ExpresionResult {
+        self.binary(state, &[TokenType::Ampersand], Self::shift)
+    }
+
+    fn shift(&self, state: &mut TempState) -> ExpresionResult {
+        self.binary(
+            state,
+            &[TokenType::LessLess, TokenType::GreaterGreater],
+            Self::term,
+        )
+    }
+
+    fn term(&self, state: &mut TempState) -> ExpresionResult {
+        self.binary(state, &[TokenType::Minus, TokenType::Plus], Self::factor)
+    }
+
+    fn factor(&self, state: &mut TempState) -> ExpresionResult {
+        self.binary(
+            state,
+            &[TokenType::Slash, TokenType::Star, TokenType::Percent],
+            Self::unary,
+        )
+    }
+
+    /// Parses a left-associative chain of `operand (operator operand)*`.
+    fn binary(
+        &self,
+        state: &mut TempState,
+        operators: &[TokenType],
+        operand: fn(&Self, &mut TempState) -> ExpresionResult,
+    ) -> ExpresionResult {
+        let mut left = operand(self, state)?;
+
+        while state.matches(operators) {
+            let token = state.previous();
+            let (operator, span) = (Self::operator_symbol(token.token_type), token.span);
+            let right = operand(self, state)?;
+            left = Expresion::Binary(Box::new(left), operator, Box::new(right), span);
+        }
+
+        Ok(left)
+    }
+
+    fn unary(&self, state: &mut TempState) -> ExpresionResult {
+        if state.matches(&[TokenType::Bang, TokenType::Minus, TokenType::Tilde]) {
+            let token = state.previous();
+            let (operator, span) = (Self::operator_symbol(token.token_type), token.span);
+            let right = self.unary(state)?;
+            return Ok(Expresion::Unary(operator, Box::new(right), span));
+        }
+
+        if state.matches(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let token = state.previous().clone();
+            return match self.unary(state)? {
+                Expresion::Variable(name, span) => {
+                    let operator = Self::compound_operator(token.token_type).unwrap_or("+");
+                    let value = Expresion::Binary(
+                        Box::new(Expresion::Variable(name.clone(), span)),
+                        operator,
+                        Box::new(Expresion::Integer(1, token.span)),
+                        token.span,
+                    );
+                    Ok(Expresion::Assign(name, Box::new(value), span))
+                }
+                _ => Err(ParseError::new(
+                    ParseErrorKind::InvalidAssignmentTarget,
+                    Some(&token),
+                    token.span,
+                    &format!("a variable after '{}'", token.literal),
+                )),
+            };
+        }
+
+        self.power(state)
+    }
+
+    /// `**` is right-associative and binds tighter than a unary operator on
+    /// its left, so `-2 ** 2` is `-(2 ** 2)`.
+    fn power(&self, state: &mut TempState) -> ExpresionResult {
+        let base = self.postfix(state)?;
+
+        if state.matches(&[TokenType::StarStar]) {
+            let span = state.previous().span;
+            let exponent = self.unary(state)?;
+            return Ok(Expresion::Binary(
+                Box::new(base),
+                "**",
+                Box::new(exponent),
+                span,
+            ));
+        }
+
+        Ok(base)
+    }
+
+    fn postfix(&self, state: &mut TempState) -> ExpresionResult {
+        let expression = self.call(state)?;
+
+        if !state.matches(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            return Ok(expression);
+        }
+
+        let token = state.previous().clone();
+        match expression {
+            Expresion::Variable(name, span) => Ok(Expresion::Postfix(
+                name,
+                Self::operator_symbol(token.token_type),
+                span.merge(token.span),
+            )),
+            _ => Err(ParseError::new(
+                ParseErrorKind::InvalidAssignmentTarget,
+                Some(&token),
+                token.span,
+                &format!("a variable before '{}'", token.literal),
+            )),
+        }
+    }
+
+    fn call(&self, state: &mut TempState) -> ExpresionResult {
+        let mut expression = self.primary(state)?;
+
+        while state.matches(&[TokenType::LeftParen]) {
+            let mut arguments = vec![];
+            if !state.check(TokenType::RightParen) {
+                loop {
+                    arguments.push(self.expression(state)?);
+                    if !state.matches(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
             }
-            _ => {
-                state.current += 1;
+            let paren = state.consume(TokenType::RightParen, "')' after arguments")?;
+            let span = expression.span().merge(paren.span);
+            expression = Expresion::Call(Box::new(expression), arguments, span);
+        }
+
+        Ok(expression)
+    }
+
+    fn primary(&self, state: &mut TempState) -> ExpresionResult {
+        let token = match state.peek() {
+            Some(token) => token.clone(),
+            None => return Err(state.error("an expression")),
+        };
+
+        let span = token.span;
+        let expression = match (token.token_type, token.value) {
+            (TokenType::Integer, TokenValue::Int(value)) => Expresion::Integer(value, span),
+            // The scanner only accepts unsigned literals that fit in an `int`.
+            (TokenType::Integer, TokenValue::UInt(value)) => Expresion::Integer(value as i64, span),
+            (TokenType::Real, TokenValue::Float(value)) => Expresion::Float(value, span),
+            (TokenType::Real, TokenValue::Float32(value)) => {
+                Expresion::Float(TokenValue::widen_f32(value), span)
             }
+            (TokenType::Str, TokenValue::String(value)) => Expresion::Str(value, span),
+            (TokenType::Character, TokenValue::Char(value)) => Expresion::Char(value, span),
+            (TokenType::True, _) => Expresion::Bool(true, span),
+            (TokenType::False, _) => Expresion::Bool(false, span),
+            (TokenType::Nil, _) => Expresion::Nil(span),
+            (TokenType::Identifier, _) => Expresion::Variable(token.literal.to_string(), span),
+            (TokenType::LeftParen, _) => {
+                state.advance();
+                let expression = self.expression(state)?;
+                let paren = state.consume(TokenType::RightParen, "')' after expression")?;
+                return Ok(Expresion::Grouping(
+                    Box::new(expression),
+                    span.merge(paren.span),
+                ));
+            }
+            (TokenType::Unknown, _) => return Err(state.error("a valid token")),
+            _ => return Err(state.error("an expression")),
         };
+        state.advance();
+
+        Ok(expression)
+    }
+
+    /// The type after `:` or `->`.
+    fn type_annotation(&self, state: &mut TempState) -> Result<DataType, ParseError> {
+        match state.peek().and_then(|t| Self::data_type(t.token_type)) {
+            Some(data_type) => {
+                state.advance();
+                Ok(data_type)
+            }
+            None => Err(state.error("a type")),
+        }
+    }
+
+    fn data_type(token_type: TokenType) -> Option<DataType> {
+        match token_type {
+            TokenType::Int => Some(DataType::Int),
+            TokenType::Float => Some(DataType::Float),
+            TokenType::String => Some(DataType::String),
+            TokenType::Char => Some(DataType::Char),
+            TokenType::Bool => Some(DataType::Bool),
+            TokenType::Void => Some(DataType::Void),
+            _ => None,
+        }
+    }
+
+    fn operator_symbol(token_type: TokenType) -> &'static str {
+        match token_type {
+            TokenType::Or => "||",
+            TokenType::And => "&&",
+            TokenType::BangEqual => "!=",
+            TokenType::EqualEqual => "==",
+            TokenType::Greater => ">",
+            TokenType::GreaterEqual => ">=",
+            TokenType::Less => "<",
+            TokenType::LessEqual => "<=",
+            TokenType::Minus => "-",
+            TokenType::Plus => "+",
+            TokenType::Slash => "/",
+            TokenType::Star => "*",
+            TokenType::Bang => "!",
+            TokenType::Percent => "%",
+            TokenType::StarStar => "**",
+            TokenType::Ampersand => "&",
+            TokenType::Pipe => "|",
+            TokenType::Caret => "^",
+            TokenType::Tilde => "~",
+            TokenType::LessLess => "<<",
+            TokenType::GreaterGreater => ">>",
+            TokenType::PlusPlus => "++",
+            TokenType::MinusMinus => "--",
+            _ => "?",
+        }
+    }
+
+    /// Binary operator applied by a compound assignment or increment.
+    fn compound_operator(token_type: TokenType) -> Option<&'static str> {
+        match token_type {
+            TokenType::PlusEqual | TokenType::PlusPlus => Some("+"),
+            TokenType::MinusEqual | TokenType::MinusMinus => Some("-"),
+            TokenType::StarEqual => Some("*"),
+            TokenType::SlashEqual => Some("/"),
+            _ => None,
+        }
     }
 
-    pub fn get(&self) -> &[Expresion] {
+    pub fn get(&self) -> &[Statement] {
         &self.statements
     }
 }
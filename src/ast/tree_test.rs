@@ -1,12 +1,22 @@
 #![allow(unused_imports, unused_variables, dead_code)]
 
-use super::tree::AbstractSyntaxTree;
-use crate::{ast::tree_types::*, lexer::scanner::Scanner};
+use super::{parse_error::ParseErrorKind, tree::AbstractSyntaxTree};
+use crate::{
+    ast::tree_types::*,
+    lexer::{scanner::Scanner, token::Token},
+    test_support::parse,
+    types::{span::Span, token_type::TokenType},
+};
+
+/// Span on the first line of the input.
+fn at(start: usize, end: usize) -> Span {
+    Span::new(start, end, 1, start + 1)
+}
 
 #[test]
 fn test_basic_int() {
     let input = " 5 ";
-    let tokens = Scanner::new(input).get_tokens();
+    let tokens = Scanner::new(input).get_tokens().unwrap();
 
     let mut ast = AbstractSyntaxTree::new();
     let result = ast.accept_tokens(&tokens);
@@ -15,7 +25,7 @@ fn test_basic_int() {
 
     let statements = ast.get();
 
-    let value = Expresion::Integer(5);
+    let value = Statement::Expression(Expresion::Integer(5, at(1, 2)), at(1, 2));
     assert_eq!(value, statements[0]);
 
     for i in statements.iter() {
@@ -23,10 +33,10 @@ fn test_basic_int() {
     }
 }
 
-//#[test]
+#[test]
 fn basic_plus_expression() {
     let input = " 5 + 5";
-    let tokens = Scanner::new(input).get_tokens();
+    let tokens = Scanner::new(input).get_tokens().unwrap();
 
     let mut ast = AbstractSyntaxTree::new();
     let result = ast.accept_tokens(&tokens);
@@ -35,8 +45,626 @@ fn basic_plus_expression() {
 
     let statements = ast.get();
     let value = Expresion::Binary(
-        Box::new(Expresion::Integer(5)),
+        Box::new(Expresion::Integer(5, at(1, 2))),
         "+",
-        Box::new(Expresion::Integer(5)),
+        Box::new(Expresion::Integer(5, at(5, 6))),
+        at(3, 4),
+    );
+    assert_eq!(Statement::Expression(value, at(1, 6)), statements[0]);
+}
+
+#[test]
+fn precedence() {
+    let input = " 1 + 2 * 3 - 4";
+    let tokens = Scanner::new(input).get_tokens().unwrap();
+
+    let mut ast = AbstractSyntaxTree::new();
+    let result = ast.accept_tokens(&tokens);
+
+    assert!(result.is_ok());
+
+    let value = Expresion::Binary(
+        Box::new(Expresion::Binary(
+            Box::new(Expresion::Integer(1, at(1, 2))),
+            "+",
+            Box::new(Expresion::Binary(
+                Box::new(Expresion::Integer(2, at(5, 6))),
+                "*",
+                Box::new(Expresion::Integer(3, at(9, 10))),
+                at(7, 8),
+            )),
+            at(3, 4),
+        )),
+        "-",
+        Box::new(Expresion::Integer(4, at(13, 14))),
+        at(11, 12),
+    );
+    assert_eq!(Statement::Expression(value, at(1, 14)), ast.get()[0]);
+}
+
+#[test]
+fn grouping_and_unary() {
+    let input = " -(1 + 2) * !x";
+    let tokens = Scanner::new(input).get_tokens().unwrap();
+
+    let mut ast = AbstractSyntaxTree::new();
+    let result = ast.accept_tokens(&tokens);
+
+    assert!(result.is_ok());
+
+    let value = Expresion::Binary(
+        Box::new(Expresion::Unary(
+            "-",
+            Box::new(Expresion::Grouping(
+                Box::new(Expresion::Binary(
+                    Box::new(Expresion::Integer(1, at(3, 4))),
+                    "+",
+                    Box::new(Expresion::Integer(2, at(7, 8))),
+                    at(5, 6),
+                )),
+                at(2, 9),
+            )),
+            at(1, 2),
+        )),
+        "*",
+        Box::new(Expresion::Unary(
+            "!",
+            Box::new(Expresion::Variable("x".to_owned(), at(13, 14))),
+            at(12, 13),
+        )),
+        at(10, 11),
+    );
+    assert_eq!(Statement::Expression(value, at(1, 14)), ast.get()[0]);
+}
+
+#[test]
+fn logical_and_comparison() {
+    let input = " a < 1 || b >= 2.5 && c != \"s\"";
+    let tokens = Scanner::new(input).get_tokens().unwrap();
+
+    let mut ast = AbstractSyntaxTree::new();
+    let result = ast.accept_tokens(&tokens);
+
+    assert!(result.is_ok());
+
+    let value = Expresion::Binary(
+        Box::new(Expresion::Binary(
+            Box::new(Expresion::Variable("a".to_owned(), at(1, 2))),
+            "<",
+            Box::new(Expresion::Integer(1, at(5, 6))),
+            at(3, 4),
+        )),
+        "||",
+        Box::new(Expresion::Binary(
+            Box::new(Expresion::Binary(
+                Box::new(Expresion::Variable("b".to_owned(), at(10, 11))),
+                ">=",
+                Box::new(Expresion::Float(2.5, at(15, 18))),
+                at(12, 14),
+            )),
+            "&&",
+            Box::new(Expresion::Binary(
+                Box::new(Expresion::Variable("c".to_owned(), at(22, 23))),
+                "!=",
+                Box::new(Expresion::Str("s".to_owned(), at(27, 30))),
+                at(24, 26),
+            )),
+            at(19, 21),
+        )),
+        at(7, 9),
     );
+    assert_eq!(Statement::Expression(value, at(1, 30)), ast.get()[0]);
+}
+
+#[test]
+fn unclosed_grouping() {
+    let input = " (1 + 2";
+    let tokens = Scanner::new(input).get_tokens().unwrap();
+
+    let mut ast = AbstractSyntaxTree::new();
+    let result = ast.accept_tokens(&tokens);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn variable_declaration() {
+    let statements = parse("int x = 10; string s;");
+
+    assert_eq!(
+        statements,
+        vec![
+            Statement::Variable {
+                data_type: Some(DataType::Int),
+                name: "x".to_owned(),
+                initializer: Some(Expresion::Integer(10, at(8, 10))),
+                span: at(4, 5),
+            },
+            Statement::Variable {
+                data_type: Some(DataType::String),
+                name: "s".to_owned(),
+                initializer: None,
+                span: at(19, 20),
+            },
+        ]
+    );
+}
+
+#[test]
+fn var_and_void_declarations() {
+    let statements = parse("var a = 1; void log() { }");
+
+    assert_eq!(
+        statements,
+        vec![
+            Statement::Variable {
+                data_type: None,
+                name: "a".to_owned(),
+                initializer: Some(Expresion::Integer(1, at(8, 9))),
+                span: at(4, 5),
+            },
+            Statement::Function {
+                name: "log".to_owned(),
+                parameters: vec![],
+                return_type: Some(DataType::Void),
+                body: vec![],
+                span: at(16, 19),
+            },
+        ]
+    );
+}
+
+#[test]
+fn break_and_continue() {
+    let statements = parse("while (true) { break; continue; }");
+
+    assert_eq!(
+        statements,
+        vec![Statement::While {
+            condition: Expresion::Bool(true, at(7, 11)),
+            body: Box::new(Statement::Block(
+                vec![
+                    Statement::Break(at(15, 20)),
+                    Statement::Continue(at(22, 30))
+                ],
+                at(13, 14),
+            )),
+            span: at(0, 5),
+        }]
+    );
+}
+
+#[test]
+fn break_outside_loop() {
+    let tokens = Scanner::new(
+        "break;
+while (true) { fun f() { continue; } }",
+    )
+    .get_tokens()
+    .unwrap();
+    let mut ast = AbstractSyntaxTree::new();
+    let errors = ast.accept_tokens(&tokens).unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].expected, "an enclosing loop for 'break'");
+    assert_eq!(errors[0].span, at(0, 5));
+    assert_eq!(errors[0].kind, ParseErrorKind::LoopControlOutsideLoop);
+    assert_eq!(errors[1].expected, "an enclosing loop for 'continue'");
+    assert_eq!(errors[1].span.line, 2);
+}
+
+#[test]
+fn assignment_and_call() {
+    let statements = parse("x = y = add(1, 2);");
+
+    let value = Expresion::Assign(
+        "x".to_owned(),
+        Box::new(Expresion::Assign(
+            "y".to_owned(),
+            Box::new(Expresion::Call(
+                Box::new(Expresion::Variable("add".to_owned(), at(8, 11))),
+                vec![
+                    Expresion::Integer(1, at(12, 13)),
+                    Expresion::Integer(2, at(15, 16)),
+                ],
+                at(8, 17),
+            )),
+            at(4, 5),
+        )),
+        at(0, 1),
+    );
+    assert_eq!(statements, vec![Statement::Expression(value, at(0, 1))]);
+}
+
+#[test]
+fn if_else_block() {
+    let statements = parse(
+        r#"
+        if (x > 1) {
+            print x;
+        } else print("small");
+        "#,
+    );
+
+    let value = Statement::If {
+        condition: Expresion::Binary(
+            Box::new(Expresion::Variable(
+                "x".to_owned(),
+                Span::new(13, 14, 2, 13),
+            )),
+            ">",
+            Box::new(Expresion::Integer(1, Span::new(17, 18, 2, 17))),
+            Span::new(15, 16, 2, 15),
+        ),
+        then_branch: Box::new(Statement::Block(
+            vec![Statement::Print(
+                Expresion::Variable("x".to_owned(), Span::new(40, 41, 3, 19)),
+                Span::new(34, 39, 3, 13),
+            )],
+            Span::new(20, 21, 2, 20),
+        )),
+        else_branch: Some(Box::new(Statement::Print(
+            Expresion::Grouping(
+                Box::new(Expresion::Str("small".to_owned(), Span::new(64, 71, 4, 22))),
+                Span::new(63, 72, 4, 21),
+            ),
+            Span::new(58, 63, 4, 16),
+        ))),
+        span: Span::new(9, 11, 2, 9),
+    };
+    assert_eq!(statements, vec![value]);
+}
+
+#[test]
+fn while_and_for_loops() {
+    let statements = parse(
+        r#"
+        while (true) { }
+        for (int i = 0; i < 10; i = i + 1) print i;
+        for (;;) { }
+        "#,
+    );
+
+    assert_eq!(statements.len(), 3);
+    assert!(matches!(statements[0], Statement::While { .. }));
+
+    match &statements[1] {
+        Statement::For {
+            initializer,
+            condition,
+            increment,
+            ..
+        } => {
+            assert!(matches!(
+                initializer.as_deref(),
+                Some(Statement::Variable { .. })
+            ));
+            assert!(condition.is_some());
+            assert!(matches!(
+                increment,
+                Some(Expresion::Assign(_, _, Span { line: 3, .. }))
+            ));
+        }
+        _ => panic!("Expected for statement."),
+    }
+
+    assert_eq!(
+        statements[2],
+        Statement::For {
+            initializer: None,
+            condition: None,
+            increment: None,
+            body: Box::new(Statement::Block(vec![], Span::new(95, 96, 4, 18))),
+            span: Span::new(86, 89, 4, 9),
+        }
+    );
+}
+
+#[test]
+fn function_declaration() {
+    let statements = parse(
+        r#"
+        int add(int a, b) {
+            return a + b;
+        }
+        fun main() {
+            float y = 3.14;
+            return;
+        }
+        "#,
+    );
+
+    assert_eq!(
+        statements[0],
+        Statement::Function {
+            name: "add".to_owned(),
+            parameters: vec![
+                Parameter {
+                    data_type: Some(DataType::Int),
+                    name: "a".to_owned(),
+                    span: Span::new(21, 22, 2, 21),
+                },
+                Parameter {
+                    data_type: None,
+                    name: "b".to_owned(),
+                    span: Span::new(24, 25, 2, 24),
+                },
+            ],
+            return_type: Some(DataType::Int),
+            body: vec![Statement::Return(
+                Some(Expresion::Binary(
+                    Box::new(Expresion::Variable(
+                        "a".to_owned(),
+                        Span::new(48, 49, 3, 20)
+                    )),
+                    "+",
+                    Box::new(Expresion::Variable(
+                        "b".to_owned(),
+                        Span::new(52, 53, 3, 24)
+                    )),
+                    Span::new(50, 51, 3, 22),
+                )),
+                Span::new(41, 47, 3, 13),
+            )],
+            span: Span::new(13, 16, 2, 13),
+        }
+    );
+
+    match &statements[1] {
+        Statement::Function {
+            return_type, body, ..
+        } => {
+            assert_eq!(*return_type, None);
+            assert_eq!(body.len(), 2);
+            assert!(matches!(body[1], Statement::Return(None, _)));
+        }
+        _ => panic!("Expected function declaration."),
+    }
+}
+
+#[test]
+fn missing_semicolon() {
+    let tokens = Scanner::new("int x = 10 int y = 5;").get_tokens().unwrap();
+
+    let mut ast = AbstractSyntaxTree::new();
+    let result = ast.accept_tokens(&tokens);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn parse_error_details() {
+    let tokens = Scanner::new("int x = 10\nint y = 5;").get_tokens().unwrap();
+
+    let mut ast = AbstractSyntaxTree::new();
+    let errors = ast.accept_tokens(&tokens).unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].span, Span::new(11, 14, 2, 1));
+    assert_eq!(errors[0].expected, "';' after statement");
+    assert_eq!(errors[0].found, "'int'");
+    assert_eq!(errors[0].kind, ParseErrorKind::Unexpected);
+    assert_eq!(
+        errors[0].token.as_ref().map(|t| t.token_type),
+        Some(TokenType::Int)
+    );
+}
+
+#[test]
+fn unknown_token_error() {
+    let mut tokens = Scanner::new("x = ;").get_tokens().unwrap();
+    tokens.insert(2, Token::new(TokenType::Unknown, "@", at(4, 5)));
+
+    let mut ast = AbstractSyntaxTree::new();
+    let errors = ast.accept_tokens(&tokens).unwrap_err();
+
+    assert_eq!(errors[0].expected, "a valid token");
+    assert_eq!(errors[0].found, "'@'");
+}
+
+#[test]
+fn unexpected_end_of_input() {
+    let tokens = Scanner::new("print (1 +").get_tokens().unwrap();
+
+    let mut ast = AbstractSyntaxTree::new();
+    let errors = ast.accept_tokens(&tokens).unwrap_err();
+
+    assert!(errors[0].token.is_none());
+    assert_eq!(errors[0].found, "end of input");
+    assert_eq!(errors[0].span, at(10, 10));
+}
+
+#[test]
+fn end_of_input_after_wide_and_multi_line_tokens() {
+    let tokens = Scanner::new("print (\"žluť\"").get_tokens().unwrap();
+    let errors = AbstractSyntaxTree::new()
+        .accept_tokens(&tokens)
+        .unwrap_err();
+    assert_eq!(errors[0].span, Span::new(15, 15, 1, 14));
+
+    let tokens = Scanner::new("print (\"\"\"a\nbb\"\"\"")
+        .get_tokens()
+        .unwrap();
+    let errors = AbstractSyntaxTree::new()
+        .accept_tokens(&tokens)
+        .unwrap_err();
+    assert_eq!(errors[0].span, Span::new(17, 17, 2, 6));
+}
+
+#[test]
+fn reports_multiple_errors() {
+    let input = r#"
+        int x = ;
+        print x;
+        float y = 1.5 2.5;
+        fun f() {
+            return (1 + ;
+            print 2;
+        }
+        if x) print 3;
+        string s = "ok";
+    "#;
+    let tokens = Scanner::new(input).get_tokens().unwrap();
+
+    let mut ast = AbstractSyntaxTree::new();
+    let errors = ast.accept_tokens(&tokens).unwrap_err();
+
+    let lines = errors.iter().map(|e| e.span.line).collect::<Vec<usize>>();
+    assert_eq!(lines, vec![2, 4, 6, 9]);
+
+    let statements = ast.get();
+    assert_eq!(statements.len(), 4);
+    assert!(matches!(statements[0], Statement::Print(..)));
+    assert!(matches!(
+        &statements[1],
+        Statement::Function { body, .. } if body.len() == 1
+    ));
+    assert!(matches!(statements[2], Statement::Print(..)));
+    assert!(matches!(statements[3], Statement::Variable { .. }));
+}
+
+#[test]
+fn keeps_the_closing_brace_after_an_error() {
+    let tokens = Scanner::new("{ print 1 }\nprint 2;").get_tokens().unwrap();
+
+    let mut ast = AbstractSyntaxTree::new();
+    let errors = ast.accept_tokens(&tokens).unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].expected, "';' after statement");
+    assert_eq!(errors[0].found, "'}'");
+
+    let statements = ast.get();
+    assert_eq!(statements.len(), 2);
+    assert!(matches!(&statements[0], Statement::Block(body, _) if body.is_empty()));
+    assert!(matches!(statements[1], Statement::Print(..)));
+}
+
+/// Fully parenthesized form of an expression statement, to check grouping.
+fn grouped(input: &str) -> String {
+    fn show(expression: &Expresion) -> String {
+        match expression {
+            Expresion::Integer(value, _) => value.to_string(),
+            Expresion::Bool(value, _) => value.to_string(),
+            Expresion::Variable(name, _) => name.clone(),
+            Expresion::Assign(name, value, _) => format!("({} = {})", name, show(value)),
+            Expresion::Binary(left, operator, right, _) => {
+                format!("({} {} {})", show(left), operator, show(right))
+            }
+            Expresion::Unary(operator, right, _) => format!("({}{})", operator, show(right)),
+            Expresion::Postfix(name, operator, _) => format!("({}{})", name, operator),
+            Expresion::Ternary(condition, then_branch, else_branch, _) => format!(
+                "({} ? {} : {})",
+                show(condition),
+                show(then_branch),
+                show(else_branch)
+            ),
+            other => format!("{:?}", other),
+        }
+    }
+
+    match &parse(input)[0] {
+        Statement::Expression(expression, _) => show(expression),
+        other => panic!("not an expression: {:?}", other),
+    }
+}
+
+#[test]
+fn extended_operator_precedence() {
+    assert_eq!(
+        grouped("a | b ^ c & d << 1 + 2;"),
+        "(a | (b ^ (c & (d << (1 + 2)))))"
+    );
+    assert_eq!(grouped("a & 1 == 0;"), "((a & 1) == 0)");
+    assert_eq!(grouped("a % b * c;"), "((a % b) * c)");
+    assert_eq!(grouped("-2 ** 3 ** 2;"), "(-(2 ** (3 ** 2)))");
+    assert_eq!(grouped("~a + b--;"), "((~a) + (b--))");
+    assert_eq!(grouped("a ? b : c ? d : e;"), "(a ? b : (c ? d : e))");
+    assert_eq!(grouped("x = a || b ? 1 : 2;"), "(x = ((a || b) ? 1 : 2))");
+}
+
+#[test]
+fn compound_assignment_and_increment() {
+    assert_eq!(grouped("x += y -= 2;"), "(x = (x + (y = (y - 2))))");
+    assert_eq!(grouped("x *= 3;"), "(x = (x * 3))");
+    assert_eq!(grouped("++x;"), "(x = (x + 1))");
+    assert_eq!(grouped("--x;"), "(x = (x - 1))");
+
+    let statements = parse("x++;");
+    assert_eq!(
+        statements[0],
+        Statement::Expression(Expresion::Postfix("x".to_owned(), "++", at(0, 3)), at(0, 3))
+    );
+}
+
+#[test]
+fn invalid_update_targets() {
+    let tokens = Scanner::new("1 += 2;\n(a)++;\n++3;").get_tokens().unwrap();
+    let mut ast = AbstractSyntaxTree::new();
+    let errors = ast.accept_tokens(&tokens).unwrap_err();
+
+    let expected = errors
+        .iter()
+        .map(|e| e.expected.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        expected,
+        vec![
+            "a variable before '+='",
+            "a variable before '++'",
+            "a variable after '++'",
+        ]
+    );
+    assert!(errors
+        .iter()
+        .all(|e| matches!(e.kind, ParseErrorKind::InvalidAssignmentTarget)));
+}
+
+#[test]
+fn type_annotations() {
+    let statements = parse("var x: float; fun f(a: int, b) -> bool { }");
+
+    assert_eq!(
+        statements[0],
+        Statement::Variable {
+            data_type: Some(DataType::Float),
+            name: "x".to_owned(),
+            initializer: None,
+            span: at(4, 5),
+        }
+    );
+    match &statements[1] {
+        Statement::Function {
+            parameters,
+            return_type,
+            ..
+        } => {
+            assert_eq!(parameters[0].data_type, Some(DataType::Int));
+            assert_eq!(parameters[1].data_type, None);
+            assert_eq!(*return_type, Some(DataType::Bool));
+        }
+        other => panic!("not a function: {:?}", other),
+    }
+}
+
+#[test]
+fn parse_token_stream() {
+    let input = "var a = 1; print a + 2;\nwhile (a < 3) a = a + 1;";
+    let tokens = Scanner::new(input).tokens().map(Result::unwrap);
+
+    let mut streamed = AbstractSyntaxTree::new();
+    streamed.accept(tokens).unwrap();
+
+    assert_eq!(streamed.get(), parse(input).as_slice());
+}
+
+#[test]
+fn token_stream_end_of_input() {
+    let tokens = Scanner::new("print (1 +").tokens().map(Result::unwrap);
+
+    let mut ast = AbstractSyntaxTree::new();
+    let errors = ast.accept(tokens).unwrap_err();
+
+    assert_eq!(errors[0].found, "end of input");
+    assert_eq!(errors[0].span, Span::new(10, 10, 1, 11));
 }
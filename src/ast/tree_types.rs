@@ -4,12 +4,56 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+use crate::types::span::Span;
+
+/// Every node keeps its source span: literals and names their token,
+/// operators the operator token and groupings their parentheses.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum Expresion {
-    Unknown(String),
-    Integer(i32),
-    Binary(Box<Expresion>, &'static str, Box<Expresion>),
-    Unary(&'static str, Box<Expresion>),
+    Unknown(String, Span),
+    Integer(i64, Span),
+    Float(f64, Span),
+    Str(String, Span),
+    Char(char, Span),
+    Bool(bool, Span),
+    Nil(Span),
+    Variable(String, Span),
+    Assign(String, Box<Expresion>, Span),
+    Grouping(Box<Expresion>, Span),
+    Binary(Box<Expresion>, &'static str, Box<Expresion>, Span),
+    Unary(&'static str, Box<Expresion>, Span),
+    /// `x++` or `x--`, evaluates to the value before the update. Prefix
+    /// forms and compound assignments are parsed into `Assign`.
+    Postfix(String, &'static str, Span),
+    /// `condition ? then : else`, the span is the `?`.
+    Ternary(Box<Expresion>, Box<Expresion>, Box<Expresion>, Span),
+    Call(Box<Expresion>, Vec<Expresion>, Span),
+}
+
+impl Expresion {
+    /// Span of the expression, covering the operands of unary, binary and
+    /// conditional expressions.
+    pub fn span(&self) -> Span {
+        match self {
+            Expresion::Unknown(_, span)
+            | Expresion::Integer(_, span)
+            | Expresion::Float(_, span)
+            | Expresion::Str(_, span)
+            | Expresion::Char(_, span)
+            | Expresion::Bool(_, span)
+            | Expresion::Nil(span)
+            | Expresion::Variable(_, span)
+            | Expresion::Assign(_, _, span)
+            | Expresion::Grouping(_, span)
+            | Expresion::Postfix(_, _, span)
+            | Expresion::Call(_, _, span) => *span,
+            Expresion::Binary(left, _, right, span) => left.span().merge(*span).merge(right.span()),
+            Expresion::Unary(_, right, span) => span.merge(right.span()),
+            Expresion::Ternary(condition, _, otherwise, _) => {
+                condition.span().merge(otherwise.span())
+            }
+        }
+    }
 }
 
 impl Display for Expresion {
@@ -19,3 +63,92 @@ impl Display for Expresion {
         write!(f, "{:?}", result.unwrap_or("Error".to_string()))
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum DataType {
+    Int,
+    Float,
+    String,
+    Char,
+    Bool,
+    /// Only used as a function return type.
+    Void,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Parameter {
+    pub data_type: Option<DataType>,
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Statement {
+    /// The span of the expression.
+    Expression(Expresion, Span),
+    Variable {
+        /// `None` for `var` declarations.
+        data_type: Option<DataType>,
+        name: String,
+        initializer: Option<Expresion>,
+        span: Span,
+    },
+    /// The span of the `{`.
+    Block(Vec<Statement>, Span),
+    // Statements starting with a keyword keep its span.
+    If {
+        condition: Expresion,
+        then_branch: Box<Statement>,
+        else_branch: Option<Box<Statement>>,
+        span: Span,
+    },
+    While {
+        condition: Expresion,
+        body: Box<Statement>,
+        span: Span,
+    },
+    For {
+        initializer: Option<Box<Statement>>,
+        condition: Option<Expresion>,
+        increment: Option<Expresion>,
+        body: Box<Statement>,
+        span: Span,
+    },
+    Return(Option<Expresion>, Span),
+    Break(Span),
+    Continue(Span),
+    Print(Expresion, Span),
+    Function {
+        name: String,
+        parameters: Vec<Parameter>,
+        return_type: Option<DataType>,
+        body: Vec<Statement>,
+        span: Span,
+    },
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Expression(_, span)
+            | Statement::Block(_, span)
+            | Statement::Return(_, span)
+            | Statement::Break(span)
+            | Statement::Continue(span)
+            | Statement::Print(_, span) => *span,
+            Statement::Variable { span, .. }
+            | Statement::If { span, .. }
+            | Statement::While { span, .. }
+            | Statement::For { span, .. }
+            | Statement::Function { span, .. } => *span,
+        }
+    }
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let result = serde_json::to_string_pretty(self);
+
+        write!(f, "{:?}", result.unwrap_or("Error".to_string()))
+    }
+}
@@ -0,0 +1,126 @@
+#![allow(dead_code)]
+
+use std::{fmt, rc::Rc};
+
+use super::opcode::OpCode;
+use crate::types::span::Span;
+
+/// Values known at compile time, referenced by index from the code.
+#[derive(Debug, Clone)]
+pub enum Constant {
+    Int(i64),
+    Float(f64),
+    String(String),
+    Char(char),
+    Function(Rc<CompiledFunction>),
+}
+
+impl Constant {
+    /// Whether two constants can share a slot of the pool.
+    fn same(&self, other: &Constant) -> bool {
+        match (self, other) {
+            (Constant::Int(a), Constant::Int(b)) => a == b,
+            (Constant::Float(a), Constant::Float(b)) => a.to_bits() == b.to_bits(),
+            (Constant::String(a), Constant::String(b)) => a == b,
+            (Constant::Char(a), Constant::Char(b)) => a == b,
+            (Constant::Function(a), Constant::Function(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constant::Int(value) => write!(f, "{}", value),
+            Constant::Float(value) => write!(f, "{:?}", value),
+            Constant::String(value) => write!(f, "{:?}", value),
+            Constant::Char(value) => write!(f, "{:?}", value),
+            Constant::Function(function) => write!(f, "<fn {}>", function.name),
+        }
+    }
+}
+
+/// A function body compiled to bytecode.
+#[derive(Debug, Clone)]
+pub struct CompiledFunction {
+    pub name: String,
+    pub arity: usize,
+    /// Number of variables the function captures from enclosing ones.
+    pub upvalue_count: usize,
+    pub chunk: Chunk,
+}
+
+impl CompiledFunction {
+    pub fn new(name: &str, arity: usize) -> Self {
+        Self {
+            name: name.to_owned(),
+            arity,
+            upvalue_count: 0,
+            chunk: Chunk::new(),
+        }
+    }
+}
+
+/// Bytecode with its constant pool. The span of each instruction is kept
+/// in a run-length encoded table to locate runtime errors.
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+    pub code: Vec<u8>,
+    pub constants: Vec<Constant>,
+    /// Offsets where a new span starts, in increasing order.
+    spans: Vec<(usize, Span)>,
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Self {
+            code: vec![],
+            constants: vec![],
+            spans: vec![],
+        }
+    }
+
+    pub fn write(&mut self, byte: u8, span: Span) {
+        if self.spans.last().map(|(_, last)| *last) != Some(span) {
+            self.spans.push((self.code.len(), span));
+        }
+        self.code.push(byte);
+    }
+
+    pub fn write_op(&mut self, op: OpCode, span: Span) {
+        self.write(op as u8, span);
+    }
+
+    /// Index of the constant in the pool, adding it when it is new.
+    pub fn add_constant(&mut self, constant: Constant) -> usize {
+        match self.constants.iter().position(|c| c.same(&constant)) {
+            Some(index) => index,
+            None => {
+                self.constants.push(constant);
+                self.constants.len() - 1
+            }
+        }
+    }
+
+    pub fn read_u16(&self, offset: usize) -> u16 {
+        u16::from_be_bytes([self.code[offset], self.code[offset + 1]])
+    }
+
+    /// Span of the instruction at `offset`.
+    pub fn span_at(&self, offset: usize) -> Span {
+        let index = self.spans.partition_point(|(start, _)| *start <= offset);
+        index
+            .checked_sub(1)
+            .map_or_else(Span::default, |index| self.spans[index].1)
+    }
+
+    pub fn spans(&self) -> &[(usize, Span)] {
+        &self.spans
+    }
+
+    /// Replaces the span table, offsets must be in increasing order.
+    pub fn set_spans(&mut self, spans: Vec<(usize, Span)>) {
+        self.spans = spans;
+    }
+}
@@ -0,0 +1,30 @@
+#![allow(dead_code)]
+
+use std::{error::Error, fmt};
+
+use crate::types::span::Span;
+
+/// A program the bytecode cannot represent, such as a function with more
+/// locals than a frame can address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompileError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl CompileError {
+    pub fn new(message: &str, span: Span) -> Self {
+        Self {
+            message: message.to_owned(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+impl Error for CompileError {}
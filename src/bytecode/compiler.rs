@@ -0,0 +1,755 @@
+#![allow(dead_code)]
+
+use std::rc::Rc;
+
+use super::{
+    chunk::{Chunk, CompiledFunction, Constant},
+    compile_error::CompileError,
+    opcode::OpCode,
+};
+use crate::{
+    ast::tree_types::*,
+    semantic::resolution::{Binding, Resolution},
+    types::span::Span,
+};
+
+type CompileResult<T = ()> = Result<T, CompileError>;
+
+/// Name of the function holding the top-level statements.
+pub const SCRIPT_NAME: &str = "script";
+
+const MAX_LOCALS: usize = u8::MAX as usize + 1;
+const MAX_UPVALUES: usize = u8::MAX as usize + 1;
+const MAX_ARGUMENTS: usize = u8::MAX as usize;
+
+/// Compiles resolved statements to bytecode. Locals live in stack slots
+/// found from the resolver's bindings, globals are looked up by name.
+#[derive(Debug, Clone)]
+pub struct Compiler<'a> {
+    resolution: &'a Resolution,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Local {
+    /// Whether a closure captures the variable, it must then be moved off
+    /// the stack when its scope ends.
+    captured: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Upvalue {
+    /// Captured from a slot of the enclosing function rather than from one
+    /// of its upvalues.
+    is_local: bool,
+    index: u8,
+}
+
+#[derive(Debug, Clone)]
+struct Loop {
+    /// Where `continue` jumps back to, `None` when it jumps forward to the
+    /// increment of a `for` loop.
+    continue_target: Option<usize>,
+    /// Locals alive when the loop started, the others are popped on exit.
+    locals: usize,
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct FunctionState {
+    function: CompiledFunction,
+    return_type: Option<DataType>,
+    /// Stack slots of the frame, the first one holds the called closure.
+    locals: Vec<Local>,
+    upvalues: Vec<Upvalue>,
+    loops: Vec<Loop>,
+}
+
+impl FunctionState {
+    fn new(name: &str, arity: usize, return_type: Option<DataType>) -> Self {
+        Self {
+            function: CompiledFunction::new(name, arity),
+            return_type,
+            locals: vec![Local { captured: false }],
+            upvalues: vec![],
+            loops: vec![],
+        }
+    }
+}
+
+/// A scope of the resolver, placed in the frame of a function.
+#[derive(Debug, Clone, Copy)]
+struct Scope {
+    function: usize,
+    /// Frame slot of the first local of the scope.
+    base: usize,
+}
+
+#[derive(Debug, Clone)]
+struct TempState {
+    functions: Vec<FunctionState>,
+    scopes: Vec<Scope>,
+    /// Span of the closest node, given to the instructions emitted for it.
+    location: Span,
+}
+
+impl TempState {
+    fn new() -> Self {
+        Self {
+            functions: vec![FunctionState::new(SCRIPT_NAME, 0, None)],
+            scopes: vec![],
+            location: Span::default(),
+        }
+    }
+
+    fn current(&mut self) -> &mut FunctionState {
+        self.functions.last_mut().unwrap()
+    }
+
+    fn chunk(&mut self) -> &mut Chunk {
+        &mut self.current().function.chunk
+    }
+
+    fn error(&self, message: &str) -> CompileError {
+        CompileError::new(message, self.location)
+    }
+
+    fn emit(&mut self, op: OpCode) {
+        let span = self.location;
+        self.chunk().write_op(op, span);
+    }
+
+    fn emit_byte(&mut self, byte: u8) {
+        let span = self.location;
+        self.chunk().write(byte, span);
+    }
+
+    fn emit_u16(&mut self, value: u16) {
+        for byte in value.to_be_bytes() {
+            self.emit_byte(byte);
+        }
+    }
+
+    fn emit_with_byte(&mut self, op: OpCode, operand: u8) {
+        self.emit(op);
+        self.emit_byte(operand);
+    }
+
+    fn constant(&mut self, constant: Constant) -> CompileResult<u16> {
+        let index = self.chunk().add_constant(constant);
+        u16::try_from(index).map_err(|_| self.error("Too many constants in one function."))
+    }
+
+    fn emit_constant(&mut self, op: OpCode, constant: Constant) -> CompileResult {
+        let index = self.constant(constant)?;
+        self.emit(op);
+        self.emit_u16(index);
+        Ok(())
+    }
+
+    /// Emits a jump with a placeholder offset, returns where to patch it.
+    fn emit_jump(&mut self, op: OpCode) -> usize {
+        self.emit(op);
+        self.emit_u16(u16::MAX);
+        self.chunk().code.len() - 2
+    }
+
+    /// Points the jump whose operand is at `offset` to the next instruction.
+    fn patch_jump(&mut self, offset: usize) -> CompileResult {
+        let distance = self.chunk().code.len() - offset - 2;
+        let distance =
+            u16::try_from(distance).map_err(|_| self.error("Too much code to jump over."))?;
+        self.chunk().code[offset..offset + 2].copy_from_slice(&distance.to_be_bytes());
+        Ok(())
+    }
+
+    fn emit_loop(&mut self, start: usize) -> CompileResult {
+        self.emit(OpCode::Loop);
+        let distance = self.chunk().code.len() + 2 - start;
+        let distance = u16::try_from(distance).map_err(|_| self.error("Loop body too large."))?;
+        self.emit_u16(distance);
+        Ok(())
+    }
+
+    fn add_local(&mut self) -> CompileResult {
+        if self.current().locals.len() == MAX_LOCALS {
+            return Err(self.error("Too many local variables in function."));
+        }
+        self.current().locals.push(Local { captured: false });
+        Ok(())
+    }
+
+    /// Emits the instructions discarding the locals above `count`, the
+    /// compiler still tracks them.
+    fn discard_locals(&mut self, count: usize) {
+        let locals = self.current().locals[count..].to_vec();
+        for local in locals.iter().rev() {
+            self.emit(if local.captured {
+                OpCode::CloseUpvalue
+            } else {
+                OpCode::Pop
+            });
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        let scope = Scope {
+            function: self.functions.len() - 1,
+            base: self.current().locals.len(),
+        };
+        self.scopes.push(scope);
+    }
+
+    fn end_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap();
+        self.discard_locals(scope.base);
+        self.current().locals.truncate(scope.base);
+    }
+
+    /// Index of the upvalue of `function` capturing `slot` of the enclosing
+    /// function `owner`, threading it through the functions in between.
+    fn upvalue(&mut self, function: usize, owner: usize, slot: usize) -> CompileResult<u8> {
+        let upvalue = if owner + 1 == function {
+            self.functions[owner].locals[slot].captured = true;
+            Upvalue {
+                is_local: true,
+                index: slot as u8,
+            }
+        } else {
+            Upvalue {
+                is_local: false,
+                index: self.upvalue(function - 1, owner, slot)?,
+            }
+        };
+
+        let upvalues = &self.functions[function].upvalues;
+        if let Some(index) = upvalues.iter().position(|u| *u == upvalue) {
+            return Ok(index as u8);
+        }
+        if upvalues.len() == MAX_UPVALUES {
+            return Err(self.error("Too many captured variables in function."));
+        }
+        let upvalues = &mut self.functions[function].upvalues;
+        upvalues.push(upvalue);
+        Ok((upvalues.len() - 1) as u8)
+    }
+}
+
+/// Where a variable is read from and written to.
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    Local(u8),
+    Upvalue(u8),
+    Global(u16),
+}
+
+impl Target {
+    fn get(self, state: &mut TempState) {
+        match self {
+            Target::Local(slot) => state.emit_with_byte(OpCode::GetLocal, slot),
+            Target::Upvalue(index) => state.emit_with_byte(OpCode::GetUpvalue, index),
+            Target::Global(name) => {
+                state.emit(OpCode::GetGlobal);
+                state.emit_u16(name);
+            }
+        }
+    }
+
+    fn set(self, state: &mut TempState) {
+        match self {
+            Target::Local(slot) => state.emit_with_byte(OpCode::SetLocal, slot),
+            Target::Upvalue(index) => state.emit_with_byte(OpCode::SetUpvalue, index),
+            Target::Global(name) => {
+                state.emit(OpCode::SetGlobal);
+                state.emit_u16(name);
+            }
+        }
+    }
+}
+
+impl<'a> Compiler<'a> {
+    pub fn new(resolution: &'a Resolution) -> Self {
+        Self { resolution }
+    }
+
+    /// Compiles a program into the function run by the virtual machine.
+    pub fn compile(&self, statements: &[Statement]) -> CompileResult<CompiledFunction> {
+        let mut state = TempState::new();
+
+        for statement in statements {
+            self.statement(&mut state, statement)?;
+        }
+        state.emit(OpCode::Nil);
+        state.emit(OpCode::Return);
+
+        Ok(state.functions.pop().unwrap().function)
+    }
+
+    fn statement(&self, state: &mut TempState, statement: &Statement) -> CompileResult {
+        match statement {
+            Statement::Expression(expression, span) | Statement::Print(expression, span) => {
+                state.location = *span;
+                self.expression(state, expression)?;
+                state.location = *span;
+                state.emit(match statement {
+                    Statement::Print(_, _) => OpCode::Print,
+                    _ => OpCode::Pop,
+                });
+            }
+            Statement::Variable {
+                data_type,
+                name,
+                initializer,
+                span,
+            } => {
+                state.location = *span;
+                match (initializer, data_type) {
+                    (Some(expression), _) => {
+                        self.expression(state, expression)?;
+                        state.location = *span;
+                        if *data_type == Some(DataType::Float) {
+                            state.emit(OpCode::ToFloat);
+                        }
+                    }
+                    (None, Some(data_type)) => Self::default_value(state, *data_type)?,
+                    (None, None) => state.emit(OpCode::Nil),
+                }
+                self.define(state, name)?;
+            }
+            Statement::Block(statements, _) => {
+                state.begin_scope();
+                for statement in statements {
+                    self.statement(state, statement)?;
+                }
+                state.end_scope();
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => {
+                state.location = *span;
+                self.expression(state, condition)?;
+                let then_jump = state.emit_jump(OpCode::JumpIfFalse);
+                self.statement(state, then_branch)?;
+
+                match else_branch {
+                    Some(else_branch) => {
+                        let else_jump = state.emit_jump(OpCode::Jump);
+                        state.patch_jump(then_jump)?;
+                        self.statement(state, else_branch)?;
+                        state.patch_jump(else_jump)?;
+                    }
+                    None => state.patch_jump(then_jump)?,
+                }
+            }
+            Statement::While {
+                condition,
+                body,
+                span,
+            } => self.while_statement(state, condition, body, *span)?,
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                span,
+            } => {
+                state.begin_scope();
+                if let Some(initializer) = initializer {
+                    self.statement(state, initializer)?;
+                }
+                self.for_loop(state, condition.as_ref(), increment.as_ref(), body, *span)?;
+                state.end_scope();
+            }
+            Statement::Return(value, span) => {
+                state.location = *span;
+                match value {
+                    Some(value) => {
+                        self.expression(state, value)?;
+                        state.location = *span;
+                        if state.current().return_type == Some(DataType::Float) {
+                            state.emit(OpCode::ToFloat);
+                        }
+                    }
+                    None => state.emit(OpCode::Nil),
+                }
+                state.emit(OpCode::Return);
+            }
+            Statement::Break(span) => {
+                state.location = *span;
+                let locals = state.current().loops.last().unwrap().locals;
+                state.discard_locals(locals);
+                let jump = state.emit_jump(OpCode::Jump);
+                state.current().loops.last_mut().unwrap().breaks.push(jump);
+            }
+            Statement::Continue(span) => {
+                state.location = *span;
+                let innermost = state.current().loops.last().unwrap();
+                let (locals, target) = (innermost.locals, innermost.continue_target);
+                state.discard_locals(locals);
+                match target {
+                    Some(start) => state.emit_loop(start)?,
+                    None => {
+                        let jump = state.emit_jump(OpCode::Jump);
+                        state
+                            .current()
+                            .loops
+                            .last_mut()
+                            .unwrap()
+                            .continues
+                            .push(jump);
+                    }
+                }
+            }
+            Statement::Function {
+                name,
+                parameters,
+                return_type,
+                body,
+                span,
+            } => {
+                state.location = *span;
+                // A local function takes its slot before the body is
+                // compiled so it can call itself.
+                let local = !state.scopes.is_empty();
+                if local {
+                    state.add_local()?;
+                }
+                self.function(state, name, parameters, *return_type, body)?;
+                if !local {
+                    state.emit_constant(OpCode::DefineGlobal, Constant::String(name.clone()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stores the value on top of the stack into a new variable.
+    fn define(&self, state: &mut TempState, name: &str) -> CompileResult {
+        if state.scopes.is_empty() {
+            state.emit_constant(OpCode::DefineGlobal, Constant::String(name.to_owned()))
+        } else {
+            state.add_local()
+        }
+    }
+
+    fn default_value(state: &mut TempState, data_type: DataType) -> CompileResult {
+        match data_type {
+            DataType::Int => state.emit_constant(OpCode::Constant, Constant::Int(0))?,
+            DataType::Float => state.emit_constant(OpCode::Constant, Constant::Float(0.0))?,
+            DataType::String => {
+                state.emit_constant(OpCode::Constant, Constant::String(String::new()))?
+            }
+            DataType::Char => state.emit_constant(OpCode::Constant, Constant::Char('\0'))?,
+            DataType::Bool => state.emit(OpCode::False),
+            DataType::Void => state.emit(OpCode::Nil),
+        }
+        Ok(())
+    }
+
+    fn while_statement(
+        &self,
+        state: &mut TempState,
+        condition: &Expresion,
+        body: &Statement,
+        span: Span,
+    ) -> CompileResult {
+        let start = state.chunk().code.len();
+        state.location = span;
+        self.expression(state, condition)?;
+        state.location = span;
+        let exit = state.emit_jump(OpCode::JumpIfFalse);
+
+        let locals = state.current().locals.len();
+        state.current().loops.push(Loop {
+            continue_target: Some(start),
+            locals,
+            breaks: vec![],
+            continues: vec![],
+        });
+        self.statement(state, body)?;
+        let finished = state.current().loops.pop().unwrap();
+
+        state.location = span;
+        state.emit_loop(start)?;
+        state.patch_jump(exit)?;
+        for jump in finished.breaks {
+            state.patch_jump(jump)?;
+        }
+        Ok(())
+    }
+
+    fn for_loop(
+        &self,
+        state: &mut TempState,
+        condition: Option<&Expresion>,
+        increment: Option<&Expresion>,
+        body: &Statement,
+        span: Span,
+    ) -> CompileResult {
+        let start = state.chunk().code.len();
+        let exit = match condition {
+            Some(condition) => {
+                state.location = span;
+                self.expression(state, condition)?;
+                state.location = span;
+                Some(state.emit_jump(OpCode::JumpIfFalse))
+            }
+            None => None,
+        };
+
+        let locals = state.current().locals.len();
+        state.current().loops.push(Loop {
+            continue_target: None,
+            locals,
+            breaks: vec![],
+            continues: vec![],
+        });
+        self.statement(state, body)?;
+        let finished = state.current().loops.pop().unwrap();
+
+        for jump in finished.continues {
+            state.patch_jump(jump)?;
+        }
+        if let Some(increment) = increment {
+            self.expression(state, increment)?;
+            state.emit(OpCode::Pop);
+        }
+        state.location = span;
+        state.emit_loop(start)?;
+        if let Some(exit) = exit {
+            state.patch_jump(exit)?;
+        }
+        for jump in finished.breaks {
+            state.patch_jump(jump)?;
+        }
+        Ok(())
+    }
+
+    /// Compiles the function in its own frame and emits the closure creating
+    /// it at runtime.
+    fn function(
+        &self,
+        state: &mut TempState,
+        name: &str,
+        parameters: &[Parameter],
+        return_type: Option<DataType>,
+        body: &[Statement],
+    ) -> CompileResult {
+        if parameters.len() > MAX_ARGUMENTS {
+            return Err(state.error("Too many parameters."));
+        }
+
+        let location = state.location;
+        state
+            .functions
+            .push(FunctionState::new(name, parameters.len(), return_type));
+        state.begin_scope();
+
+        for (index, parameter) in parameters.iter().enumerate() {
+            state.add_local()?;
+            // Integers passed to `float` parameters are widened on entry.
+            if parameter.data_type == Some(DataType::Float) {
+                state.location = parameter.span;
+                let slot = index as u8 + 1;
+                state.emit_with_byte(OpCode::GetLocal, slot);
+                state.emit(OpCode::ToFloat);
+                state.emit_with_byte(OpCode::SetLocal, slot);
+                state.emit(OpCode::Pop);
+            }
+        }
+        for statement in body {
+            self.statement(state, statement)?;
+        }
+        state.emit(OpCode::Nil);
+        state.emit(OpCode::Return);
+
+        // The frame is discarded on return, the scope needs no cleanup.
+        state.scopes.pop();
+        let finished = state.functions.pop().unwrap();
+        let mut function = finished.function;
+        function.upvalue_count = finished.upvalues.len();
+
+        state.location = location;
+        state.emit_constant(OpCode::Closure, Constant::Function(Rc::new(function)))?;
+        for upvalue in finished.upvalues {
+            state.emit_byte(upvalue.is_local as u8);
+            state.emit_byte(upvalue.index);
+        }
+        Ok(())
+    }
+
+    fn target(&self, state: &mut TempState, name: &str, span: Span) -> CompileResult<Target> {
+        let binding = self.resolution.get(span);
+
+        let scope = match binding {
+            Some(Binding::Local { depth, .. }) => state
+                .scopes
+                .len()
+                .checked_sub(depth + 1)
+                .map(|index| state.scopes[index]),
+            _ => None,
+        };
+
+        match (binding, scope) {
+            (Some(Binding::Local { slot, .. }), Some(scope)) => {
+                let slot = scope.base + slot;
+                let current = state.functions.len() - 1;
+                if scope.function == current {
+                    Ok(Target::Local(slot as u8))
+                } else {
+                    Ok(Target::Upvalue(state.upvalue(
+                        current,
+                        scope.function,
+                        slot,
+                    )?))
+                }
+            }
+            // Unresolved names are looked up among the globals at runtime.
+            _ => Ok(Target::Global(
+                state.constant(Constant::String(name.to_owned()))?,
+            )),
+        }
+    }
+
+    fn expression(&self, state: &mut TempState, expression: &Expresion) -> CompileResult {
+        match expression {
+            Expresion::Unknown(literal, span) => {
+                state.location = *span;
+                return Err(state.error(&format!("Unknown expression '{}'.", literal)));
+            }
+            Expresion::Integer(value, span) => {
+                state.location = *span;
+                state.emit_constant(OpCode::Constant, Constant::Int(*value))?
+            }
+            Expresion::Float(value, span) => {
+                state.location = *span;
+                state.emit_constant(OpCode::Constant, Constant::Float(*value))?
+            }
+            Expresion::Str(value, span) => {
+                state.location = *span;
+                state.emit_constant(OpCode::Constant, Constant::String(value.clone()))?
+            }
+            Expresion::Char(value, span) => {
+                state.location = *span;
+                state.emit_constant(OpCode::Constant, Constant::Char(*value))?
+            }
+            Expresion::Bool(value, span) => {
+                state.location = *span;
+                state.emit(if *value { OpCode::True } else { OpCode::False })
+            }
+            Expresion::Nil(span) => {
+                state.location = *span;
+                state.emit(OpCode::Nil)
+            }
+            Expresion::Grouping(expression, _) => self.expression(state, expression)?,
+            Expresion::Variable(name, span) => {
+                state.location = *span;
+                self.target(state, name, *span)?.get(state);
+            }
+            Expresion::Assign(name, value, span) => {
+                self.expression(state, value)?;
+                state.location = *span;
+                if self.resolution.widens(*span) {
+                    state.emit(OpCode::ToFloat);
+                }
+                self.target(state, name, *span)?.set(state);
+            }
+            Expresion::Postfix(name, operator, span) => {
+                // Leaves the previous value below the updated one.
+                state.location = *span;
+                let target = self.target(state, name, *span)?;
+                target.get(state);
+                state.emit(OpCode::Dup);
+                state.emit_constant(OpCode::Constant, Constant::Int(1))?;
+                state.emit(OpCode::for_binary(&operator[..1]).unwrap());
+                target.set(state);
+                state.emit(OpCode::Pop);
+            }
+            Expresion::Ternary(condition, then_branch, else_branch, span) => {
+                self.expression(state, condition)?;
+                state.location = *span;
+                let else_jump = state.emit_jump(OpCode::JumpIfFalse);
+                self.expression(state, then_branch)?;
+                state.location = *span;
+                let end_jump = state.emit_jump(OpCode::Jump);
+                state.patch_jump(else_jump)?;
+                self.expression(state, else_branch)?;
+                state.patch_jump(end_jump)?;
+                if self.resolution.widens(*span) {
+                    state.location = *span;
+                    state.emit(OpCode::ToFloat);
+                }
+            }
+            Expresion::Unary(operator, right, span) => {
+                self.expression(state, right)?;
+                state.location = *span;
+                let op = OpCode::for_unary(operator)
+                    .ok_or_else(|| state.error(&format!("Unknown operator '{}'.", operator)))?;
+                state.emit(op);
+            }
+            Expresion::Binary(left, operator, right, span) => {
+                self.binary(state, left, operator, right, *span)?
+            }
+            Expresion::Call(callee, arguments, span) => {
+                self.expression(state, callee)?;
+                for argument in arguments {
+                    self.expression(state, argument)?;
+                }
+                state.location = *span;
+                if arguments.len() > MAX_ARGUMENTS {
+                    return Err(state.error("Too many arguments."));
+                }
+                state.emit_with_byte(OpCode::Call, arguments.len() as u8);
+            }
+        }
+        Ok(())
+    }
+
+    fn binary(
+        &self,
+        state: &mut TempState,
+        left: &Expresion,
+        operator: &str,
+        right: &Expresion,
+        span: Span,
+    ) -> CompileResult {
+        self.expression(state, left)?;
+
+        // Both sides of `&&` and `||` give a boolean, like the interpreter.
+        match operator {
+            "&&" => {
+                state.location = span;
+                let short_circuit = state.emit_jump(OpCode::JumpIfFalse);
+                self.expression(state, right)?;
+                state.location = span;
+                state.emit(OpCode::ToBool);
+                let end = state.emit_jump(OpCode::Jump);
+                state.patch_jump(short_circuit)?;
+                state.emit(OpCode::False);
+                state.patch_jump(end)?;
+            }
+            "||" => {
+                state.location = span;
+                let evaluate_right = state.emit_jump(OpCode::JumpIfFalse);
+                state.emit(OpCode::True);
+                let end = state.emit_jump(OpCode::Jump);
+                state.patch_jump(evaluate_right)?;
+                self.expression(state, right)?;
+                state.location = span;
+                state.emit(OpCode::ToBool);
+                state.patch_jump(end)?;
+            }
+            _ => {
+                self.expression(state, right)?;
+                state.location = span;
+                let op = OpCode::for_binary(operator)
+                    .ok_or_else(|| state.error(&format!("Unknown operator '{}'.", operator)))?;
+                state.emit(op);
+            }
+        }
+        Ok(())
+    }
+}
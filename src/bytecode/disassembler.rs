@@ -0,0 +1,158 @@
+#![allow(dead_code)]
+
+use std::fmt::Write;
+
+use super::{
+    chunk::{Chunk, CompiledFunction, Constant},
+    opcode::OpCode,
+};
+
+/// Prints compiled functions one instruction per line:
+///
+/// ```text
+/// == script ==
+///           ; print 1 + 2;
+/// 0000    1 Constant         0 1
+/// 0003    | Constant         1 2
+/// 0006    | Add
+/// ```
+///
+/// Source lines are shown above their first instruction when the source
+/// text is known.
+#[derive(Debug, Clone, Default)]
+pub struct Disassembler<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Disassembler<'a> {
+    pub fn new() -> Self {
+        Self { lines: vec![] }
+    }
+
+    pub fn with_source(source: &'a str) -> Self {
+        Self {
+            lines: source.lines().collect(),
+        }
+    }
+
+    /// Disassembles the function followed by the functions it defines.
+    pub fn disassemble(&self, function: &CompiledFunction) -> String {
+        let mut output = String::new();
+        self.function(function, &mut output);
+        output
+    }
+
+    fn function(&self, function: &CompiledFunction, output: &mut String) {
+        let _ = writeln!(output, "== {} ==", function.name);
+
+        let chunk = &function.chunk;
+        let mut offset = 0;
+        while offset < chunk.code.len() {
+            offset = self.instruction(chunk, offset, output);
+        }
+
+        for constant in &chunk.constants {
+            if let Constant::Function(function) = constant {
+                output.push('\n');
+                self.function(function, output);
+            }
+        }
+    }
+
+    /// Writes the instruction at `offset`, returns the offset of the next.
+    pub fn instruction(&self, chunk: &Chunk, offset: usize, output: &mut String) -> usize {
+        let line = chunk.span_at(offset).line;
+        let new_line = offset == 0 || chunk.span_at(offset - 1).line != line;
+
+        if new_line {
+            if let Some(text) = line.checked_sub(1).and_then(|i| self.lines.get(i)) {
+                let _ = writeln!(output, "          ; {}", text.trim());
+            }
+            let _ = write!(output, "{:04} {:>4} ", offset, line);
+        } else {
+            let _ = write!(output, "{:04}    | ", offset);
+        }
+
+        let byte = chunk.code[offset];
+        let Some(op) = OpCode::from_byte(byte) else {
+            let _ = writeln!(output, "<invalid {}>", byte);
+            return offset + 1;
+        };
+        let name = format!("{:?}", op);
+        let operands = offset + 1;
+
+        match op {
+            OpCode::Constant | OpCode::GetGlobal | OpCode::DefineGlobal | OpCode::SetGlobal => {
+                let index = chunk.read_u16(operands) as usize;
+                let _ = writeln!(
+                    output,
+                    "{:<16} {:>4} {}",
+                    name,
+                    index,
+                    Self::constant(chunk, index)
+                );
+            }
+            OpCode::GetLocal
+            | OpCode::SetLocal
+            | OpCode::GetUpvalue
+            | OpCode::SetUpvalue
+            | OpCode::Call => {
+                let _ = writeln!(output, "{:<16} {:>4}", name, chunk.code[operands]);
+            }
+            OpCode::Jump | OpCode::JumpIfFalse | OpCode::Loop => {
+                let distance = chunk.read_u16(operands) as usize;
+                let next = operands + 2;
+                let target = if op == OpCode::Loop {
+                    next.wrapping_sub(distance)
+                } else {
+                    next + distance
+                };
+                let _ = writeln!(output, "{:<16} {:>4} -> {:04}", name, distance, target);
+            }
+            OpCode::Closure => {
+                let index = chunk.read_u16(operands) as usize;
+                let _ = writeln!(
+                    output,
+                    "{:<16} {:>4} {}",
+                    name,
+                    index,
+                    Self::constant(chunk, index)
+                );
+
+                let upvalues = match chunk.constants.get(index) {
+                    Some(Constant::Function(function)) => function.upvalue_count,
+                    _ => 0,
+                };
+                let mut position = operands + 2;
+                for _ in 0..upvalues {
+                    let kind = if chunk.code[position] != 0 {
+                        "local"
+                    } else {
+                        "upvalue"
+                    };
+                    let _ = writeln!(
+                        output,
+                        "{:04}    |   {} {}",
+                        position,
+                        kind,
+                        chunk.code[position + 1]
+                    );
+                    position += 2;
+                }
+                return position;
+            }
+            _ => {
+                let _ = writeln!(output, "{}", name);
+            }
+        }
+
+        operands + op.operand_bytes()
+    }
+
+    fn constant(chunk: &Chunk, index: usize) -> String {
+        chunk
+            .constants
+            .get(index)
+            .map_or_else(|| "<missing>".to_owned(), Constant::to_string)
+    }
+}
@@ -0,0 +1,72 @@
+#![allow(unused_imports, dead_code)]
+
+use super::disassembler::Disassembler;
+use crate::test_support::compile;
+
+fn disassemble(input: &str, with_source: bool) -> String {
+    let function = compile(input);
+
+    let disassembler = if with_source {
+        Disassembler::with_source(input)
+    } else {
+        Disassembler::new()
+    };
+    disassembler.disassemble(&function)
+}
+
+#[test]
+fn instructions_with_source_lines() {
+    let output = disassemble("var x = 1;\nprint x + 2.5;", true);
+
+    assert_eq!(
+        output,
+        "\
+== script ==
+          ; var x = 1;
+0000    1 Constant            0 1
+0003    | DefineGlobal        1 \"x\"
+          ; print x + 2.5;
+0006    2 GetGlobal           1 \"x\"
+0009    | Constant            2 2.5
+0012    | Add
+0013    | Print
+0014    | Nil
+0015    | Return
+"
+    );
+}
+
+#[test]
+fn literals_keep_their_line() {
+    let output = disassemble("var x = 1;\n\n\nprint \"x\";\nprint 2;", true);
+
+    assert!(output.contains(
+        "          ; print \"x\";\n0006    4 Constant            1 \"x\"\n0009    | Print\n"
+    ));
+    assert!(output
+        .contains("          ; print 2;\n0010    5 Constant            2 2\n0013    | Print\n"));
+}
+
+#[test]
+fn jumps_show_their_target() {
+    let output = disassemble("while (false) print 1;", false);
+
+    assert!(output.contains("0001    | JumpIfFalse         7 -> 0011\n"));
+    assert!(output.contains("0008    | Loop               11 -> 0000\n"));
+}
+
+#[test]
+fn nested_functions_follow_their_parent() {
+    let output = disassemble(
+        "fun outer() { var a = 1; fun inner() { return a; } return inner; }",
+        false,
+    );
+
+    let sections = output
+        .lines()
+        .filter(|line| line.starts_with("=="))
+        .collect::<Vec<_>>();
+    assert_eq!(sections, ["== script ==", "== outer ==", "== inner =="]);
+    assert!(output.contains("0003    | Closure             1 <fn inner>\n0006    |   local 1\n"));
+    assert!(output.contains("GetUpvalue          0\n"));
+}
@@ -0,0 +1,413 @@
+//! The `.loxc` file format, compiled programs that run without the source.
+//!
+//! All integers are little-endian, strings are a `u32` byte length followed
+//! by UTF-8 text.
+//!
+//! ```text
+//! header    "LOXC" magic, u16 version, string source name ("" if unknown)
+//! function  string name, u32 arity, u32 upvalue count,
+//!           u32 code length, code bytes,
+//!           u32 constant count, constants,
+//!           u32 span count, spans as u32 offset, start, end, line, column
+//! constant  u8 tag then 0: i64 int, 1: f64 float, 2: string, 3: u32 char,
+//!           4: function
+//! ```
+//!
+//! The header is followed by the top-level function. Spans form the line
+//! table, each applies from its offset until the next one.
+
+#![allow(dead_code)]
+
+use std::{error::Error, fmt, rc::Rc};
+
+use super::{
+    chunk::{Chunk, CompiledFunction, Constant},
+    opcode::OpCode,
+};
+use crate::types::span::Span;
+
+pub const MAGIC: &[u8; 4] = b"LOXC";
+pub const VERSION: u16 = 1;
+pub const EXTENSION: &str = "loxc";
+
+const TAG_INT: u8 = 0;
+const TAG_FLOAT: u8 = 1;
+const TAG_STRING: u8 = 2;
+const TAG_CHAR: u8 = 3;
+const TAG_FUNCTION: u8 = 4;
+
+/// A compiled program as stored on disk.
+#[derive(Debug, Clone)]
+pub struct BytecodeFile {
+    /// Name of the source file it was compiled from, for diagnostics.
+    pub source_name: Option<String>,
+    pub script: CompiledFunction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    NotBytecode,
+    UnsupportedVersion(u16),
+    UnexpectedEnd,
+    Malformed(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::NotBytecode => write!(f, "not a compiled program"),
+            LoadError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported bytecode version {}, expected {}",
+                version, VERSION
+            ),
+            LoadError::UnexpectedEnd => write!(f, "unexpected end of file"),
+            LoadError::Malformed(message) => write!(f, "malformed bytecode: {}", message),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+type LoadResult<T> = Result<T, LoadError>;
+
+impl BytecodeFile {
+    pub fn new(source_name: Option<String>, script: CompiledFunction) -> Self {
+        Self {
+            source_name,
+            script,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer { bytes: vec![] };
+
+        writer.bytes.extend_from_slice(MAGIC);
+        writer.bytes.extend_from_slice(&VERSION.to_le_bytes());
+        writer.string(self.source_name.as_deref().unwrap_or(""));
+        writer.function(&self.script);
+
+        writer.bytes
+    }
+
+    /// Reads a program, checking that its instructions are well formed.
+    pub fn decode(bytes: &[u8]) -> LoadResult<Self> {
+        let mut reader = Reader { bytes, position: 0 };
+
+        if !bytes.starts_with(MAGIC) {
+            return Err(LoadError::NotBytecode);
+        }
+        reader.position = MAGIC.len();
+        let version = reader.u16()?;
+        if version != VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
+        }
+
+        let source_name = Some(reader.string()?).filter(|name| !name.is_empty());
+        let script = reader.function()?;
+        if script.arity != 0 || script.upvalue_count != 0 {
+            return Err(LoadError::Malformed(
+                "script with parameters or captured variables".to_owned(),
+            ));
+        }
+        if reader.position != bytes.len() {
+            return Err(LoadError::Malformed("trailing bytes".to_owned()));
+        }
+
+        Ok(Self::new(source_name, script))
+    }
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u32(&mut self, value: usize) {
+        self.bytes.extend_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len());
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn function(&mut self, function: &CompiledFunction) {
+        self.string(&function.name);
+        self.u32(function.arity);
+        self.u32(function.upvalue_count);
+
+        let chunk = &function.chunk;
+        self.u32(chunk.code.len());
+        self.bytes.extend_from_slice(&chunk.code);
+
+        self.u32(chunk.constants.len());
+        for constant in &chunk.constants {
+            self.constant(constant);
+        }
+
+        self.u32(chunk.spans().len());
+        for (offset, span) in chunk.spans() {
+            for value in [*offset, span.start, span.end, span.line, span.column] {
+                self.u32(value);
+            }
+        }
+    }
+
+    fn constant(&mut self, constant: &Constant) {
+        match constant {
+            Constant::Int(value) => {
+                self.bytes.push(TAG_INT);
+                self.bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            Constant::Float(value) => {
+                self.bytes.push(TAG_FLOAT);
+                self.bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+            }
+            Constant::String(value) => {
+                self.bytes.push(TAG_STRING);
+                self.string(value);
+            }
+            Constant::Char(value) => {
+                self.bytes.push(TAG_CHAR);
+                self.u32(*value as usize);
+            }
+            Constant::Function(function) => {
+                self.bytes.push(TAG_FUNCTION);
+                self.function(function);
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take<const N: usize>(&mut self) -> LoadResult<[u8; N]> {
+        let bytes = self.slice(N)?;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn slice(&mut self, length: usize) -> LoadResult<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(LoadError::UnexpectedEnd)?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> LoadResult<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> LoadResult<u16> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> LoadResult<usize> {
+        Ok(u32::from_le_bytes(self.take()?) as usize)
+    }
+
+    fn string(&mut self) -> LoadResult<String> {
+        let length = self.u32()?;
+        let bytes = self.slice(length)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|_| LoadError::Malformed("string is not valid UTF-8".to_owned()))
+    }
+
+    fn function(&mut self) -> LoadResult<CompiledFunction> {
+        let name = self.string()?;
+        let mut function = CompiledFunction::new(&name, self.u32()?);
+        function.upvalue_count = self.u32()?;
+
+        let length = self.u32()?;
+        let mut chunk = Chunk::new();
+        chunk.code = self.slice(length)?.to_vec();
+
+        let count = self.u32()?;
+        for _ in 0..count {
+            let constant = self.constant()?;
+            chunk.constants.push(constant);
+        }
+
+        let mut spans = vec![];
+        let count = self.u32()?;
+        for _ in 0..count {
+            let offset = self.u32()?;
+            let (start, end, line, column) = (self.u32()?, self.u32()?, self.u32()?, self.u32()?);
+            spans.push((offset, Span::new(start, end, line, column)));
+        }
+        if !spans.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+            return Err(LoadError::Malformed("line table out of order".to_owned()));
+        }
+        chunk.set_spans(spans);
+
+        function.chunk = chunk;
+        verify(&function)?;
+        Ok(function)
+    }
+
+    fn constant(&mut self) -> LoadResult<Constant> {
+        let constant = match self.u8()? {
+            TAG_INT => Constant::Int(i64::from_le_bytes(self.take()?)),
+            TAG_FLOAT => Constant::Float(f64::from_bits(u64::from_le_bytes(self.take()?))),
+            TAG_STRING => Constant::String(self.string()?),
+            TAG_CHAR => {
+                let value = self.u32()? as u32;
+                let value = char::from_u32(value)
+                    .ok_or_else(|| LoadError::Malformed(format!("invalid character {}", value)))?;
+                Constant::Char(value)
+            }
+            TAG_FUNCTION => Constant::Function(Rc::new(self.function()?)),
+            tag => {
+                return Err(LoadError::Malformed(format!(
+                    "unknown constant tag {}",
+                    tag
+                )))
+            }
+        };
+        Ok(constant)
+    }
+}
+
+/// Checks that every instruction of the function is complete, refers to
+/// existing constants and jumps to the start of an instruction, and that no
+/// path through the code pops more values than the stack holds or reads a
+/// slot above its top, so the virtual machine never reads past either.
+fn verify(function: &CompiledFunction) -> LoadResult<()> {
+    let chunk = &function.chunk;
+    let code = &chunk.code;
+    let malformed =
+        |message: String| LoadError::Malformed(format!("{} in '{}'", message, function.name));
+
+    // Offset of the next instruction, for each offset an instruction starts at.
+    let mut ends = vec![None; code.len()];
+    let mut offset = 0;
+    let mut last = None;
+    while offset < code.len() {
+        let op = OpCode::from_byte(code[offset])
+            .ok_or_else(|| malformed(format!("invalid opcode {}", code[offset])))?;
+        let operands = offset + 1;
+        let mut next = operands + op.operand_bytes();
+        if next > code.len() {
+            return Err(malformed(format!("incomplete {:?} at {:04}", op, offset)));
+        }
+
+        match op {
+            OpCode::Constant | OpCode::GetGlobal | OpCode::DefineGlobal | OpCode::SetGlobal => {
+                let constant = chunk.constants.get(chunk.read_u16(operands) as usize);
+                let valid = match constant {
+                    Some(Constant::String(_)) => true,
+                    // Functions capturing variables need the `Closure` that
+                    // captures them.
+                    Some(Constant::Function(function)) if function.upvalue_count > 0 => false,
+                    Some(_) => op == OpCode::Constant,
+                    None => false,
+                };
+                if !valid {
+                    return Err(malformed(format!(
+                        "bad constant for {:?} at {:04}",
+                        op, offset
+                    )));
+                }
+            }
+            OpCode::Jump | OpCode::JumpIfFalse | OpCode::Loop => {
+                let distance = chunk.read_u16(operands) as usize;
+                let target = if op == OpCode::Loop {
+                    next.checked_sub(distance)
+                } else {
+                    Some(next + distance).filter(|target| *target < code.len())
+                };
+                if target.is_none() {
+                    return Err(malformed(format!("jump out of code at {:04}", offset)));
+                }
+            }
+            OpCode::Closure => {
+                let upvalues = match chunk.constants.get(chunk.read_u16(operands) as usize) {
+                    Some(Constant::Function(function)) => function.upvalue_count,
+                    _ => return Err(malformed(format!("bad closure at {:04}", offset))),
+                };
+                next += 2 * upvalues;
+                if next > code.len() {
+                    return Err(malformed(format!("incomplete Closure at {:04}", offset)));
+                }
+                let captures_missing_upvalue = code[operands + 2..next]
+                    .chunks(2)
+                    .any(|pair| pair[0] == 0 && pair[1] as usize >= function.upvalue_count);
+                if captures_missing_upvalue {
+                    return Err(malformed(format!("bad capture at {:04}", offset)));
+                }
+            }
+            OpCode::GetUpvalue | OpCode::SetUpvalue
+                if code[operands] as usize >= function.upvalue_count =>
+            {
+                return Err(malformed(format!("bad upvalue at {:04}", offset)));
+            }
+            _ => {}
+        }
+        ends[offset] = Some(next);
+        offset = next;
+        last = Some(op);
+    }
+
+    if last != Some(OpCode::Return) {
+        return Err(malformed("missing final Return".to_owned()));
+    }
+
+    // Follows every path with the height of the stack above the frame's
+    // base, which holds the callee and its arguments on entry.
+    let mut heights = vec![None; code.len()];
+    let mut pending = vec![(0, function.arity + 1)];
+    while let Some((offset, height)) = pending.pop() {
+        match heights[offset] {
+            Some(known) if known == height => continue,
+            Some(_) => return Err(malformed(format!("uneven stack at {:04}", offset))),
+            None => heights[offset] = Some(height),
+        }
+
+        let op = OpCode::from_byte(code[offset]).unwrap();
+        let operands = offset + 1;
+        let next = ends[offset].unwrap();
+        let (pops, pushes) = op.stack_effect(code.get(operands).copied().unwrap_or(0) as usize);
+        if pops > height {
+            return Err(malformed(format!("stack underflow at {:04}", offset)));
+        }
+
+        let slot_missing = match op {
+            OpCode::GetLocal | OpCode::SetLocal => code[operands] as usize >= height,
+            OpCode::Closure => code[operands + 2..next]
+                .chunks(2)
+                .any(|pair| pair[0] != 0 && pair[1] as usize >= height),
+            _ => false,
+        };
+        if slot_missing {
+            return Err(malformed(format!("bad local slot at {:04}", offset)));
+        }
+
+        let height = height - pops + pushes;
+        let target = match op {
+            OpCode::Jump | OpCode::JumpIfFalse => Some(next + chunk.read_u16(operands) as usize),
+            OpCode::Loop => Some(next - chunk.read_u16(operands) as usize),
+            _ => None,
+        };
+        if let Some(target) = target {
+            if ends[target].is_none() {
+                return Err(malformed(format!(
+                    "jump into an instruction at {:04}",
+                    offset
+                )));
+            }
+            pending.push((target, height));
+        }
+        if !matches!(op, OpCode::Jump | OpCode::Loop | OpCode::Return) && next < code.len() {
+            pending.push((next, height));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,188 @@
+#![allow(unused_imports, dead_code)]
+
+use std::rc::Rc;
+
+use super::{
+    chunk::{CompiledFunction, Constant},
+    disassembler::Disassembler,
+    loxc::{BytecodeFile, LoadError, MAGIC, VERSION},
+    opcode::OpCode,
+    vm::Vm,
+};
+use crate::{interpreter::runtime_error::RuntimeError, test_support::compile, types::span::Span};
+
+fn run(function: CompiledFunction) -> Result<String, RuntimeError> {
+    let mut vm = Vm::new(Vec::new());
+    vm.run(function)?;
+
+    Ok(String::from_utf8(vm.into_output()).unwrap())
+}
+
+const PROGRAM: &str = r#"
+fun counter(step: float) {
+    var count = 0;
+    fun next() { count += step; return count; }
+    return next;
+}
+var next = counter(1);
+next();
+print next();
+print "s" + 'c' + "\u{e9}";
+print 1 / 0;
+"#;
+
+#[test]
+fn round_trips_programs() {
+    let function = compile(PROGRAM);
+    let file = BytecodeFile::new(Some("counter.lox".to_owned()), function.clone());
+
+    let loaded = BytecodeFile::decode(&file.encode()).unwrap();
+
+    assert_eq!(loaded.source_name.as_deref(), Some("counter.lox"));
+    assert_eq!(
+        Disassembler::new().disassemble(&loaded.script),
+        Disassembler::new().disassemble(&function)
+    );
+    assert_eq!(loaded.script.chunk.spans(), function.chunk.spans());
+    assert_eq!(run(loaded.script), run(function));
+}
+
+#[test]
+fn runtime_errors_keep_their_location() {
+    let file = BytecodeFile::new(None, compile(PROGRAM));
+    let loaded = BytecodeFile::decode(&file.encode()).unwrap();
+
+    let error = run(loaded.script).unwrap_err();
+    assert_eq!(error.message, "Division by zero.");
+    assert_eq!((error.span.line, error.span.column), (11, 9));
+    assert_eq!(loaded.source_name, None);
+}
+
+#[test]
+fn rejects_other_files() {
+    assert_eq!(
+        BytecodeFile::decode(b"print 1;").unwrap_err(),
+        LoadError::NotBytecode
+    );
+
+    let mut bytes = BytecodeFile::new(None, compile("print 1;")).encode();
+    bytes[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&(VERSION + 1).to_le_bytes());
+    assert_eq!(
+        BytecodeFile::decode(&bytes).unwrap_err(),
+        LoadError::UnsupportedVersion(VERSION + 1)
+    );
+}
+
+#[test]
+fn rejects_damaged_files() {
+    let bytes = BytecodeFile::new(None, compile("print 1;")).encode();
+
+    assert_eq!(
+        BytecodeFile::decode(&bytes[..bytes.len() - 1]).unwrap_err(),
+        LoadError::UnexpectedEnd
+    );
+
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert!(matches!(
+        BytecodeFile::decode(&extended),
+        Err(LoadError::Malformed(_))
+    ));
+
+    // Code of the script starts after the header, its name, arity, upvalue
+    // count and code length.
+    let code = MAGIC.len() + 2 + 4 + 4 + "script".len() + 4 + 4 + 4;
+    assert_eq!(bytes[code], OpCode::Constant as u8);
+
+    let mut invalid_opcode = bytes.clone();
+    invalid_opcode[code] = 0xff;
+    assert!(matches!(
+        BytecodeFile::decode(&invalid_opcode),
+        Err(LoadError::Malformed(message)) if message.contains("invalid opcode 255")
+    ));
+
+    let mut missing_constant = bytes.clone();
+    missing_constant[code + 2] = 9;
+    assert!(matches!(
+        BytecodeFile::decode(&missing_constant),
+        Err(LoadError::Malformed(message)) if message.contains("bad constant")
+    ));
+
+    let script = |code: &[OpCode], operands: &[(usize, u8)]| {
+        let mut function = CompiledFunction::new("script", 0);
+        function.chunk.add_constant(Constant::Int(1));
+        for op in code {
+            function.chunk.write_op(*op, Span::default());
+        }
+        for (offset, byte) in operands {
+            function.chunk.code.insert(*offset, *byte);
+        }
+        BytecodeFile::decode(&BytecodeFile::new(None, function).encode())
+    };
+    let rejected = |result: Result<BytecodeFile, LoadError>, problem: &str| {
+        assert!(
+            matches!(&result, Err(LoadError::Malformed(message)) if message.contains(problem)),
+            "{:?}",
+            result.map(|file| file.script.chunk.code)
+        );
+    };
+
+    assert!(script(&[OpCode::Nil, OpCode::Return], &[]).is_ok());
+    rejected(
+        script(&[OpCode::Pop, OpCode::Pop, OpCode::Return], &[]),
+        "stack underflow at 0001",
+    );
+    rejected(
+        script(&[OpCode::GetLocal, OpCode::Return], &[(1, 200)]),
+        "bad local slot at 0000",
+    );
+    // The jump lands on the second operand byte of the constant.
+    rejected(
+        script(
+            &[OpCode::Jump, OpCode::Constant, OpCode::Return],
+            &[(1, 0), (2, 1), (4, 0), (5, 0)],
+        ),
+        "jump into an instruction at 0000",
+    );
+    // Only one path pushes the nil before the return.
+    rejected(
+        script(
+            &[
+                OpCode::True,
+                OpCode::JumpIfFalse,
+                OpCode::Nil,
+                OpCode::Return,
+            ],
+            &[(2, 0), (3, 1)],
+        ),
+        "uneven stack at 0005",
+    );
+
+    // The virtual machine calls the script without arguments.
+    let mut with_parameters = CompiledFunction::new("script", 3);
+    for byte in [OpCode::GetLocal as u8, 2, OpCode::Return as u8] {
+        with_parameters.chunk.write(byte, Span::default());
+    }
+    rejected(
+        BytecodeFile::decode(&BytecodeFile::new(None, with_parameters).encode()),
+        "script with parameters",
+    );
+
+    // A `Constant` would load the function without its captured variable.
+    let mut capturing = CompiledFunction::new("inner", 0);
+    capturing.upvalue_count = 1;
+    for byte in [OpCode::GetUpvalue as u8, 0, OpCode::Return as u8] {
+        capturing.chunk.write(byte, Span::default());
+    }
+    let mut function = CompiledFunction::new("script", 0);
+    let index = function
+        .chunk
+        .add_constant(Constant::Function(Rc::new(capturing)));
+    for byte in [OpCode::Constant as u8, 0, index as u8, OpCode::Return as u8] {
+        function.chunk.write(byte, Span::default());
+    }
+    rejected(
+        BytecodeFile::decode(&BytecodeFile::new(None, function).encode()),
+        "bad constant for Constant at 0000",
+    );
+}
@@ -0,0 +1,14 @@
+pub mod chunk;
+pub mod compile_error;
+pub mod compiler;
+pub mod disassembler;
+#[cfg(test)]
+mod disassembler_test;
+pub mod loxc;
+#[cfg(test)]
+mod loxc_test;
+pub mod object;
+pub mod opcode;
+pub mod vm;
+#[cfg(test)]
+mod vm_test;
@@ -0,0 +1,21 @@
+#![allow(dead_code)]
+
+use std::{cell::RefCell, rc::Rc};
+
+use super::chunk::CompiledFunction;
+use crate::interpreter::value::Value;
+
+/// A compiled function along with the variables it captured.
+#[derive(Debug)]
+pub struct Closure {
+    pub function: Rc<CompiledFunction>,
+    pub upvalues: Vec<Rc<RefCell<Upvalue>>>,
+}
+
+/// A captured variable. It stays on the stack while its scope is running
+/// and moves into the upvalue once the scope ends.
+#[derive(Debug, Clone)]
+pub enum Upvalue {
+    Open(usize),
+    Closed(Value),
+}
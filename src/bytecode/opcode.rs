@@ -0,0 +1,258 @@
+#![allow(dead_code)]
+
+/// Instructions of the virtual machine. Operands follow the opcode byte,
+/// constant and jump operands are two bytes in big-endian order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum OpCode {
+    /// Pushes constant `u16`.
+    Constant,
+    Nil,
+    True,
+    False,
+    Pop,
+    /// Pushes a copy of the top of the stack.
+    Dup,
+
+    /// Pushes frame slot `u8`.
+    GetLocal,
+    /// Stores the top of the stack into frame slot `u8`, leaving it there.
+    SetLocal,
+    /// Pushes the global named by constant `u16`.
+    GetGlobal,
+    /// Pops a value into a new global named by constant `u16`.
+    DefineGlobal,
+    /// Stores the top of the stack into the global named by constant `u16`.
+    SetGlobal,
+    /// Pushes captured variable `u8` of the running closure.
+    GetUpvalue,
+    /// Stores the top of the stack into captured variable `u8`.
+    SetUpvalue,
+
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Power,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+
+    Not,
+    Negate,
+    BitNot,
+    /// Replaces the top of the stack by its truthiness.
+    ToBool,
+    /// Widens an integer on top of the stack for `float` declarations.
+    ToFloat,
+
+    Print,
+    /// Jumps forward by `u16` bytes.
+    Jump,
+    /// Pops the condition and jumps forward by `u16` bytes when it is falsy.
+    JumpIfFalse,
+    /// Jumps backward by `u16` bytes.
+    Loop,
+    /// Calls the value below `u8` arguments.
+    Call,
+    /// Wraps function constant `u16` in a closure, followed by an
+    /// `(is_local: u8, index: u8)` pair for each variable it captures.
+    Closure,
+    /// Moves the variable on top of the stack to the heap and pops it.
+    CloseUpvalue,
+    Return,
+}
+
+impl OpCode {
+    /// Every opcode, indexed by its byte.
+    const ALL: [OpCode; 43] = [
+        OpCode::Constant,
+        OpCode::Nil,
+        OpCode::True,
+        OpCode::False,
+        OpCode::Pop,
+        OpCode::Dup,
+        OpCode::GetLocal,
+        OpCode::SetLocal,
+        OpCode::GetGlobal,
+        OpCode::DefineGlobal,
+        OpCode::SetGlobal,
+        OpCode::GetUpvalue,
+        OpCode::SetUpvalue,
+        OpCode::Equal,
+        OpCode::NotEqual,
+        OpCode::Greater,
+        OpCode::GreaterEqual,
+        OpCode::Less,
+        OpCode::LessEqual,
+        OpCode::Add,
+        OpCode::Subtract,
+        OpCode::Multiply,
+        OpCode::Divide,
+        OpCode::Modulo,
+        OpCode::Power,
+        OpCode::BitAnd,
+        OpCode::BitOr,
+        OpCode::BitXor,
+        OpCode::ShiftLeft,
+        OpCode::ShiftRight,
+        OpCode::Not,
+        OpCode::Negate,
+        OpCode::BitNot,
+        OpCode::ToBool,
+        OpCode::ToFloat,
+        OpCode::Print,
+        OpCode::Jump,
+        OpCode::JumpIfFalse,
+        OpCode::Loop,
+        OpCode::Call,
+        OpCode::Closure,
+        OpCode::CloseUpvalue,
+        OpCode::Return,
+    ];
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        Self::ALL.get(byte as usize).copied()
+    }
+
+    /// Bytes of operands following the opcode. `Closure` is followed by two
+    /// more bytes for each variable its function captures.
+    pub fn operand_bytes(self) -> usize {
+        match self {
+            OpCode::Constant
+            | OpCode::GetGlobal
+            | OpCode::DefineGlobal
+            | OpCode::SetGlobal
+            | OpCode::Jump
+            | OpCode::JumpIfFalse
+            | OpCode::Loop
+            | OpCode::Closure => 2,
+            OpCode::GetLocal
+            | OpCode::SetLocal
+            | OpCode::GetUpvalue
+            | OpCode::SetUpvalue
+            | OpCode::Call => 1,
+            _ => 0,
+        }
+    }
+
+    /// Values the instruction pops and pushes, `arguments` is the operand of
+    /// a `Call`. `Return` leaves the frame, so it pushes nothing here.
+    pub fn stack_effect(self, arguments: usize) -> (usize, usize) {
+        match self {
+            OpCode::Constant
+            | OpCode::Nil
+            | OpCode::True
+            | OpCode::False
+            | OpCode::GetLocal
+            | OpCode::GetGlobal
+            | OpCode::GetUpvalue
+            | OpCode::Closure => (0, 1),
+            OpCode::Pop
+            | OpCode::DefineGlobal
+            | OpCode::Print
+            | OpCode::JumpIfFalse
+            | OpCode::CloseUpvalue
+            | OpCode::Return => (1, 0),
+            OpCode::Dup => (1, 2),
+            OpCode::SetLocal
+            | OpCode::SetGlobal
+            | OpCode::SetUpvalue
+            | OpCode::Not
+            | OpCode::Negate
+            | OpCode::BitNot
+            | OpCode::ToBool
+            | OpCode::ToFloat => (1, 1),
+            OpCode::Equal
+            | OpCode::NotEqual
+            | OpCode::Greater
+            | OpCode::GreaterEqual
+            | OpCode::Less
+            | OpCode::LessEqual
+            | OpCode::Add
+            | OpCode::Subtract
+            | OpCode::Multiply
+            | OpCode::Divide
+            | OpCode::Modulo
+            | OpCode::Power
+            | OpCode::BitAnd
+            | OpCode::BitOr
+            | OpCode::BitXor
+            | OpCode::ShiftLeft
+            | OpCode::ShiftRight => (2, 1),
+            OpCode::Jump | OpCode::Loop => (0, 0),
+            OpCode::Call => (arguments + 1, 1),
+        }
+    }
+
+    /// Opcode of a binary operator, `&&` and `||` compile to jumps instead.
+    pub fn for_binary(operator: &str) -> Option<Self> {
+        let op = match operator {
+            "==" => OpCode::Equal,
+            "!=" => OpCode::NotEqual,
+            ">" => OpCode::Greater,
+            ">=" => OpCode::GreaterEqual,
+            "<" => OpCode::Less,
+            "<=" => OpCode::LessEqual,
+            "+" => OpCode::Add,
+            "-" => OpCode::Subtract,
+            "*" => OpCode::Multiply,
+            "/" => OpCode::Divide,
+            "%" => OpCode::Modulo,
+            "**" => OpCode::Power,
+            "&" => OpCode::BitAnd,
+            "|" => OpCode::BitOr,
+            "^" => OpCode::BitXor,
+            "<<" => OpCode::ShiftLeft,
+            ">>" => OpCode::ShiftRight,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    pub fn for_unary(operator: &str) -> Option<Self> {
+        match operator {
+            "!" => Some(OpCode::Not),
+            "-" => Some(OpCode::Negate),
+            "~" => Some(OpCode::BitNot),
+            _ => None,
+        }
+    }
+
+    /// Source operator of an arithmetic, comparison or unary opcode.
+    pub fn operator(self) -> Option<&'static str> {
+        let operator = match self {
+            OpCode::Equal => "==",
+            OpCode::NotEqual => "!=",
+            OpCode::Greater => ">",
+            OpCode::GreaterEqual => ">=",
+            OpCode::Less => "<",
+            OpCode::LessEqual => "<=",
+            OpCode::Add => "+",
+            OpCode::Subtract => "-",
+            OpCode::Multiply => "*",
+            OpCode::Divide => "/",
+            OpCode::Modulo => "%",
+            OpCode::Power => "**",
+            OpCode::BitAnd => "&",
+            OpCode::BitOr => "|",
+            OpCode::BitXor => "^",
+            OpCode::ShiftLeft => "<<",
+            OpCode::ShiftRight => ">>",
+            OpCode::Not => "!",
+            OpCode::Negate => "-",
+            OpCode::BitNot => "~",
+            _ => return None,
+        };
+        Some(operator)
+    }
+}
@@ -0,0 +1,401 @@
+#![allow(dead_code)]
+
+use std::{cell::RefCell, collections::HashMap, io::Write, rc::Rc};
+
+use super::{
+    chunk::{Chunk, CompiledFunction, Constant},
+    object::{Closure, Upvalue},
+    opcode::OpCode,
+};
+use crate::{
+    interpreter::{
+        evaluator::MAX_CALL_DEPTH, operators, runtime_error::RuntimeError, value::Value,
+    },
+    types::span::Span,
+};
+
+type VmResult<T = ()> = Result<T, RuntimeError>;
+
+/// A function being run, its locals start at `base` on the value stack.
+#[derive(Debug)]
+struct CallFrame {
+    closure: Rc<Closure>,
+    ip: usize,
+    base: usize,
+}
+
+impl CallFrame {
+    fn chunk(&self) -> &Chunk {
+        &self.closure.function.chunk
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let byte = self.closure.function.chunk.code[self.ip];
+        self.ip += 1;
+        byte
+    }
+
+    fn read_u16(&mut self) -> u16 {
+        let value = self.chunk().read_u16(self.ip);
+        self.ip += 2;
+        value
+    }
+
+    fn constant(&mut self) -> &Constant {
+        let index = self.read_u16() as usize;
+        &self.chunk().constants[index]
+    }
+
+    /// Name of a global, stored as a string constant.
+    fn name(&self, index: u16) -> &str {
+        match &self.chunk().constants[index as usize] {
+            Constant::String(name) => name,
+            _ => "",
+        }
+    }
+
+    /// Error located at the instruction starting at `offset`.
+    fn error(&self, message: &str, offset: usize) -> RuntimeError {
+        RuntimeError::new(message, self.chunk().span_at(offset))
+    }
+}
+
+/// Runs compiled functions on a value stack. Globals persist between runs.
+pub struct Vm<W: Write> {
+    stack: Vec<Value>,
+    /// Callers of the running function, innermost last.
+    frames: Vec<CallFrame>,
+    globals: HashMap<String, Value>,
+    /// Captured variables still living on the stack, by slot.
+    open_upvalues: Vec<Rc<RefCell<Upvalue>>>,
+    output: W,
+}
+
+impl<W: Write> Vm<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            stack: vec![],
+            frames: vec![],
+            globals: HashMap::new(),
+            open_upvalues: vec![],
+            output,
+        }
+    }
+
+    /// Runs the top-level function of a compiled program.
+    pub fn run(&mut self, function: CompiledFunction) -> VmResult {
+        let closure = Rc::new(Closure {
+            function: Rc::new(function),
+            upvalues: vec![],
+        });
+        self.stack.push(Value::Closure(closure.clone()));
+        let frame = CallFrame {
+            closure,
+            ip: 0,
+            base: 0,
+        };
+
+        let result = self.execute(frame);
+        if result.is_err() {
+            self.stack.clear();
+            self.frames.clear();
+            self.open_upvalues.clear();
+        }
+        result
+    }
+
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("value stack underflow")
+    }
+
+    fn peek(&self) -> &Value {
+        self.stack.last().expect("value stack underflow")
+    }
+
+    /// The dispatch loop. The running frame is kept out of `frames` so its
+    /// instruction pointer stays in a local.
+    fn execute(&mut self, mut frame: CallFrame) -> VmResult {
+        loop {
+            let start = frame.ip;
+            let byte = frame.read_byte();
+            let Some(op) = OpCode::from_byte(byte) else {
+                return Err(frame.error(&format!("Invalid opcode {}.", byte), start));
+            };
+
+            match op {
+                OpCode::Constant => {
+                    let value = match frame.constant() {
+                        Constant::Int(value) => Value::Int(*value),
+                        Constant::Float(value) => Value::Float(*value),
+                        Constant::String(value) => Value::String(value.clone()),
+                        Constant::Char(value) => Value::Char(*value),
+                        Constant::Function(function) => Value::Closure(Rc::new(Closure {
+                            function: function.clone(),
+                            upvalues: vec![],
+                        })),
+                    };
+                    self.stack.push(value);
+                }
+                OpCode::Nil => self.stack.push(Value::Nil),
+                OpCode::True => self.stack.push(Value::Bool(true)),
+                OpCode::False => self.stack.push(Value::Bool(false)),
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::Dup => self.stack.push(self.peek().clone()),
+
+                OpCode::GetLocal => {
+                    let slot = frame.base + frame.read_byte() as usize;
+                    self.stack.push(self.stack[slot].clone());
+                }
+                OpCode::SetLocal => {
+                    let slot = frame.base + frame.read_byte() as usize;
+                    self.stack[slot] = self.peek().clone();
+                }
+                OpCode::GetGlobal => {
+                    let index = frame.read_u16();
+                    let name = frame.name(index);
+                    match self.globals.get(name) {
+                        Some(value) => self.stack.push(value.clone()),
+                        None => return Err(Self::undefined(&frame, name, start)),
+                    }
+                }
+                OpCode::DefineGlobal => {
+                    let index = frame.read_u16();
+                    let name = frame.name(index).to_owned();
+                    let value = self.pop();
+                    self.globals.insert(name, value);
+                }
+                OpCode::SetGlobal => {
+                    let index = frame.read_u16();
+                    let name = frame.name(index);
+                    let value = self.peek().clone();
+                    match self.globals.get_mut(name) {
+                        Some(global) => *global = value,
+                        None => return Err(Self::undefined(&frame, name, start)),
+                    }
+                }
+                OpCode::GetUpvalue => {
+                    let index = frame.read_byte() as usize;
+                    let value = match &*frame.closure.upvalues[index].borrow() {
+                        Upvalue::Open(slot) => self.stack[*slot].clone(),
+                        Upvalue::Closed(value) => value.clone(),
+                    };
+                    self.stack.push(value);
+                }
+                OpCode::SetUpvalue => {
+                    let index = frame.read_byte() as usize;
+                    let value = self.peek().clone();
+                    match &mut *frame.closure.upvalues[index].borrow_mut() {
+                        Upvalue::Open(slot) => self.stack[*slot] = value,
+                        Upvalue::Closed(closed) => *closed = value,
+                    }
+                }
+
+                OpCode::Equal
+                | OpCode::NotEqual
+                | OpCode::Greater
+                | OpCode::GreaterEqual
+                | OpCode::Less
+                | OpCode::LessEqual
+                | OpCode::Add
+                | OpCode::Subtract
+                | OpCode::Multiply
+                | OpCode::Divide
+                | OpCode::Modulo
+                | OpCode::Power
+                | OpCode::BitAnd
+                | OpCode::BitOr
+                | OpCode::BitXor
+                | OpCode::ShiftLeft
+                | OpCode::ShiftRight => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let value = match Self::integer_fast_path(op, &left, &right) {
+                        Some(value) => value,
+                        None => operators::binary(left, op.operator().unwrap(), right)
+                            .map_err(|message| frame.error(&message, start))?,
+                    };
+                    self.stack.push(value);
+                }
+                OpCode::Not | OpCode::Negate | OpCode::BitNot => {
+                    let right = self.pop();
+                    let value = operators::unary(op.operator().unwrap(), right)
+                        .map_err(|message| frame.error(&message, start))?;
+                    self.stack.push(value);
+                }
+                OpCode::ToBool => {
+                    let value = self.pop();
+                    self.stack.push(Value::Bool(value.is_truthy()));
+                }
+                OpCode::ToFloat => {
+                    if let Some(Value::Int(value)) = self.stack.last() {
+                        let value = Value::Float(*value as f64);
+                        *self.stack.last_mut().unwrap() = value;
+                    }
+                }
+
+                OpCode::Print => {
+                    let value = self.pop();
+                    writeln!(self.output, "{}", value)
+                        .map_err(|e| RuntimeError::new(&e.to_string(), Span::default()))?;
+                }
+                OpCode::Jump => {
+                    let distance = frame.read_u16() as usize;
+                    frame.ip += distance;
+                }
+                OpCode::JumpIfFalse => {
+                    let distance = frame.read_u16() as usize;
+                    if !self.pop().is_truthy() {
+                        frame.ip += distance;
+                    }
+                }
+                OpCode::Loop => {
+                    let distance = frame.read_u16() as usize;
+                    frame.ip -= distance;
+                }
+                OpCode::Call => {
+                    let count = frame.read_byte() as usize;
+                    let base = self.stack.len() - count - 1;
+                    let callee = self
+                        .call(&self.stack[base], count)
+                        .map_err(|message| frame.error(&message, start))?;
+                    let callee = CallFrame {
+                        closure: callee,
+                        ip: 0,
+                        base,
+                    };
+                    self.frames.push(std::mem::replace(&mut frame, callee));
+                }
+                OpCode::Closure => {
+                    let function = match frame.constant() {
+                        Constant::Function(function) => function.clone(),
+                        other => {
+                            let message = format!("Cannot make a closure of {}.", other);
+                            return Err(frame.error(&message, start));
+                        }
+                    };
+                    let upvalues = (0..function.upvalue_count)
+                        .map(|_| {
+                            let is_local = frame.read_byte() != 0;
+                            let index = frame.read_byte() as usize;
+                            if is_local {
+                                self.capture(frame.base + index)
+                            } else {
+                                frame.closure.upvalues[index].clone()
+                            }
+                        })
+                        .collect();
+                    let closure = Closure { function, upvalues };
+                    self.stack.push(Value::Closure(Rc::new(closure)));
+                }
+                OpCode::CloseUpvalue => {
+                    self.close_upvalues(self.stack.len() - 1);
+                    self.pop();
+                }
+                OpCode::Return => {
+                    let value = self.pop();
+                    self.close_upvalues(frame.base);
+                    self.stack.truncate(frame.base);
+                    match self.frames.pop() {
+                        Some(caller) => {
+                            frame = caller;
+                            self.stack.push(value);
+                        }
+                        None => return Ok(()),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Integer arithmetic and comparisons without the general dispatch on
+    /// operand types, `None` leaves the operation to `operators::binary`.
+    fn integer_fast_path(op: OpCode, left: &Value, right: &Value) -> Option<Value> {
+        let (Value::Int(a), Value::Int(b)) = (left, right) else {
+            return None;
+        };
+
+        match op {
+            OpCode::Add => a.checked_add(*b).map(Value::Int),
+            OpCode::Subtract => a.checked_sub(*b).map(Value::Int),
+            OpCode::Multiply => a.checked_mul(*b).map(Value::Int),
+            OpCode::Less => Some(Value::Bool(a < b)),
+            OpCode::LessEqual => Some(Value::Bool(a <= b)),
+            OpCode::Greater => Some(Value::Bool(a > b)),
+            OpCode::GreaterEqual => Some(Value::Bool(a >= b)),
+            OpCode::Equal => Some(Value::Bool(a == b)),
+            OpCode::NotEqual => Some(Value::Bool(a != b)),
+            _ => None,
+        }
+    }
+
+    /// Checks a call to `callee` with `count` arguments, returning the
+    /// closure to run.
+    fn call(&self, callee: &Value, count: usize) -> Result<Rc<Closure>, String> {
+        let closure = match callee {
+            Value::Closure(closure) => closure,
+            other => {
+                return Err(format!(
+                    "Can only call functions, not {}.",
+                    other.type_name()
+                ))
+            }
+        };
+
+        if count != closure.function.arity {
+            return Err(format!(
+                "Function '{}' expects {} arguments but got {}.",
+                closure.function.name, closure.function.arity, count
+            ));
+        }
+
+        // The callers of the running frame, one for each call in progress.
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Err("Stack overflow.".to_owned());
+        }
+
+        Ok(closure.clone())
+    }
+
+    fn undefined(frame: &CallFrame, name: &str, offset: usize) -> RuntimeError {
+        frame.error(&format!("Undefined variable '{}'.", name), offset)
+    }
+
+    /// The upvalue for stack `slot`, shared by every closure capturing it.
+    fn capture(&mut self, slot: usize) -> Rc<RefCell<Upvalue>> {
+        let open = self
+            .open_upvalues
+            .iter()
+            .find(|upvalue| matches!(&*upvalue.borrow(), Upvalue::Open(s) if *s == slot));
+        if let Some(upvalue) = open {
+            return upvalue.clone();
+        }
+
+        let upvalue = Rc::new(RefCell::new(Upvalue::Open(slot)));
+        self.open_upvalues.push(upvalue.clone());
+        upvalue
+    }
+
+    /// Moves the variables at and above stack slot `from` into their
+    /// upvalues.
+    fn close_upvalues(&mut self, from: usize) {
+        let stack = &self.stack;
+        self.open_upvalues.retain(|upvalue| {
+            let slot = match &*upvalue.borrow() {
+                Upvalue::Open(slot) if *slot >= from => *slot,
+                _ => return true,
+            };
+            *upvalue.borrow_mut() = Upvalue::Closed(stack[slot].clone());
+            false
+        });
+    }
+}
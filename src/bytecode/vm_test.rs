@@ -0,0 +1,236 @@
+#![allow(unused_imports, dead_code)]
+
+use super::{
+    chunk::{Chunk, CompiledFunction, Constant},
+    compiler::Compiler,
+    opcode::OpCode,
+    vm::Vm,
+};
+use crate::{
+    interpreter::runtime_error::RuntimeError,
+    semantic::resolver::Resolver,
+    test_support::{compile, compile_with, interpret},
+    types::span::Span,
+};
+
+fn run(input: &str) -> Result<String, RuntimeError> {
+    let mut vm = Vm::new(Vec::new());
+    vm.run(compile(input))?;
+
+    Ok(String::from_utf8(vm.into_output()).unwrap())
+}
+
+#[test]
+fn opcodes_round_trip_through_bytes() {
+    for byte in 0..=u8::MAX {
+        if let Some(op) = OpCode::from_byte(byte) {
+            assert_eq!(op as u8, byte);
+        }
+    }
+    assert_eq!(
+        OpCode::from_byte(OpCode::Return as u8),
+        Some(OpCode::Return)
+    );
+    assert_eq!(OpCode::from_byte(OpCode::Return as u8 + 1), None);
+}
+
+#[test]
+fn chunks_share_constants_and_locate_instructions() {
+    let mut chunk = Chunk::new();
+    let first = Span::new(0, 1, 1, 1);
+    let second = Span::new(4, 5, 2, 1);
+
+    chunk.write_op(OpCode::Nil, first);
+    chunk.write_op(OpCode::Pop, first);
+    chunk.write_op(OpCode::Return, second);
+
+    assert_eq!(chunk.spans().len(), 2);
+    assert_eq!(chunk.span_at(1), first);
+    assert_eq!(chunk.span_at(2), second);
+
+    let int = chunk.add_constant(Constant::Int(1));
+    let string = chunk.add_constant(Constant::String("a".to_owned()));
+    assert_eq!(chunk.add_constant(Constant::Int(1)), int);
+    assert_ne!(int, string);
+}
+
+#[test]
+fn matches_the_interpreter() {
+    let programs = [
+        "print 1 + 2 * 3; print (1 + 2) * 3; print 7 / 2; print 7 / 2.0; print -4;",
+        "print 17 % 5; print 7.5 % 2; print 2 ** 10; print 2 ** -1.0; print 6 & 3; print 6 | 3;
+         print 6 ^ 3; print ~0; print 1 << 4; print -16 >> 2; print true ^ false;
+         print 1 > 2 ? \"yes\" : \"no\";",
+        "int x = 10; x += 5; x -= 3; x *= 2; x /= 4; print x; print x++; print x; print ++x;
+         print x--; print --x;",
+        "string s = \"hello\"; print s + \" world\"; print 1 < 2 && \"a\" != \"b\";
+         print !true || nil == nil; print nil && 1; print 0 || nil;",
+        "char c = 'b'; char d; print \"\\\"\" + c + '\\u{21}' + \"\\\"\"; print c > 'a';
+         print d == '\\0';",
+        "int x = 10; float y = 2; bool z; { int x = 20; print x; y = y / 4; } print x;
+         print y; print z;",
+        "int sum = 0; for (int i = 0; i < 5; i = i + 1) { if (i == 2) print \"two\";
+         else sum = sum + i; } while (sum > 0) sum = sum - 3; print sum;",
+        "var total = 0; for (int i = 0; i < 10; i = i + 1) { if (i == 2) continue;
+         if (i == 5) break; total = total + i; } print total;
+         int n = 0; while (true) { n = n + 1; if (n < 3) { continue; } break; } print n;",
+        "var a; var b = 1.5; print a; print b; a = \"x\"; print a;",
+        "float half(float x) { return x / 2; } float one() { return 1; } print half(3);
+         print one(); fun f() {} print f(); print f; print half;",
+        "var a = \"global\"; { fun show() { print a; } show(); var a = \"block\"; show(); }",
+        "print 0 ** 4294967296; print 1 ** 4294967297; print (-1) ** 4294967297;
+         print (-1) ** 4294967296; print 2 ** -1.0;",
+        "float f = 1.5; f = 3; print f / 2;",
+        "print (true ? 1 : 2.5) / 2; print (false ? 2.5 : 1) / 2;",
+    ];
+
+    for program in programs {
+        assert_eq!(run(program), interpret(program), "{}", program);
+    }
+}
+
+#[test]
+fn functions_and_recursion() {
+    let output = run(r#"
+        int fib(int n) {
+            if (n < 2) return n;
+            return fib(n - 1) + fib(n - 2);
+        }
+        fun counter() {
+            int count = 0;
+            fun next() {
+                count = count + 1;
+                return count;
+            }
+            return next;
+        }
+        print fib(15);
+        var next = counter();
+        next();
+        print next();
+    "#);
+
+    assert_eq!(output.unwrap(), "610\n2\n");
+}
+
+#[test]
+fn closures_share_captured_variables() {
+    let output = run(r#"
+        fun pair() {
+            var value = 0;
+            fun get() { return value; }
+            fun set(v) { value = v; }
+            set(5);
+            print get();
+            return get;
+        }
+        var get = pair();
+        print get();
+
+        fun outer() {
+            var x = "outer";
+            fun middle() {
+                fun inner() { return x; }
+                return inner;
+            }
+            x = "changed";
+            return middle();
+        }
+        print outer()();
+    "#);
+
+    assert_eq!(output.unwrap(), "5\n5\nchanged\n");
+}
+
+#[test]
+fn loops_close_captured_locals() {
+    let output = run(r#"
+        var first;
+        var second;
+        for (var i = 0; i < 3; i++) {
+            var j = i * 10;
+            fun show() { return j; }
+            if (i == 0) first = show;
+            if (i == 1) { second = show; break; }
+        }
+        print first();
+        print second();
+    "#);
+
+    assert_eq!(output.unwrap(), "0\n10\n");
+}
+
+#[test]
+fn globals_persist_between_runs() {
+    let mut resolver = Resolver::new();
+    let mut vm = Vm::new(Vec::new());
+
+    vm.run(compile_with(
+        &mut resolver,
+        "var a = 1; fun inc() { a = a + 1; }",
+    ))
+    .unwrap();
+    vm.run(compile_with(&mut resolver, "inc(); print a;"))
+        .unwrap();
+
+    assert_eq!(String::from_utf8(vm.into_output()).unwrap(), "2\n");
+}
+
+#[test]
+fn runtime_errors_match_the_interpreter() {
+    let error = run("int x = 1;\nprint x + \"a\";").unwrap_err();
+    assert_eq!(
+        error,
+        RuntimeError::new(
+            "Operator '+' cannot be applied to int and string.",
+            Span::new(19, 20, 2, 9)
+        )
+    );
+
+    let error = run("fun f(a) { return a; }\nf(1, 2);").unwrap_err();
+    assert_eq!(
+        error,
+        RuntimeError::new(
+            "Function 'f' expects 1 arguments but got 2.",
+            Span::new(23, 30, 2, 1)
+        )
+    );
+
+    let error = run("var x = 1;\nx();").unwrap_err();
+    assert_eq!(
+        error,
+        RuntimeError::new("Can only call functions, not int.", Span::new(11, 14, 2, 1))
+    );
+
+    for program in [
+        "print 10 / 0;",
+        "print 1 << 64;",
+        "print 2 ** 63;",
+        "int x = 2 ** -1;\nprint x;",
+        "print 2 ** 4294967296;",
+        "var x = 9223372036854775807; x++;",
+        "print -\"a\";",
+        "fun f() { return f(); }\nf();",
+    ] {
+        assert_eq!(run(program), interpret(program), "{}", program);
+    }
+}
+
+#[test]
+fn call_depth_limit_matches_the_interpreter() {
+    let depth = "int d(int n) { if (n == 0) return 0; return 1 + d(n - 1); }\n";
+
+    let program = format!("{}print d(1023);", depth);
+    assert_eq!(run(&program).unwrap(), "1023\n");
+    assert_eq!(interpret(&program).unwrap(), "1023\n");
+
+    let program = format!("{}print d(1024);", depth);
+    let error = run(&program).unwrap_err();
+    assert_eq!(error.message, "Stack overflow.");
+    assert_eq!(Err(error), interpret(&program));
+}
+
+#[test]
+fn top_level_return_stops_the_program() {
+    assert_eq!(run("print 1; return; print 2;").unwrap(), "1\n");
+}
@@ -0,0 +1,999 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use super::{
+    lowering::{self, CodegenResult},
+    toolchain::RUNTIME_HEADER,
+};
+use crate::{
+    ast::tree_types::*,
+    semantic::static_type::{Signature, StaticType},
+    types::span::Span,
+};
+
+/// Translates a type checked program to a C99 translation unit including
+/// the runtime header. Lox types map onto C ones: `int` to `int64_t`,
+/// `float` to `double`, `boolean` to `bool`, `char` to a `uint32_t` code
+/// point and `string` to `const char *`.
+///
+/// Integer operators that can fail call checked runtime functions, and
+/// operands with side effects go through temporaries so they run left to
+/// right as in the interpreter. Like the assembly backend, every type must
+/// be known and functions must be declared at the top level.
+#[derive(Debug, Clone, Default)]
+pub struct CTranspiler {
+    /// Stored in `lox_source_name` for the runtime's error messages.
+    source_name: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Variable {
+    static_type: StaticType,
+    /// Name of the C variable.
+    name: String,
+}
+
+#[derive(Debug, Clone)]
+struct Function {
+    name: String,
+    signature: Signature,
+}
+
+/// A translated expression.
+#[derive(Debug, Clone)]
+struct Operand {
+    code: String,
+    static_type: StaticType,
+    /// Whether evaluating it calls a function or assigns a variable.
+    effects: bool,
+    /// Whether it is a literal.
+    constant: bool,
+}
+
+impl Operand {
+    fn new(code: String, static_type: StaticType, effects: bool) -> Self {
+        Self {
+            code,
+            static_type,
+            effects,
+            constant: false,
+        }
+    }
+
+    fn constant(code: String, static_type: StaticType) -> Self {
+        Self {
+            code,
+            static_type,
+            effects: false,
+            constant: true,
+        }
+    }
+}
+
+/// The C function being written.
+#[derive(Debug, Clone)]
+struct Frame {
+    code: String,
+    depth: usize,
+    /// Types of the temporaries `lox_t0`, `lox_t1`... declared first.
+    temporaries: Vec<StaticType>,
+    return_type: StaticType,
+    /// Whether this is `main`, where the top-level statements go.
+    main: bool,
+    scopes: Vec<HashMap<String, Variable>>,
+}
+
+impl Frame {
+    fn new(return_type: StaticType) -> Self {
+        Self {
+            code: String::new(),
+            depth: 1,
+            temporaries: vec![],
+            return_type,
+            main: false,
+            scopes: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TempState {
+    frame: Frame,
+    context: lowering::Context<Function, Variable>,
+    /// Global C declarations in the order of the source.
+    global_declarations: Vec<String>,
+    /// C names of the globals, redeclared ones get a numbered name.
+    global_names: HashSet<String>,
+}
+
+impl TempState {
+    fn new() -> Self {
+        Self {
+            frame: Frame {
+                main: true,
+                ..Frame::new(StaticType::Void)
+            },
+            context: lowering::Context::new("C"),
+            global_declarations: vec![],
+            global_names: HashSet::new(),
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        let indent = "    ".repeat(self.frame.depth);
+        let _ = writeln!(self.frame.code, "{}{}", indent, line);
+    }
+
+    /// Arguments locating a runtime error at the current node.
+    fn location(&self) -> String {
+        format!(
+            "{}, {}",
+            self.context.location.line, self.context.location.column
+        )
+    }
+
+    fn temporary(&mut self, static_type: &StaticType) -> String {
+        self.frame.temporaries.push(static_type.clone());
+        format!("lox_t{}", self.frame.temporaries.len() - 1)
+    }
+
+    /// Declares a variable, returns its C name.
+    fn declare(&mut self, name: &str, static_type: StaticType) -> String {
+        let mut c_name = format!("var_{}", identifier(name));
+        if self.frame.scopes.is_empty() {
+            let base = c_name.clone();
+            let mut count = 1;
+            while self.global_names.contains(&c_name) {
+                count += 1;
+                c_name = format!("{}_{}", base, count);
+            }
+            self.global_names.insert(c_name.clone());
+            self.global_declarations
+                .push(format!("static {};", declaration(&static_type, &c_name)));
+        }
+
+        let variable = Variable {
+            static_type,
+            name: c_name.clone(),
+        };
+        match self.frame.scopes.last_mut() {
+            Some(scope) => scope.insert(name.to_owned(), variable),
+            None => self.context.globals.insert(name.to_owned(), variable),
+        };
+        c_name
+    }
+
+    fn lookup(&self, name: &str) -> CodegenResult<Variable> {
+        let local = self
+            .frame
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name));
+        if let Some(variable) = local {
+            return Ok(variable.clone());
+        }
+        if self.context.functions.contains_key(name) {
+            return Err(self.context.function_value(name, self.context.location));
+        }
+        self.context.globals.get(name).cloned().ok_or_else(|| {
+            self.context
+                .error(&format!("Undefined variable '{}'.", name))
+        })
+    }
+
+    /// Evaluates the operands left to right. C leaves the order of most
+    /// operands unspecified, so when one has side effects that another
+    /// could observe they are stored in temporaries first. With `first`
+    /// the side effects also run before whatever uses the values. Returns
+    /// the code running them and the code of each value.
+    fn ordered(&mut self, operands: &[Operand], first: bool) -> (String, Vec<String>) {
+        let variable = operands.iter().filter(|o| !o.constant).count();
+        if (variable < 2 && !first) || !operands.iter().any(|o| o.effects) {
+            return (
+                String::new(),
+                operands.iter().map(|o| o.code.clone()).collect(),
+            );
+        }
+
+        let mut prefix = String::new();
+        let mut values = vec![];
+        for operand in operands {
+            if operand.constant {
+                values.push(operand.code.clone());
+            } else if matches!(operand.static_type, StaticType::Nil | StaticType::Void) {
+                let _ = write!(prefix, "(void){}, ", operand.code);
+                values.push("0".to_owned());
+            } else {
+                let temporary = self.temporary(&operand.static_type);
+                let _ = write!(prefix, "{} = {}, ", temporary, operand.code);
+                values.push(temporary);
+            }
+        }
+        (prefix, values)
+    }
+}
+
+impl CTranspiler {
+    pub fn new() -> Self {
+        Self { source_name: None }
+    }
+
+    /// Runtime errors of the program will name `source_name`.
+    pub fn with_source_name(source_name: &str) -> Self {
+        Self {
+            source_name: Some(source_name.to_owned()),
+        }
+    }
+
+    /// Translates a whole program.
+    pub fn transpile(&self, statements: &[Statement]) -> CodegenResult<String> {
+        let mut state = TempState::new();
+
+        // Functions can be called before their declaration.
+        let mut prototypes = vec![];
+        for statement in statements {
+            if let Statement::Function {
+                name,
+                parameters,
+                return_type,
+                span,
+                ..
+            } = statement
+            {
+                if state.context.functions.contains_key(name) {
+                    return Err(state.context.duplicate_function(name, *span));
+                }
+                let function = Function {
+                    name: format!("fn_{}", identifier(name)),
+                    signature: state.context.signature(parameters, *return_type)?,
+                };
+                prototypes.push(format!("{};", Self::prototype(&function, parameters)));
+                state.context.functions.insert(name.clone(), function);
+            }
+        }
+
+        for statement in statements {
+            if !matches!(statement, Statement::Function { .. }) {
+                self.statement(&mut state, statement)?;
+            }
+        }
+        state.line("return 0;");
+        let main = Self::finish(&mut state, "int main(void)");
+
+        // Bodies come last so they see every global.
+        let mut functions = vec![];
+        for statement in statements {
+            if let Statement::Function {
+                name,
+                parameters,
+                body,
+                ..
+            } = statement
+            {
+                let function = state.context.functions[name].clone();
+                self.function(&mut state, &function, parameters, body)?;
+                let prototype = Self::prototype(&function, parameters);
+                functions.push(Self::finish(&mut state, &prototype));
+            }
+        }
+
+        let mut output = String::new();
+        match &self.source_name {
+            Some(name) => {
+                let _ = writeln!(output, "/* Generated from {} */", name.replace("*/", "* /"));
+            }
+            None => {
+                let _ = writeln!(output, "/* Generated program */");
+            }
+        }
+        let _ = writeln!(output, "#include \"{}\"\n", RUNTIME_HEADER);
+        let _ = writeln!(
+            output,
+            "const char lox_source_name[] = \"{}\";\n",
+            escape(self.source_name.as_deref().unwrap_or(""))
+        );
+
+        for section in [prototypes, state.global_declarations] {
+            if !section.is_empty() {
+                for declaration in &section {
+                    let _ = writeln!(output, "{}", declaration);
+                }
+                output.push('\n');
+            }
+        }
+        for function in functions {
+            output += &function;
+            output.push('\n');
+        }
+        output += &main;
+
+        Ok(output)
+    }
+
+    /// The C function header, without the trailing semicolon of a
+    /// prototype declaration.
+    fn prototype(function: &Function, parameters: &[Parameter]) -> String {
+        let parameters = parameters
+            .iter()
+            .zip(&function.signature.parameters)
+            .map(|(parameter, static_type)| {
+                declaration(static_type, &format!("var_{}", identifier(&parameter.name)))
+            })
+            .collect::<Vec<_>>();
+        let parameters = match parameters.is_empty() {
+            true => "void".to_owned(),
+            false => parameters.join(", "),
+        };
+        format!(
+            "static {}",
+            declaration(
+                &function.signature.return_type,
+                &format!("{}({})", function.name, parameters)
+            )
+        )
+    }
+
+    /// Wraps the statements of the current frame into a function definition
+    /// and starts a new frame.
+    fn finish(state: &mut TempState, header: &str) -> String {
+        let frame = std::mem::replace(&mut state.frame, Frame::new(StaticType::Void));
+
+        let mut text = format!("{} {{\n", header);
+        for (index, static_type) in frame.temporaries.iter().enumerate() {
+            let _ = writeln!(
+                text,
+                "    {};",
+                declaration(static_type, &format!("lox_t{}", index))
+            );
+        }
+        text += &frame.code;
+        text += "}\n";
+        text
+    }
+
+    fn function(
+        &self,
+        state: &mut TempState,
+        function: &Function,
+        parameters: &[Parameter],
+        body: &[Statement],
+    ) -> CodegenResult {
+        state.frame = Frame::new(function.signature.return_type.clone());
+        state.frame.scopes.push(HashMap::new());
+        for (parameter, static_type) in parameters.iter().zip(&function.signature.parameters) {
+            state.declare(&parameter.name, static_type.clone());
+        }
+
+        for statement in body {
+            self.statement(state, statement)?;
+        }
+        // Falling off the end returns nil, a null pointer for strings.
+        let returns = matches!(body.last(), Some(Statement::Return(..)));
+        if function.signature.return_type != StaticType::Void && !returns {
+            state.line("return 0;");
+        }
+        Ok(())
+    }
+
+    fn statement(&self, state: &mut TempState, statement: &Statement) -> CodegenResult {
+        match statement {
+            Statement::Expression(expression, _) => {
+                state.context.location = expression.span();
+                let operand = self.expression(state, expression)?;
+                match operand.effects {
+                    true => state.line(&format!("{};", unwrap(&operand.code))),
+                    false => state.line(&format!("(void){};", operand.code)),
+                }
+            }
+            Statement::Print(expression, _) => {
+                state.context.location = expression.span();
+                let operand = self.expression(state, expression)?;
+                self.print(state, operand)?;
+            }
+            Statement::Variable {
+                data_type,
+                name,
+                initializer,
+                span,
+            } => {
+                state.context.location = *span;
+                let (static_type, value) =
+                    self.initializer(state, name, *data_type, initializer)?;
+                let global = state.frame.scopes.is_empty();
+                let c_name = state.declare(name, static_type.clone());
+                match global {
+                    true => state.line(&format!("{} = {};", c_name, value)),
+                    false => state.line(&format!(
+                        "{} = {};",
+                        declaration(&static_type, &c_name),
+                        value
+                    )),
+                }
+            }
+            Statement::Block(statements, _) => {
+                state.line("{");
+                self.block(state, statements)?;
+                state.line("}");
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => {
+                state.context.location = *span;
+                let condition = self.condition(state, condition)?;
+                state.line(&format!("if ({}) {{", condition));
+                self.branch(state, then_branch)?;
+
+                // `else if` chains stay flat.
+                let mut else_branch = else_branch.as_deref();
+                while let Some(statement) = else_branch {
+                    match statement {
+                        Statement::If {
+                            condition,
+                            then_branch,
+                            else_branch: next,
+                            span,
+                        } => {
+                            state.context.location = *span;
+                            let condition = self.condition(state, condition)?;
+                            state.line(&format!("}} else if ({}) {{", condition));
+                            self.branch(state, then_branch)?;
+                            else_branch = next.as_deref();
+                        }
+                        other => {
+                            state.line("} else {");
+                            self.branch(state, other)?;
+                            else_branch = None;
+                        }
+                    }
+                }
+                state.line("}");
+            }
+            Statement::While {
+                condition,
+                body,
+                span,
+            } => {
+                state.context.location = *span;
+                let condition = self.condition(state, condition)?;
+                state.line(&format!("while ({}) {{", condition));
+                self.branch(state, body)?;
+                state.line("}");
+            }
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                span,
+            } => {
+                state.frame.scopes.push(HashMap::new());
+                let initializer = match initializer.as_deref() {
+                    Some(initializer) => self.for_initializer(state, initializer)?,
+                    None => String::new(),
+                };
+                state.context.location = *span;
+                let condition = match condition {
+                    Some(condition) => format!(" {}", self.condition(state, condition)?),
+                    None => String::new(),
+                };
+                let increment = match increment {
+                    Some(increment) => {
+                        state.context.location = increment.span();
+                        format!(" {}", unwrap(&self.expression(state, increment)?.code))
+                    }
+                    None => String::new(),
+                };
+
+                state.line(&format!(
+                    "for ({};{};{}) {{",
+                    initializer, condition, increment
+                ));
+                self.branch(state, body)?;
+                state.line("}");
+                state.frame.scopes.pop();
+            }
+            Statement::Return(value, span) => {
+                state.context.location = *span;
+                match value {
+                    Some(value) => {
+                        let operand = self.expression(state, value)?;
+                        state.context.location = *span;
+                        let expected = state.frame.return_type.clone();
+                        state
+                            .context
+                            .return_value(&expected, &operand.static_type, *span)?;
+                        let value = widen(&operand, &expected);
+                        state.line(&format!("return {};", unwrap(&value)));
+                    }
+                    None if state.frame.main => state.line("return 0;"),
+                    None => state.line("return;"),
+                }
+            }
+            Statement::Break(_) => state.line("break;"),
+            Statement::Continue(_) => state.line("continue;"),
+            Statement::Function { span, .. } => return Err(state.context.nested_function(*span)),
+        }
+        Ok(())
+    }
+
+    fn block(&self, state: &mut TempState, statements: &[Statement]) -> CodegenResult {
+        state.frame.depth += 1;
+        state.frame.scopes.push(HashMap::new());
+        for statement in statements {
+            self.statement(state, statement)?;
+        }
+        state.frame.scopes.pop();
+        state.frame.depth -= 1;
+        Ok(())
+    }
+
+    /// The body of an `if` or a loop, inside braces the caller writes.
+    fn branch(&self, state: &mut TempState, statement: &Statement) -> CodegenResult {
+        match statement {
+            Statement::Block(statements, _) => self.block(state, statements),
+            statement => self.block(state, std::slice::from_ref(statement)),
+        }
+    }
+
+    fn for_initializer(
+        &self,
+        state: &mut TempState,
+        initializer: &Statement,
+    ) -> CodegenResult<String> {
+        match initializer {
+            Statement::Variable {
+                data_type,
+                name,
+                initializer,
+                span,
+            } => {
+                state.context.location = *span;
+                let (static_type, value) =
+                    self.initializer(state, name, *data_type, initializer)?;
+                let c_name = state.declare(name, static_type.clone());
+                Ok(format!(
+                    "{} = {}",
+                    declaration(&static_type, &c_name),
+                    value
+                ))
+            }
+            Statement::Expression(expression, _) => {
+                state.context.location = expression.span();
+                Ok(unwrap(&self.expression(state, expression)?.code).to_owned())
+            }
+            _ => Err(state.context.error("Unsupported loop initializer.")),
+        }
+    }
+
+    /// Type and initial value of a declared variable.
+    fn initializer(
+        &self,
+        state: &mut TempState,
+        name: &str,
+        data_type: Option<DataType>,
+        initializer: &Option<Expresion>,
+    ) -> CodegenResult<(StaticType, String)> {
+        let span = state.context.location;
+        match initializer {
+            Some(expression) => {
+                let operand = self.expression(state, expression)?;
+                let static_type =
+                    state
+                        .context
+                        .variable(name, data_type, Some(&operand.static_type), span)?;
+                let value = widen(&operand, &static_type);
+                Ok((static_type, unwrap(&value).to_owned()))
+            }
+            None => {
+                let static_type = state.context.variable(name, data_type, None, span)?;
+                let value = match static_type {
+                    StaticType::Float => "0.0",
+                    StaticType::Bool => "false",
+                    StaticType::String => "\"\"",
+                    _ => "0",
+                };
+                Ok((static_type, value.to_owned()))
+            }
+        }
+    }
+
+    fn condition(&self, state: &mut TempState, condition: &Expresion) -> CodegenResult<String> {
+        let operand = self.expression(state, condition)?;
+        state.context.location = condition.span();
+        lowering::expect(
+            &StaticType::Bool,
+            &operand.static_type,
+            state.context.location,
+        )?;
+        Ok(unwrap(&operand.code).to_owned())
+    }
+
+    fn print(&self, state: &mut TempState, operand: Operand) -> CodegenResult {
+        let function = match operand.static_type {
+            StaticType::Int => "lox_print_int",
+            StaticType::Float => "lox_print_float",
+            StaticType::Bool => "lox_print_bool",
+            StaticType::Char => "lox_print_char",
+            StaticType::String => "lox_print_string",
+            StaticType::Nil | StaticType::Void => {
+                if !operand.constant {
+                    state.line(&format!("{};", unwrap(&operand.code)));
+                }
+                state.line("lox_print_nil();");
+                return Ok(());
+            }
+            other => {
+                return Err(state.context.error(&format!(
+                    "Native code cannot print a value of type {}.",
+                    other
+                )))
+            }
+        };
+        state.line(&format!("{}({});", function, unwrap(&operand.code)));
+        Ok(())
+    }
+
+    fn expression(&self, state: &mut TempState, expression: &Expresion) -> CodegenResult<Operand> {
+        let operand = match expression {
+            Expresion::Unknown(literal, _) => {
+                return Err(state
+                    .context
+                    .error(&format!("Unknown expression '{}'.", literal)))
+            }
+            Expresion::Integer(value, _) => Operand::constant(value.to_string(), StaticType::Int),
+            Expresion::Float(value, _) => {
+                Operand::constant(format!("{:?}", value), StaticType::Float)
+            }
+            Expresion::Str(value, _) => {
+                Operand::constant(format!("\"{}\"", escape(value)), StaticType::String)
+            }
+            Expresion::Char(value, _) => {
+                Operand::constant((*value as u32).to_string(), StaticType::Char)
+            }
+            Expresion::Bool(value, _) => Operand::constant(value.to_string(), StaticType::Bool),
+            Expresion::Nil(_) => Operand::constant("0".to_owned(), StaticType::Nil),
+            Expresion::Grouping(expression, _) => self.expression(state, expression)?,
+            Expresion::Variable(name, span) => {
+                state.context.location = *span;
+                let variable = state.lookup(name)?;
+                Operand::new(variable.name, variable.static_type, false)
+            }
+            Expresion::Assign(name, value, span) => {
+                let value = self.expression(state, value)?;
+                state.context.location = *span;
+                let variable = state.lookup(name)?;
+                lowering::expect(&variable.static_type, &value.static_type, *span)?;
+                let value = widen(&value, &variable.static_type);
+                Operand::new(
+                    format!("({} = {})", variable.name, unwrap(&value)),
+                    variable.static_type,
+                    true,
+                )
+            }
+            Expresion::Postfix(name, operator, span) => {
+                state.context.location = *span;
+                let variable = state.lookup(name)?;
+                let code = match variable.static_type {
+                    StaticType::Int => format!(
+                        "lox_increment(&{}, {}, {})",
+                        variable.name,
+                        if *operator == "++" { 1 } else { -1 },
+                        state.location()
+                    ),
+                    StaticType::Float => format!("({}{})", variable.name, operator),
+                    ref other => {
+                        return Err(state.context.error(&format!(
+                            "Operator '{}' cannot be applied to {}.",
+                            operator, other
+                        )))
+                    }
+                };
+                Operand::new(code, variable.static_type, true)
+            }
+            Expresion::Ternary(condition, then_branch, else_branch, _) => {
+                let condition = self.expression(state, condition)?;
+                lowering::expect(
+                    &StaticType::Bool,
+                    &condition.static_type,
+                    state.context.location,
+                )?;
+                let then_branch = self.expression(state, then_branch)?;
+                let else_branch = self.expression(state, else_branch)?;
+                let static_type = state.context.ternary(
+                    &then_branch.static_type,
+                    &else_branch.static_type,
+                    state.context.location,
+                )?;
+                Operand::new(
+                    format!(
+                        "({} ? {} : {})",
+                        condition.code,
+                        widen(&then_branch, &static_type),
+                        widen(&else_branch, &static_type)
+                    ),
+                    static_type,
+                    condition.effects || then_branch.effects || else_branch.effects,
+                )
+            }
+            Expresion::Unary(operator, right, span) => {
+                let right = self.expression(state, right)?;
+                state.context.location = *span;
+                self.unary(state, operator, right)?
+            }
+            Expresion::Binary(left, operator, right, span) => {
+                self.binary(state, left, operator, right, *span)?
+            }
+            Expresion::Call(callee, arguments, span) => {
+                self.call(state, callee, arguments, *span)?
+            }
+        };
+        Ok(operand)
+    }
+
+    fn unary(
+        &self,
+        state: &mut TempState,
+        operator: &str,
+        right: Operand,
+    ) -> CodegenResult<Operand> {
+        let static_type = lowering::unary(operator, &right.static_type, state.context.location)?;
+        let code = match (operator, &right.static_type) {
+            // Literals are never the smallest integer, it has no literal.
+            ("-", StaticType::Int) if right.constant => format!("(-{})", right.code),
+            ("-", StaticType::Int) => {
+                format!("lox_negate({}, {})", unwrap(&right.code), state.location())
+            }
+            _ => format!("({}{})", operator, right.code),
+        };
+        Ok(Operand {
+            code,
+            static_type,
+            effects: right.effects,
+            constant: right.constant,
+        })
+    }
+
+    fn binary(
+        &self,
+        state: &mut TempState,
+        left: &Expresion,
+        operator: &str,
+        right: &Expresion,
+        span: Span,
+    ) -> CodegenResult<Operand> {
+        let left = self.expression(state, left)?;
+        let right = self.expression(state, right)?;
+        state.context.location = span;
+        let static_type = lowering::binary(&left.static_type, operator, &right.static_type, span)?;
+        let effects = left.effects || right.effects;
+
+        // The right side of `&&` and `||` may not run, C short-circuits
+        // them the same way.
+        if operator == "&&" || operator == "||" {
+            let code = format!("({} {} {})", left.code, operator, right.code);
+            return Ok(Operand::new(code, static_type, effects));
+        }
+
+        let (left_type, right_type) = (left.static_type.clone(), right.static_type.clone());
+        let (prefix, values) = state.ordered(&[left, right], false);
+        let (a, b) = (&values[0], &values[1]);
+        let location = state.location();
+
+        use StaticType::*;
+        let code = match (&left_type, &right_type) {
+            (Int, Int) => match operator {
+                "+" | "-" | "*" | "/" | "%" | "**" | "<<" | ">>" => {
+                    let function = match operator {
+                        "+" => "lox_add",
+                        "-" => "lox_subtract",
+                        "*" => "lox_multiply",
+                        "/" => "lox_divide",
+                        "%" => "lox_modulo",
+                        "**" => "lox_power",
+                        "<<" => "lox_shift_left",
+                        _ => "lox_shift_right",
+                    };
+                    format!("{}({}, {}, {})", function, unwrap(a), unwrap(b), location)
+                }
+                _ => format!("({} {} {})", a, operator, b),
+            },
+            (l, r) if l.is_numeric() && r.is_numeric() => {
+                let a = widen_code(a, l, &Float);
+                let b = widen_code(b, r, &Float);
+                match operator {
+                    "%" => format!("fmod({}, {})", unwrap(&a), unwrap(&b)),
+                    "**" => format!("pow({}, {})", unwrap(&a), unwrap(&b)),
+                    _ => format!("({} {} {})", a, operator, b),
+                }
+            }
+            (String, String) if operator == "+" => {
+                format!("lox_concat({}, {})", unwrap(a), unwrap(b))
+            }
+            (String, Char) => format!("lox_concat_char({}, {})", unwrap(a), unwrap(b)),
+            (Char, String) => format!("lox_char_concat({}, {})", unwrap(a), unwrap(b)),
+            (String, String) => format!(
+                "(lox_compare_strings({}, {}) {} 0)",
+                unwrap(a),
+                unwrap(b),
+                operator
+            ),
+            (Char, Char) | (Bool, Bool) => format!("({} {} {})", a, operator, b),
+            // Both sides still run for their side effects.
+            (l, r) => {
+                let equal = matches!((l, r), (Nil | Void, Nil | Void));
+                let result = (operator == "==") == equal;
+                format!("((void){}, (void){}, {})", a, b, result)
+            }
+        };
+
+        let code = match prefix.is_empty() {
+            true => code,
+            false => format!("({}{})", prefix, code),
+        };
+        Ok(Operand::new(code, static_type, effects))
+    }
+
+    fn call(
+        &self,
+        state: &mut TempState,
+        callee: &Expresion,
+        arguments: &[Expresion],
+        span: Span,
+    ) -> CodegenResult<Operand> {
+        state.context.location = span;
+        let function = match callee {
+            Expresion::Variable(name, _)
+                if !state.frame.scopes.iter().any(|s| s.contains_key(name)) =>
+            {
+                state.context.functions.get(name).cloned()
+            }
+            _ => None,
+        };
+        let Some(function) = function else {
+            return Err(state.context.indirect_call(span));
+        };
+
+        let signature = &function.signature;
+        if arguments.len() != signature.parameters.len() {
+            let expected = signature.parameters.len();
+            return Err(lowering::argument_count(expected, arguments.len(), span));
+        }
+
+        let mut operands = vec![];
+        for (argument, expected) in arguments.iter().zip(&signature.parameters) {
+            let operand = self.expression(state, argument)?;
+            let location = argument.span();
+            lowering::expect(expected, &operand.static_type, location)?;
+            operands.push(Operand {
+                code: widen(&operand, expected),
+                ..operand
+            });
+        }
+
+        // Calls made by the arguments are over before this one is counted,
+        // so deep recursion stops where the interpreter's would.
+        let (prefix, values) = state.ordered(&operands, true);
+        state.context.location = span;
+        let values = values.iter().map(|v| unwrap(v)).collect::<Vec<_>>();
+        let call = format!("{}({})", function.name, values.join(", "));
+        let enter = format!("lox_enter({})", state.location());
+        let code = match signature.return_type {
+            StaticType::Void => format!("({}{}, {}, lox_leave())", prefix, enter, call),
+            ref return_type => {
+                let result = state.temporary(return_type);
+                format!(
+                    "({}{}, {} = {}, lox_leave(), {})",
+                    prefix, enter, result, call, result
+                )
+            }
+        };
+        Ok(Operand::new(code, signature.return_type.clone(), true))
+    }
+}
+
+/// Declares `name` with the C type of `static_type`.
+fn declaration(static_type: &StaticType, name: &str) -> String {
+    let c_type = match static_type {
+        StaticType::Int => "int64_t ",
+        StaticType::Float => "double ",
+        StaticType::Bool => "bool ",
+        StaticType::Char => "uint32_t ",
+        StaticType::String => "const char *",
+        _ => "void ",
+    };
+    format!("{}{}", c_type, name)
+}
+
+/// Converts an int to a double where a float is expected.
+fn widen(operand: &Operand, expected: &StaticType) -> String {
+    widen_code(&operand.code, &operand.static_type, expected)
+}
+
+fn widen_code(code: &str, found: &StaticType, expected: &StaticType) -> String {
+    match (found, expected) {
+        (StaticType::Int, StaticType::Float) if code.bytes().all(|b| b.is_ascii_digit()) => {
+            format!("{}.0", code)
+        }
+        (StaticType::Int, StaticType::Float) => format!("(double){}", code),
+        _ => code.to_owned(),
+    }
+}
+
+/// Drops the parentheses around a whole expression where it stands alone.
+fn unwrap(code: &str) -> &str {
+    match code.strip_prefix('(').and_then(|c| c.strip_suffix(')')) {
+        // `(a) + (b)` starts and ends with parentheses that do not match,
+        // and a comma expression must stay grouped inside an argument list.
+        Some(inner) if standalone(inner) => inner,
+        _ => code,
+    }
+}
+
+/// Whether the parentheses outside of string literals match up, with no
+/// comma outside of them.
+fn standalone(code: &str) -> bool {
+    let mut depth: i32 = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in code.chars() {
+        match (in_string, c) {
+            (true, _) if escaped => escaped = false,
+            (true, '\\') => escaped = true,
+            (true, '"') => in_string = false,
+            (true, _) => {}
+            (false, '"') => in_string = true,
+            (false, '(') => depth += 1,
+            (false, ')') => {
+                depth -= 1;
+                if depth < 0 {
+                    return false;
+                }
+            }
+            (false, ',') if depth == 0 => return false,
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+/// A C identifier for a Lox name, characters outside ASCII are spelled as
+/// their code point.
+fn identifier(name: &str) -> String {
+    let mut identifier = String::new();
+    for c in name.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' => identifier.push(c),
+            _ => {
+                let _ = write!(identifier, "_u{:x}", c as u32);
+            }
+        }
+    }
+    identifier
+}
+
+/// Escapes a string for a C string literal. Bytes other than printable
+/// ASCII are written in octal and `?` is escaped against trigraphs.
+fn escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'"' | b'\\' | b'?' => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            }
+            b' '..=b'~' => escaped.push(byte as char),
+            _ => {
+                let _ = write!(escaped, "\\{:03o}", byte);
+            }
+        }
+    }
+    escaped
+}
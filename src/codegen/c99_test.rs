@@ -0,0 +1,177 @@
+#![allow(unused_imports, dead_code)]
+
+use super::{c99::CTranspiler, codegen_error::CodegenError, toolchain};
+use crate::{
+    test_support::{interpret_output, parse, run_executable},
+    types::span::Span,
+};
+
+fn transpile(input: &str) -> Result<String, CodegenError> {
+    CTranspiler::with_source_name("test.lox").transpile(&parse(input))
+}
+
+/// Compiles and runs the program, `None` when no C compiler is installed.
+fn run_compiled(input: &str) -> Option<(i32, String, String)> {
+    if !cfg!(unix) {
+        return None;
+    }
+    run_executable("lox-c99-test", |executable| {
+        toolchain::compile_c(&transpile(input).unwrap(), executable).unwrap()
+    })
+}
+
+#[test]
+fn maps_declarations_onto_c_types() {
+    let source = transpile(
+        "int twice(int n) { return n * 2; }
+         float f = 1; bool b; string s = \"a\"; char c = 'c';
+         print twice(4);",
+    )
+    .unwrap();
+
+    assert!(source.starts_with("/* Generated from test.lox */\n#include \"lox_runtime.h\"\n"));
+    assert!(source.contains("const char lox_source_name[] = \"test.lox\";\n"));
+    assert!(source.contains("static int64_t fn_twice(int64_t var_n);\n"));
+    assert!(source.contains(
+        "static double var_f;\nstatic bool var_b;\nstatic const char *var_s;\nstatic uint32_t var_c;\n"
+    ));
+    assert!(source.contains("    return lox_multiply(var_n, 2, 1, 29);\n"));
+    assert!(source.contains("    var_f = 1.0;\n    var_b = false;\n"));
+    assert!(source.contains(
+        "    lox_print_int((lox_enter(3, 16), lox_t0 = fn_twice(4), lox_leave(), lox_t0));\n    return 0;\n}\n"
+    ));
+}
+
+#[test]
+fn escapes_strings_and_names() {
+    let source = transpile("string s\u{e9} = \"a\\\"b\\n??\\u{e9}\"; print s\u{e9};").unwrap();
+
+    assert!(source.contains("static const char *var_s_ue9;\n"));
+    assert!(source.contains("    var_s_ue9 = \"a\\\"b\\012\\?\\?\\303\\251\";\n"));
+}
+
+#[test]
+fn keeps_left_to_right_evaluation() {
+    let source = transpile("int n = 1; int bump() { n++; return n; }\nprint n + bump();").unwrap();
+
+    assert!(source.contains("    int64_t lox_t0;\n    int64_t lox_t1;\n    int64_t lox_t2;\n"));
+    assert!(source.contains(
+        "    lox_print_int((lox_t1 = var_n, lox_t2 = (lox_enter(2, 11), lox_t0 = fn_bump(), \
+         lox_leave(), lox_t0), lox_add(lox_t1, lox_t2, 2, 9)));\n"
+    ));
+}
+
+#[test]
+fn rejects_what_c_cannot_express() {
+    let cases = [
+        (
+            "var a;",
+            "Variable 'a' needs a type or an initializer to compile to C.",
+            Span::new(4, 5, 1, 5),
+        ),
+        (
+            "fun outer() {\n  fun inner() {}\n}",
+            "C only supports functions declared at the top level.",
+            Span::new(20, 25, 2, 7),
+        ),
+    ];
+
+    for (program, message, span) in cases {
+        assert_eq!(
+            transpile(program),
+            Err(CodegenError::new(message, span)),
+            "{}",
+            program
+        );
+    }
+}
+
+#[test]
+fn compiled_programs_match_the_interpreter() {
+    let programs = [
+        "print 1 + 2 * 3; print 7 / 2; print 7 / 2.0; print -7 % 3; print 2 ** 10;
+         print 7.5 % 2; print 2 ** 0.5; print 6 & 3; print 6 | 3; print 6 ^ 3; print ~0;
+         print 1 << 4; print -16 >> 2; print true ^ false; print 1 > 2 ? \"yes\" : \"no\";",
+        "print 0.1 + 0.2; print 1e20; print 1.5e-7; print -0.0; print 1.0 / 3; print 100.0;
+         print 0.0 / 0 == 0.0 / 0; print 0.0 / 0 != 0.0 / 0; print 1 / 0.0; print -1 / 0.0;
+         print 1 == 1.0; print 2 > 1.5; print 1 <= 1.0;",
+        "int x = 10; x += 5; x -= 3; x *= 2; x /= 4; print x; print x++; print x; print ++x;
+         print x--; print --x; float f = 1; f++; print f; f = f / 4; print f;",
+        "string s = \"hello\"; print s + \" world\"; print s + '!' + \"\"; print '\\u{e9}' + s;
+         print \"b\" > \"a\"; print \"a\" == \"a\"; print s != \"hello\"; print 'a' < 'b';
+         string empty; char nul; bool b; print empty + \"|\"; print b; print nul == '\\0';",
+        "int sum = 0; for (int i = 0; i < 10; i++) { if (i == 2) continue; if (i == 7) break;
+         sum += i; } print sum; var n = 3; while (n > 0) { n--; { int n = 100; } } print n;
+         print true && false || !false; print nil; print 1 == true; print nil == nil;
+         if (sum > 100) print \"big\"; else if (sum > 10) print \"mid\"; else print \"small\";",
+        "int fib(int n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); }
+         float half(float x) { return x / 2; }
+         string join(string a, char c, string b) { return a + c + b; }
+         float mix(int a, float b, int c, float d) { return a + b + c + d; }
+         fun nothing() {}
+         print fib(20); print 1 + half(3); print join(\"a\", '-', \"b\"); print mix(1, 2, 3, 4.5);
+         print nothing(); int counter = 0;
+         fun bump() { counter = counter + 1; } bump(); bump(); print counter;
+         int next() { counter++; return counter; }
+         print counter * 10 + next(); print next() - counter; print nothing() == nil;",
+        "var x = 1; var x = \"shadowed\"; print x; print 1; return; print 2;",
+        "print 0 ** 4294967296; print 1 ** 4294967297; print (-1) ** 4294967297;
+         print (-1) ** 4294967296; print 2 ** -1.0;",
+        "int d(int n) { if (n == 0) return 0; return 1 + d(n - 1); } print d(1023);",
+        "float f = 1.5; f = 3; print f / 2;",
+        "print (true ? 1 : 2.5) / 2; print (false ? 2.5 : 1) / 2;",
+    ];
+
+    for program in programs {
+        let Some((code, output, _)) = run_compiled(program) else {
+            return;
+        };
+        assert_eq!(code, 0, "{}", program);
+        assert_eq!(output, interpret_output(program).0, "{}", program);
+    }
+}
+
+#[test]
+fn compiled_runtime_errors_match_the_interpreter() {
+    let cases = [
+        ("int x = 1;\nprint x / 0;", "Division by zero.", "2:9"),
+        ("print 1 << 64;", "Shift amount out of range.", "1:9"),
+        ("print 2 ** 63;", "Integer overflow.", "1:9"),
+        ("print 2 ** 4294967296;", "Integer overflow.", "1:9"),
+        (
+            "int x = 2 ** -1;\nprint x;",
+            "Negative exponent of an integer power.",
+            "1:11",
+        ),
+        (
+            "int x = 9223372036854775807;\nx++;",
+            "Integer overflow.",
+            "2:1",
+        ),
+        (
+            "int f(int n) { return -n; }\nprint f(-9223372036854775807 - 1);",
+            "Integer overflow.",
+            "1:23",
+        ),
+        ("fun f() { f(); }\nf();", "Stack overflow.", "1:11"),
+        (
+            "int d(int n) { if (n == 0) return 0; return 1 + d(n - 1); }\nprint d(1024);",
+            "Stack overflow.",
+            "1:49",
+        ),
+    ];
+
+    for (program, message, location) in cases {
+        let Some((code, output, errors)) = run_compiled(program) else {
+            return;
+        };
+        assert_eq!(code, 70, "{}", program);
+        assert_eq!(output, interpret_output(program).0, "{}", program);
+        assert_eq!(
+            errors,
+            format!("error[E0300]: {}\n --> test.lox:{}\n", message, location),
+            "{}",
+            program
+        );
+    }
+}
@@ -0,0 +1,30 @@
+#![allow(dead_code)]
+
+use std::{error::Error, fmt};
+
+use crate::types::span::Span;
+
+/// A construct a native backend cannot lower, such as a variable whose type
+/// is only known at runtime.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodegenError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl CodegenError {
+    pub fn new(message: &str, span: Span) -> Self {
+        Self {
+            message: message.to_owned(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+impl Error for CodegenError {}
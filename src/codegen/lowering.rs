@@ -0,0 +1,251 @@
+//! Typing rules shared by the backends, which need the type of every value
+//! before the program runs. Errors are reported at the given span and name
+//! the backend's target.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use super::codegen_error::CodegenError;
+use crate::{
+    ast::tree_types::*,
+    semantic::{
+        static_type::{Signature, StaticType},
+        type_checker::TypeChecker,
+    },
+    types::span::Span,
+};
+
+pub type CodegenResult<T = ()> = Result<T, CodegenError>;
+
+/// What a backend knows about the program while lowering it, `F` and `V`
+/// are its own descriptions of functions and variables.
+#[derive(Debug, Clone)]
+pub struct Context<F, V> {
+    /// Top-level functions, all declared before any code is lowered.
+    pub functions: HashMap<String, F>,
+    pub globals: HashMap<String, V>,
+    /// Span of the closest node, for errors and runtime error locations.
+    pub location: Span,
+    /// What the backend produces, as named by its errors.
+    target: &'static str,
+}
+
+impl<F, V> Context<F, V> {
+    pub fn new(target: &'static str) -> Self {
+        Self {
+            functions: HashMap::new(),
+            globals: HashMap::new(),
+            location: Span::default(),
+            target,
+        }
+    }
+
+    /// An error at the current location.
+    pub fn error(&self, message: &str) -> CodegenError {
+        CodegenError::new(message, self.location)
+    }
+
+    /// The target at the start of a sentence.
+    fn capitalized_target(&self) -> String {
+        let mut target = self.target.to_owned();
+        target[..1].make_ascii_uppercase();
+        target
+    }
+
+    /// Signature of a top-level function, every parameter must be typed. An
+    /// omitted return type means the function returns nothing.
+    pub fn signature(
+        &self,
+        parameters: &[Parameter],
+        return_type: Option<DataType>,
+    ) -> CodegenResult<Signature> {
+        let mut types = vec![];
+        for parameter in parameters {
+            let Some(data_type) = parameter.data_type else {
+                return Err(CodegenError::new(
+                    &format!(
+                        "Parameter '{}' needs a type to compile to {}.",
+                        parameter.name, self.target
+                    ),
+                    parameter.span,
+                ));
+            };
+            types.push(StaticType::from(data_type));
+        }
+
+        Ok(Signature {
+            parameters: types,
+            return_type: return_type.map_or(StaticType::Void, StaticType::from),
+        })
+    }
+
+    /// Type of a declared variable given the type of its initializer.
+    pub fn variable(
+        &self,
+        name: &str,
+        data_type: Option<DataType>,
+        initializer: Option<&StaticType>,
+        span: Span,
+    ) -> CodegenResult<StaticType> {
+        let untyped = || {
+            CodegenError::new(
+                &format!(
+                    "Variable '{}' needs a type to compile to {}.",
+                    name, self.target
+                ),
+                span,
+            )
+        };
+
+        match (data_type, initializer) {
+            (Some(data_type), found) => {
+                let expected = StaticType::from(data_type);
+                if let Some(found) = found {
+                    expect(&expected, found, span)?;
+                }
+                Ok(expected)
+            }
+            (None, Some(StaticType::Nil | StaticType::Void | StaticType::Any)) => Err(untyped()),
+            (None, Some(found)) => Ok(found.clone()),
+            (None, None) => Err(CodegenError::new(
+                &format!(
+                    "Variable '{}' needs a type or an initializer to compile to {}.",
+                    name, self.target
+                ),
+                span,
+            )),
+        }
+    }
+
+    /// Type of a conditional expression, a float if one branch is an int and
+    /// the other a float.
+    pub fn ternary(
+        &self,
+        then_type: &StaticType,
+        else_type: &StaticType,
+        span: Span,
+    ) -> CodegenResult<StaticType> {
+        match (then_type, else_type) {
+            (a, b) if a == b && *a != StaticType::Any => Ok(a.clone()),
+            (a, b) if a.is_numeric() && b.is_numeric() => Ok(StaticType::Float),
+            _ => Err(CodegenError::new(
+                &format!(
+                    "{} needs both branches of a conditional to have one type, found {} and {}.",
+                    self.capitalized_target(),
+                    then_type,
+                    else_type
+                ),
+                span,
+            )),
+        }
+    }
+
+    /// The type returned by `return value;` in a function returning `expected`.
+    pub fn return_value(
+        &self,
+        expected: &StaticType,
+        found: &StaticType,
+        span: Span,
+    ) -> CodegenResult {
+        if *expected == StaticType::Void {
+            return Err(CodegenError::new(
+                &format!(
+                    "Returning a value needs a declared return type in {}.",
+                    self.target
+                ),
+                span,
+            ));
+        }
+        expect(expected, found, span)
+    }
+
+    pub fn nested_function(&self, span: Span) -> CodegenError {
+        CodegenError::new(
+            &format!(
+                "{} only supports functions declared at the top level.",
+                self.capitalized_target()
+            ),
+            span,
+        )
+    }
+
+    pub fn function_value(&self, name: &str, span: Span) -> CodegenError {
+        CodegenError::new(
+            &format!(
+                "Function '{}' can only be called in {}, not used as a value.",
+                name, self.target
+            ),
+            span,
+        )
+    }
+
+    pub fn duplicate_function(&self, name: &str, span: Span) -> CodegenError {
+        CodegenError::new(
+            &format!(
+                "Function '{}' is declared twice, {} needs distinct names.",
+                name, self.target
+            ),
+            span,
+        )
+    }
+
+    pub fn indirect_call(&self, span: Span) -> CodegenError {
+        CodegenError::new(
+            &format!(
+                "{} can only call functions declared at the top level by their name.",
+                self.capitalized_target()
+            ),
+            span,
+        )
+    }
+}
+
+/// Checks that a value of type `found` can be stored as `expected`.
+pub fn expect(expected: &StaticType, found: &StaticType, span: Span) -> CodegenResult {
+    if *found == StaticType::Any || !expected.accepts(found) {
+        return Err(CodegenError::new(
+            &format!("Expected a value of type {} but found {}.", expected, found),
+            span,
+        ));
+    }
+    Ok(())
+}
+
+pub fn unary(operator: &str, right: &StaticType, span: Span) -> CodegenResult<StaticType> {
+    match TypeChecker::unary(operator, right.clone()) {
+        Ok(StaticType::Any) | Err(_) => Err(CodegenError::new(
+            &format!("Operator '{}' cannot be applied to {}.", operator, right),
+            span,
+        )),
+        Ok(result) => Ok(result),
+    }
+}
+
+pub fn binary(
+    left: &StaticType,
+    operator: &str,
+    right: &StaticType,
+    span: Span,
+) -> CodegenResult<StaticType> {
+    let result = match (left, right) {
+        (StaticType::Any, _) | (_, StaticType::Any) => Err(()),
+        _ => TypeChecker::binary(left.clone(), operator, right.clone()).map_err(|_| ()),
+    };
+    result.map_err(|_| {
+        CodegenError::new(
+            &format!(
+                "Operator '{}' cannot be applied to {} and {}.",
+                operator, left, right
+            ),
+            span,
+        )
+    })
+}
+
+pub fn argument_count(expected: usize, found: usize, span: Span) -> CodegenError {
+    CodegenError::new(
+        &format!("Expected {} arguments but got {}.", expected, found),
+        span,
+    )
+}
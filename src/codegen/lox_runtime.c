@@ -0,0 +1,287 @@
+/* Definitions of the runtime declared in `lox_runtime.h`. */
+
+#include "lox_runtime.h"
+
+void lox_runtime_error(const char *message, int64_t line, int64_t column) {
+    int width = snprintf(NULL, 0, "%" PRId64, line);
+
+    fflush(stdout);
+    fprintf(stderr, "error[E0300]: %s\n%*s--> ", message, width, "");
+    if (lox_source_name[0] != '\0') {
+        fprintf(stderr, "%s:", lox_source_name);
+    }
+    fprintf(stderr, "%" PRId64 ":%" PRId64 "\n", line, column);
+    exit(LOX_EXIT_RUNTIME_ERROR);
+}
+
+int64_t lox_call_depth = 0;
+
+void lox_enter(int64_t line, int64_t column) {
+    lox_call_depth++;
+    if (lox_call_depth > LOX_MAX_CALL_DEPTH) {
+        lox_runtime_error("Stack overflow.", line, column);
+    }
+}
+
+void lox_leave(void) {
+    lox_call_depth--;
+}
+
+static char *allocate(size_t size) {
+    char *memory = malloc(size);
+    if (memory == NULL) {
+        fputs("error: out of memory\n", stderr);
+        exit(LOX_EXIT_RUNTIME_ERROR);
+    }
+    return memory;
+}
+
+/* Writes the UTF-8 encoding of a code point, returns its length. */
+static size_t encode_char(int64_t code, char *buffer) {
+    uint32_t c = (uint32_t)code;
+
+    if (c < 0x80) {
+        buffer[0] = (char)c;
+        return 1;
+    }
+    if (c < 0x800) {
+        buffer[0] = (char)(0xC0 | (c >> 6));
+        buffer[1] = (char)(0x80 | (c & 0x3F));
+        return 2;
+    }
+    if (c < 0x10000) {
+        buffer[0] = (char)(0xE0 | (c >> 12));
+        buffer[1] = (char)(0x80 | ((c >> 6) & 0x3F));
+        buffer[2] = (char)(0x80 | (c & 0x3F));
+        return 3;
+    }
+    buffer[0] = (char)(0xF0 | (c >> 18));
+    buffer[1] = (char)(0x80 | ((c >> 12) & 0x3F));
+    buffer[2] = (char)(0x80 | ((c >> 6) & 0x3F));
+    buffer[3] = (char)(0x80 | (c & 0x3F));
+    return 4;
+}
+
+/*
+ * Formats a float the way the interpreter displays it: the shortest digits
+ * that read back as the same value, never in exponent notation.
+ */
+static void format_float(double value, char *buffer) {
+    char digits[32];
+    char *cursor = buffer;
+    int precision;
+
+    if (isnan(value)) {
+        strcpy(buffer, "NaN");
+        return;
+    }
+    if (isinf(value)) {
+        strcpy(buffer, value < 0 ? "-inf" : "inf");
+        return;
+    }
+
+    for (precision = 0; precision < 17; precision++) {
+        snprintf(digits, sizeof digits, "%.*e", precision, value);
+        if (strtod(digits, NULL) == value) {
+            break;
+        }
+    }
+
+    /* `digits` is now like "-1.25e+02", split it into sign, digits and
+     * the exponent of the first digit. */
+    char *mantissa = digits;
+    if (*mantissa == '-') {
+        *cursor++ = '-';
+        mantissa++;
+    }
+    char *exponent_mark = strchr(mantissa, 'e');
+    int exponent = atoi(exponent_mark + 1);
+    char significant[20];
+    int count = 0;
+    for (char *c = mantissa; c < exponent_mark; c++) {
+        if (*c != '.') {
+            significant[count++] = *c;
+        }
+    }
+    while (count > 1 && significant[count - 1] == '0') {
+        count--;
+    }
+
+    if (exponent < 0) {
+        *cursor++ = '0';
+        *cursor++ = '.';
+        for (int i = 0; i < -exponent - 1; i++) {
+            *cursor++ = '0';
+        }
+        memcpy(cursor, significant, count);
+        cursor += count;
+    } else {
+        for (int i = 0; i <= exponent; i++) {
+            *cursor++ = i < count ? significant[i] : '0';
+        }
+        if (count > exponent + 1) {
+            *cursor++ = '.';
+            memcpy(cursor, significant + exponent + 1, count - exponent - 1);
+            cursor += count - exponent - 1;
+        }
+    }
+    *cursor = '\0';
+}
+
+void lox_print_int(int64_t value) {
+    printf("%" PRId64 "\n", value);
+}
+
+void lox_print_float(double value) {
+    char buffer[400];
+    format_float(value, buffer);
+    puts(buffer);
+}
+
+void lox_print_bool(int64_t value) {
+    puts(value ? "true" : "false");
+}
+
+void lox_print_char(int64_t value) {
+    char buffer[4];
+    fwrite(buffer, 1, encode_char(value, buffer), stdout);
+    putchar('\n');
+}
+
+void lox_print_string(const char *value) {
+    puts(value != NULL ? value : "nil");
+}
+
+void lox_print_nil(void) {
+    puts("nil");
+}
+
+const char *lox_concat(const char *left, const char *right) {
+    size_t left_length = strlen(left);
+    size_t right_length = strlen(right);
+    char *result = allocate(left_length + right_length + 1);
+
+    memcpy(result, left, left_length);
+    memcpy(result + left_length, right, right_length + 1);
+    return result;
+}
+
+const char *lox_concat_char(const char *left, int64_t right) {
+    char buffer[5];
+    buffer[encode_char(right, buffer)] = '\0';
+    return lox_concat(left, buffer);
+}
+
+const char *lox_char_concat(int64_t left, const char *right) {
+    char buffer[5];
+    buffer[encode_char(left, buffer)] = '\0';
+    return lox_concat(buffer, right);
+}
+
+int64_t lox_compare_strings(const char *left, const char *right) {
+    return strcmp(left, right);
+}
+
+/* Integer operators, checked like the interpreter's. */
+
+static void overflow(int64_t line, int64_t column) {
+    lox_runtime_error("Integer overflow.", line, column);
+}
+
+int64_t lox_add(int64_t left, int64_t right, int64_t line, int64_t column) {
+    if ((right > 0 && left > INT64_MAX - right) || (right < 0 && left < INT64_MIN - right)) {
+        overflow(line, column);
+    }
+    return left + right;
+}
+
+int64_t lox_subtract(int64_t left, int64_t right, int64_t line, int64_t column) {
+    if ((right < 0 && left > INT64_MAX + right) || (right > 0 && left < INT64_MIN + right)) {
+        overflow(line, column);
+    }
+    return left - right;
+}
+
+static bool multiply_overflows(int64_t left, int64_t right) {
+    if (left > 0) {
+        return right > 0 ? left > INT64_MAX / right : right < INT64_MIN / left;
+    }
+    if (right > 0) {
+        return left < INT64_MIN / right;
+    }
+    return left != 0 && right < INT64_MAX / left;
+}
+
+int64_t lox_multiply(int64_t left, int64_t right, int64_t line, int64_t column) {
+    if (multiply_overflows(left, right)) {
+        overflow(line, column);
+    }
+    return left * right;
+}
+
+static void check_division(int64_t left, int64_t right, int64_t line, int64_t column) {
+    if (right == 0) {
+        lox_runtime_error("Division by zero.", line, column);
+    }
+    if (left == INT64_MIN && right == -1) {
+        overflow(line, column);
+    }
+}
+
+int64_t lox_divide(int64_t left, int64_t right, int64_t line, int64_t column) {
+    check_division(left, right, line, column);
+    return left / right;
+}
+
+int64_t lox_modulo(int64_t left, int64_t right, int64_t line, int64_t column) {
+    check_division(left, right, line, column);
+    return left % right;
+}
+
+int64_t lox_negate(int64_t value, int64_t line, int64_t column) {
+    if (value == INT64_MIN) {
+        overflow(line, column);
+    }
+    return -value;
+}
+
+int64_t lox_increment(int64_t *variable, int64_t delta, int64_t line, int64_t column) {
+    int64_t previous = *variable;
+    *variable = lox_add(previous, delta, line, column);
+    return previous;
+}
+
+static void check_shift(int64_t amount, int64_t line, int64_t column) {
+    if (amount < 0 || amount > 63) {
+        lox_runtime_error("Shift amount out of range.", line, column);
+    }
+}
+
+int64_t lox_shift_left(int64_t value, int64_t amount, int64_t line, int64_t column) {
+    check_shift(amount, line, column);
+    return (int64_t)((uint64_t)value << amount);
+}
+
+int64_t lox_shift_right(int64_t value, int64_t amount, int64_t line, int64_t column) {
+    check_shift(amount, line, column);
+    return value >> amount;
+}
+
+int64_t lox_power(int64_t base, int64_t exponent, int64_t line, int64_t column) {
+    int64_t result = 1;
+
+    if (exponent < 0) {
+        lox_runtime_error("Negative exponent of an integer power.", line, column);
+    }
+    if (exponent == 0) {
+        return 1;
+    }
+    while (exponent > 1) {
+        if (exponent & 1) {
+            result = lox_multiply(result, base, line, column);
+        }
+        exponent /= 2;
+        base = lox_multiply(base, base, line, column);
+    }
+    return lox_multiply(result, base, line, column);
+}
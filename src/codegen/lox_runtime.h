@@ -0,0 +1,80 @@
+/*
+ * Runtime of compiled programs, in portable C99. Generated C includes this
+ * header, the runtime itself is `lox_runtime.c`, compiled on its own and
+ * linked into every native executable.
+ *
+ * Integers are int64_t, floats double, booleans bool, chars uint32_t code
+ * points and strings NUL-terminated UTF-8 that is never freed. Functions
+ * called from assembly take and return 64-bit words.
+ */
+
+#ifndef LOX_RUNTIME_H
+#define LOX_RUNTIME_H
+
+#include <inttypes.h>
+#include <math.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#define LOX_EXIT_RUNTIME_ERROR 70
+
+/* Defined by the generated assembly or C, empty when the source had no name. */
+extern const char lox_source_name[];
+
+/* Reports an error like the interpreter does and stops the program. */
+void lox_runtime_error(const char *message, int64_t line, int64_t column);
+
+/* Calls that can be nested, the limit of the interpreter. */
+#define LOX_MAX_CALL_DEPTH 1024
+
+/* Calls running, counted around each call so deep recursion stops with
+ * the interpreter's error rather than a crash. */
+extern int64_t lox_call_depth;
+
+/* Counts a call about to start. */
+void lox_enter(int64_t line, int64_t column);
+
+/* Counts a call that returned. */
+void lox_leave(void);
+
+void lox_print_int(int64_t value);
+void lox_print_float(double value);
+void lox_print_bool(int64_t value);
+void lox_print_char(int64_t value);
+void lox_print_nil(void);
+
+/* Functions returning a string without reaching `return` give nil. */
+void lox_print_string(const char *value);
+
+const char *lox_concat(const char *left, const char *right);
+const char *lox_concat_char(const char *left, int64_t right);
+const char *lox_char_concat(int64_t left, const char *right);
+
+/* Orders strings by their bytes, like Rust compares `str`. */
+int64_t lox_compare_strings(const char *left, const char *right);
+
+/* Integer operators, checked like the interpreter's. */
+int64_t lox_add(int64_t left, int64_t right, int64_t line, int64_t column);
+int64_t lox_subtract(int64_t left, int64_t right, int64_t line, int64_t column);
+int64_t lox_multiply(int64_t left, int64_t right, int64_t line, int64_t column);
+int64_t lox_divide(int64_t left, int64_t right, int64_t line, int64_t column);
+int64_t lox_modulo(int64_t left, int64_t right, int64_t line, int64_t column);
+int64_t lox_negate(int64_t value, int64_t line, int64_t column);
+
+/* Adds `delta` to the variable, returns its previous value. */
+int64_t lox_increment(int64_t *variable, int64_t delta, int64_t line, int64_t column);
+
+/* Shifts the bits out like Rust's `<<`, signed overflow is undefined in C. */
+int64_t lox_shift_left(int64_t value, int64_t amount, int64_t line, int64_t column);
+int64_t lox_shift_right(int64_t value, int64_t amount, int64_t line, int64_t column);
+
+/*
+ * Raises an integer to a power, squaring like Rust's `checked_pow` so that
+ * it overflows exactly when the interpreter does.
+ */
+int64_t lox_power(int64_t base, int64_t exponent, int64_t line, int64_t column);
+
+#endif
@@ -0,0 +1,9 @@
+pub mod c99;
+#[cfg(test)]
+mod c99_test;
+pub mod codegen_error;
+pub mod lowering;
+pub mod toolchain;
+pub mod x86_64;
+#[cfg(test)]
+mod x86_64_test;
@@ -0,0 +1,131 @@
+#![allow(dead_code)]
+
+use std::{
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// C source of the runtime linked into native executables, compiled on its
+/// own next to the assembly or generated C.
+pub const RUNTIME: &str = include_str!("lox_runtime.c");
+
+/// File name of the runtime source.
+pub const RUNTIME_SOURCE: &str = "lox_runtime.c";
+
+/// Declarations of the runtime, included by generated C and the runtime.
+pub const HEADER: &str = include_str!("lox_runtime.h");
+
+/// File name generated C includes the runtime by.
+pub const RUNTIME_HEADER: &str = "lox_runtime.h";
+
+#[derive(Debug)]
+pub enum LinkError {
+    /// The intermediate files could not be written or the compiler started.
+    Io(io::Error),
+    /// The compiler ran and failed, with what it printed.
+    Failed(String),
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::Io(error) => write!(f, "{}", error),
+            LinkError::Failed(output) => write!(f, "the C compiler failed\n{}", output.trim_end()),
+        }
+    }
+}
+
+impl Error for LinkError {}
+
+impl From<io::Error> for LinkError {
+    fn from(error: io::Error) -> Self {
+        LinkError::Io(error)
+    }
+}
+
+/// The C compiler driving the system assembler and linker, `$CC` or `cc`.
+pub fn compiler() -> String {
+    std::env::var("CC")
+        .ok()
+        .filter(|cc| !cc.is_empty())
+        .unwrap_or_else(|| "cc".to_owned())
+}
+
+/// Assembles `assembly` and links it with the runtime into the executable
+/// `output`. The intermediate files go to a temporary directory.
+pub fn link(assembly: &str, output: &Path) -> Result<(), LinkError> {
+    build(
+        &[
+            ("program.s", assembly),
+            (RUNTIME_HEADER, HEADER),
+            (RUNTIME_SOURCE, RUNTIME),
+        ],
+        &["program.s", RUNTIME_SOURCE],
+        output,
+    )
+}
+
+/// Compiles a C translation unit including the runtime header and links it
+/// with the runtime into the executable `output`.
+pub fn compile_c(source: &str, output: &Path) -> Result<(), LinkError> {
+    build(
+        &[
+            ("program.c", source),
+            (RUNTIME_HEADER, HEADER),
+            (RUNTIME_SOURCE, RUNTIME),
+        ],
+        &["program.c", RUNTIME_SOURCE],
+        output,
+    )
+}
+
+/// Writes `files` to a temporary directory and compiles `inputs` among them.
+fn build(files: &[(&str, &str)], inputs: &[&str], output: &Path) -> Result<(), LinkError> {
+    let directory = temporary_directory()?;
+    let result = build_in(&directory, files, inputs, output);
+    let _ = fs::remove_dir_all(&directory);
+    result
+}
+
+fn build_in(
+    directory: &Path,
+    files: &[(&str, &str)],
+    inputs: &[&str],
+    output: &Path,
+) -> Result<(), LinkError> {
+    for (name, contents) in files {
+        fs::write(directory.join(name), contents)?;
+    }
+
+    let result = Command::new(compiler())
+        .arg("-std=c99")
+        .arg("-O2")
+        .arg("-o")
+        .arg(output)
+        .args(inputs.iter().map(|input| directory.join(input)))
+        .arg("-lm")
+        .output()?;
+
+    if !result.status.success() {
+        return Err(LinkError::Failed(
+            String::from_utf8_lossy(&result.stderr).into_owned(),
+        ));
+    }
+    Ok(())
+}
+
+fn temporary_directory() -> io::Result<PathBuf> {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let name = format!(
+        "lox-native-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    );
+    let directory = std::env::temp_dir().join(name);
+    fs::create_dir_all(&directory)?;
+    Ok(directory)
+}
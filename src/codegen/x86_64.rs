@@ -0,0 +1,1093 @@
+#![allow(dead_code)]
+
+use std::{collections::HashMap, fmt::Write};
+
+use super::lowering::{self, CodegenResult};
+use crate::{
+    ast::tree_types::*,
+    interpreter::evaluator::MAX_CALL_DEPTH,
+    semantic::static_type::{Signature, StaticType},
+    types::span::Span,
+};
+
+/// Registers passing integer, boolean, char and string arguments.
+const INTEGER_ARGUMENTS: [&str; 6] = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
+/// Number of `xmm` registers passing float arguments.
+const FLOAT_ARGUMENTS: usize = 8;
+
+/// Bits of the float `1.0`, added by `++` and `--`.
+const FLOAT_ONE: u64 = 0x3FF0_0000_0000_0000;
+
+const OVERFLOW: &str = "Integer overflow.";
+const DIVISION_BY_ZERO: &str = "Division by zero.";
+const SHIFT_OUT_OF_RANGE: &str = "Shift amount out of range.";
+const STACK_OVERFLOW: &str = "Stack overflow.";
+
+/// Lowers a type checked program to x86-64 assembly for the GNU assembler,
+/// following the System V calling convention. The program starts at `main`
+/// and calls the `lox_*` functions of the runtime in `lox_runtime.c`.
+///
+/// Every value is one 64-bit word, left in `rax` by the code computing it:
+/// integers, booleans as 0 or 1, chars as code points, floats as their bits
+/// and strings as pointers to NUL-terminated UTF-8. Only programs whose
+/// types are all known can be lowered, and functions must be declared at
+/// the top level and called by name.
+#[derive(Debug, Clone, Default)]
+pub struct CodeGenerator {
+    /// Name of the source file, shown by runtime errors.
+    source_name: Option<String>,
+}
+
+/// Where a variable lives.
+#[derive(Debug, Clone)]
+enum Storage {
+    /// A labelled word in `.bss`.
+    Global(String),
+    /// A slot of the frame, this many bytes below `rbp`.
+    Local(usize),
+}
+
+impl Storage {
+    fn operand(&self) -> String {
+        match self {
+            Storage::Global(label) => format!("qword ptr [rip + {}]", label),
+            Storage::Local(offset) => format!("qword ptr [rbp - {}]", offset),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Variable {
+    static_type: StaticType,
+    storage: Storage,
+}
+
+#[derive(Debug, Clone)]
+struct Function {
+    label: String,
+    signature: Signature,
+}
+
+/// Register receiving an argument.
+#[derive(Debug, Clone, Copy)]
+enum Argument {
+    Integer(&'static str),
+    Float(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Loop {
+    continue_label: String,
+    break_label: String,
+}
+
+/// The function being emitted.
+#[derive(Debug, Clone)]
+struct Frame {
+    code: String,
+    /// Runtime error calls, placed after the function's code.
+    stubs: String,
+    /// Bytes of the frame below `rbp` used by local slots.
+    size: usize,
+    /// Words pushed on the stack by the expression being emitted, calls
+    /// pad the stack when it is odd.
+    pushed: usize,
+    return_type: StaticType,
+    return_label: String,
+    loops: Vec<Loop>,
+    scopes: Vec<HashMap<String, Variable>>,
+}
+
+impl Frame {
+    fn new(return_type: StaticType, return_label: String) -> Self {
+        Self {
+            code: String::new(),
+            stubs: String::new(),
+            size: 0,
+            pushed: 0,
+            return_type,
+            return_label,
+            loops: vec![],
+            scopes: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TempState {
+    frame: Frame,
+    context: lowering::Context<Function, Variable>,
+    global_labels: Vec<String>,
+    /// String literals and runtime error messages, in `.rodata`.
+    strings: Vec<String>,
+    labels: usize,
+}
+
+impl TempState {
+    fn new() -> Self {
+        Self {
+            frame: Frame::new(StaticType::Void, ".Lreturn_main".to_owned()),
+            context: lowering::Context::new("native code"),
+            global_labels: vec![],
+            strings: vec![],
+            labels: 0,
+        }
+    }
+
+    fn emit(&mut self, instruction: &str) {
+        let _ = writeln!(self.frame.code, "    {}", instruction);
+    }
+
+    fn place(&mut self, label: &str) {
+        let _ = writeln!(self.frame.code, "{}:", label);
+    }
+
+    fn label(&mut self) -> String {
+        self.labels += 1;
+        format!(".L{}", self.labels)
+    }
+
+    fn string(&mut self, value: &str) -> String {
+        let index = match self.strings.iter().position(|s| s == value) {
+            Some(index) => index,
+            None => {
+                self.strings.push(value.to_owned());
+                self.strings.len() - 1
+            }
+        };
+        format!(".Lstr{}", index)
+    }
+
+    fn push(&mut self, register: &str) {
+        self.emit(&format!("push {}", register));
+        self.frame.pushed += 1;
+    }
+
+    fn pop(&mut self, register: &str) {
+        self.emit(&format!("pop {}", register));
+        self.frame.pushed -= 1;
+    }
+
+    /// Calls `function` with the stack aligned to 16 bytes.
+    fn call(&mut self, function: &str) {
+        let padded = self.frame.pushed % 2 == 1;
+        if padded {
+            self.emit("sub rsp, 8");
+        }
+        self.emit(&format!("call {}", function));
+        if padded {
+            self.emit("add rsp, 8");
+        }
+    }
+
+    /// Jumps to a runtime error reporting `message` at the current location
+    /// when `jump` is taken.
+    fn check(&mut self, jump: &str, message: &str) {
+        let label = self.label();
+        let message = self.string(message);
+        self.emit(&format!("{} {}", jump, label));
+
+        let location = self.context.location;
+        let stubs = &mut self.frame.stubs;
+        let _ = writeln!(stubs, "{}:", label);
+        let _ = writeln!(stubs, "    lea rdi, [rip + {}]", message);
+        let _ = writeln!(stubs, "    mov rsi, {}", location.line);
+        let _ = writeln!(stubs, "    mov rdx, {}", location.column);
+        let _ = writeln!(stubs, "    and rsp, -16");
+        let _ = writeln!(stubs, "    call lox_runtime_error@PLT");
+    }
+
+    /// Reserves a frame slot, returns its offset below `rbp`.
+    fn allocate(&mut self) -> usize {
+        self.frame.size += 8;
+        self.frame.size
+    }
+
+    fn declare(&mut self, name: &str, static_type: StaticType) -> Storage {
+        let storage = if self.frame.scopes.is_empty() {
+            let label = format!("lox_global_{}", self.global_labels.len());
+            self.global_labels.push(label.clone());
+            Storage::Global(label)
+        } else {
+            Storage::Local(self.allocate())
+        };
+        let variable = Variable {
+            static_type,
+            storage: storage.clone(),
+        };
+        match self.frame.scopes.last_mut() {
+            Some(scope) => scope.insert(name.to_owned(), variable),
+            None => self.context.globals.insert(name.to_owned(), variable),
+        };
+        storage
+    }
+
+    fn lookup(&self, name: &str) -> CodegenResult<Variable> {
+        let local = self
+            .frame
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name));
+        if let Some(variable) = local {
+            return Ok(variable.clone());
+        }
+        if self.context.functions.contains_key(name) {
+            return Err(self.context.function_value(name, self.context.location));
+        }
+        self.context.globals.get(name).cloned().ok_or_else(|| {
+            self.context
+                .error(&format!("Undefined variable '{}'.", name))
+        })
+    }
+
+    /// Converts the int in `rax` to a float when a float is expected.
+    fn widen(&mut self, found: &StaticType, expected: &StaticType) {
+        if *found == StaticType::Int && *expected == StaticType::Float {
+            self.emit("cvtsi2sd xmm0, rax");
+            self.emit("movq rax, xmm0");
+        }
+    }
+
+    fn expect(&self, expected: &StaticType, found: &StaticType) -> CodegenResult {
+        lowering::expect(expected, found, self.context.location)
+    }
+}
+
+impl CodeGenerator {
+    pub fn new() -> Self {
+        Self { source_name: None }
+    }
+
+    /// Runtime errors of the program will name `source_name`.
+    pub fn with_source_name(source_name: &str) -> Self {
+        Self {
+            source_name: Some(source_name.to_owned()),
+        }
+    }
+
+    /// Generates the assembly of a whole program.
+    pub fn generate(&self, statements: &[Statement]) -> CodegenResult<String> {
+        let mut state = TempState::new();
+
+        // Functions can be called before their declaration.
+        for statement in statements {
+            if let Statement::Function {
+                name,
+                parameters,
+                return_type,
+                span,
+                ..
+            } = statement
+            {
+                state.context.location = *span;
+                Self::declare_function(&mut state, name, parameters, *return_type)?;
+            }
+        }
+
+        for statement in statements {
+            if !matches!(statement, Statement::Function { .. }) {
+                self.statement(&mut state, statement)?;
+            }
+        }
+        let mut text = Self::finish(&mut state, "main", "main", "xor eax, eax");
+
+        // Bodies come last so they see every global.
+        for statement in statements {
+            if let Statement::Function {
+                name,
+                parameters,
+                body,
+                span,
+                ..
+            } = statement
+            {
+                state.context.location = *span;
+                self.function(&mut state, name, parameters, body)?;
+                let label = state.context.functions[name].label.clone();
+                text += &Self::finish(&mut state, &label, name, "");
+            }
+        }
+
+        let mut output = String::new();
+        let source_name = self.source_name.as_deref().unwrap_or("");
+        let _ = writeln!(output, "    .intel_syntax noprefix");
+        let _ = writeln!(output, "    .text");
+        let _ = writeln!(output, "    .globl main");
+        output += &text;
+
+        let _ = writeln!(output, "    .section .rodata");
+        let _ = writeln!(output, "    .globl lox_source_name");
+        let _ = writeln!(output, "lox_source_name:");
+        let _ = writeln!(output, "    .asciz \"{}\"", escape(source_name));
+        for (index, value) in state.strings.iter().enumerate() {
+            let _ = writeln!(output, ".Lstr{}:", index);
+            let _ = writeln!(output, "    .asciz \"{}\"", escape(value));
+        }
+
+        if !state.global_labels.is_empty() {
+            let _ = writeln!(output, "    .bss");
+            let _ = writeln!(output, "    .p2align 3");
+            for label in &state.global_labels {
+                let _ = writeln!(output, "{}:", label);
+                let _ = writeln!(output, "    .zero 8");
+            }
+        }
+        let _ = writeln!(output, "    .section .note.GNU-stack,\"\",@progbits");
+
+        Ok(output)
+    }
+
+    fn declare_function(
+        state: &mut TempState,
+        name: &str,
+        parameters: &[Parameter],
+        return_type: Option<DataType>,
+    ) -> CodegenResult {
+        if state.context.functions.contains_key(name) {
+            return Err(state
+                .context
+                .duplicate_function(name, state.context.location));
+        }
+
+        let signature = state.context.signature(parameters, return_type)?;
+        Self::arguments(state, &signature)?;
+
+        let function = Function {
+            label: format!("lox_fn_{}", state.context.functions.len()),
+            signature,
+        };
+        state.context.functions.insert(name.to_owned(), function);
+        Ok(())
+    }
+
+    /// Registers receiving the arguments of a function.
+    fn arguments(state: &TempState, signature: &Signature) -> CodegenResult<Vec<Argument>> {
+        let (mut integers, mut floats) = (0, 0);
+        let mut arguments = vec![];
+        for parameter in &signature.parameters {
+            if *parameter == StaticType::Float {
+                arguments.push(Argument::Float(floats));
+                floats += 1;
+            } else {
+                arguments.push(Argument::Integer(
+                    INTEGER_ARGUMENTS.get(integers).copied().unwrap_or_default(),
+                ));
+                integers += 1;
+            }
+        }
+
+        if integers > INTEGER_ARGUMENTS.len() || floats > FLOAT_ARGUMENTS {
+            return Err(state.context.error(&format!(
+                "Native code passes at most {} float and {} other arguments in registers.",
+                FLOAT_ARGUMENTS,
+                INTEGER_ARGUMENTS.len()
+            )));
+        }
+        Ok(arguments)
+    }
+
+    /// Wraps the code of the current frame into a function and starts a new
+    /// frame. `epilogue` runs before returning.
+    fn finish(state: &mut TempState, label: &str, name: &str, epilogue: &str) -> String {
+        let frame = std::mem::replace(
+            &mut state.frame,
+            Frame::new(StaticType::Void, String::new()),
+        );
+
+        let mut text = String::new();
+        if label != name {
+            let _ = writeln!(text, "# fun {}", name);
+        }
+        let _ = writeln!(text, "{}:", label);
+        let _ = writeln!(text, "    push rbp");
+        let _ = writeln!(text, "    mov rbp, rsp");
+        // Keeps `rsp` a multiple of 16 between pushes.
+        let size = frame.size.next_multiple_of(16);
+        if size > 0 {
+            let _ = writeln!(text, "    sub rsp, {}", size);
+        }
+        text += &frame.code;
+        let _ = writeln!(text, "{}:", frame.return_label);
+        if !epilogue.is_empty() {
+            let _ = writeln!(text, "    {}", epilogue);
+        }
+        let _ = writeln!(text, "    leave");
+        let _ = writeln!(text, "    ret");
+        text += &frame.stubs;
+        text
+    }
+
+    fn function(
+        &self,
+        state: &mut TempState,
+        name: &str,
+        parameters: &[Parameter],
+        body: &[Statement],
+    ) -> CodegenResult {
+        let function = state.context.functions[name].clone();
+        let return_label = format!(".Lreturn_{}", function.label);
+        state.frame = Frame::new(function.signature.return_type.clone(), return_label);
+        state.frame.scopes.push(HashMap::new());
+
+        let arguments = Self::arguments(state, &function.signature)?;
+        for ((parameter, static_type), argument) in parameters
+            .iter()
+            .zip(&function.signature.parameters)
+            .zip(arguments)
+        {
+            let storage = state.declare(&parameter.name, static_type.clone());
+            let instruction = match argument {
+                Argument::Integer(register) => format!("mov {}, {}", storage.operand(), register),
+                Argument::Float(index) => format!("movq {}, xmm{}", storage.operand(), index),
+            };
+            state.emit(&instruction);
+        }
+
+        for statement in body {
+            self.statement(state, statement)?;
+        }
+        // Falling off the end returns nil, a null pointer for strings.
+        state.emit("xor eax, eax");
+        Ok(())
+    }
+
+    fn statement(&self, state: &mut TempState, statement: &Statement) -> CodegenResult {
+        match statement {
+            Statement::Expression(expression, _) => {
+                state.context.location = expression.span();
+                self.expression(state, expression)?;
+            }
+            Statement::Print(expression, _) => {
+                state.context.location = expression.span();
+                let static_type = self.expression(state, expression)?;
+                Self::print(state, &static_type)?;
+            }
+            Statement::Variable {
+                data_type,
+                name,
+                initializer,
+                span,
+            } => {
+                state.context.location = *span;
+                let static_type = match initializer {
+                    Some(expression) => {
+                        let found = self.expression(state, expression)?;
+                        let expected =
+                            state
+                                .context
+                                .variable(name, *data_type, Some(&found), *span)?;
+                        state.widen(&found, &expected);
+                        expected
+                    }
+                    None => {
+                        let expected = state.context.variable(name, *data_type, None, *span)?;
+                        Self::default_value(state, &expected);
+                        expected
+                    }
+                };
+                let storage = state.declare(name, static_type);
+                state.emit(&format!("mov {}, rax", storage.operand()));
+            }
+            Statement::Block(statements, _) => {
+                state.frame.scopes.push(HashMap::new());
+                for statement in statements {
+                    self.statement(state, statement)?;
+                }
+                state.frame.scopes.pop();
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => {
+                state.context.location = *span;
+                let else_label = state.label();
+                self.condition(state, condition, &else_label)?;
+                self.statement(state, then_branch)?;
+
+                match else_branch {
+                    Some(else_branch) => {
+                        let end = state.label();
+                        state.emit(&format!("jmp {}", end));
+                        state.place(&else_label);
+                        self.statement(state, else_branch)?;
+                        state.place(&end);
+                    }
+                    None => state.place(&else_label),
+                }
+            }
+            Statement::While {
+                condition,
+                body,
+                span,
+            } => {
+                state.context.location = *span;
+                let (start, end) = (state.label(), state.label());
+                state.place(&start);
+                self.condition(state, condition, &end)?;
+                self.loop_body(state, body, &start, &end)?;
+                state.emit(&format!("jmp {}", start));
+                state.place(&end);
+            }
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                span,
+            } => {
+                state.frame.scopes.push(HashMap::new());
+                if let Some(initializer) = initializer {
+                    self.statement(state, initializer)?;
+                }
+
+                state.context.location = *span;
+                let (start, next, end) = (state.label(), state.label(), state.label());
+                state.place(&start);
+                if let Some(condition) = condition {
+                    self.condition(state, condition, &end)?;
+                }
+                self.loop_body(state, body, &next, &end)?;
+                state.place(&next);
+                if let Some(increment) = increment {
+                    self.expression(state, increment)?;
+                }
+                state.emit(&format!("jmp {}", start));
+                state.place(&end);
+                state.frame.scopes.pop();
+            }
+            Statement::Return(value, span) => {
+                state.context.location = *span;
+                if let Some(value) = value {
+                    let found = self.expression(state, value)?;
+                    state.context.location = *span;
+                    let expected = state.frame.return_type.clone();
+                    state.context.return_value(&expected, &found, *span)?;
+                    state.widen(&found, &expected);
+                    if expected == StaticType::Float {
+                        state.emit("movq xmm0, rax");
+                    }
+                }
+                let label = state.frame.return_label.clone();
+                state.emit(&format!("jmp {}", label));
+            }
+            Statement::Break(span) | Statement::Continue(span) => {
+                state.context.location = *span;
+                let innermost = state.frame.loops.last().unwrap();
+                let label = match statement {
+                    Statement::Break(_) => innermost.break_label.clone(),
+                    _ => innermost.continue_label.clone(),
+                };
+                state.emit(&format!("jmp {}", label));
+            }
+            Statement::Function { span, .. } => {
+                return Err(state.context.nested_function(*span));
+            }
+        }
+        Ok(())
+    }
+
+    fn loop_body(
+        &self,
+        state: &mut TempState,
+        body: &Statement,
+        continue_label: &str,
+        break_label: &str,
+    ) -> CodegenResult {
+        state.frame.loops.push(Loop {
+            continue_label: continue_label.to_owned(),
+            break_label: break_label.to_owned(),
+        });
+        self.statement(state, body)?;
+        state.frame.loops.pop();
+        Ok(())
+    }
+
+    /// Jumps to `otherwise` when the condition is false.
+    fn condition(
+        &self,
+        state: &mut TempState,
+        condition: &Expresion,
+        otherwise: &str,
+    ) -> CodegenResult {
+        let found = self.expression(state, condition)?;
+        state.context.location = condition.span();
+        state.expect(&StaticType::Bool, &found)?;
+        state.emit("test rax, rax");
+        state.emit(&format!("jz {}", otherwise));
+        Ok(())
+    }
+
+    fn default_value(state: &mut TempState, static_type: &StaticType) {
+        match static_type {
+            StaticType::String => {
+                let label = state.string("");
+                state.emit(&format!("lea rax, [rip + {}]", label));
+            }
+            // Zero is also 0.0, false and '\0'.
+            _ => state.emit("xor eax, eax"),
+        }
+    }
+
+    fn print(state: &mut TempState, static_type: &StaticType) -> CodegenResult {
+        let function = match static_type {
+            StaticType::Int => "lox_print_int",
+            StaticType::Float => {
+                state.emit("movq xmm0, rax");
+                "lox_print_float"
+            }
+            StaticType::Bool => "lox_print_bool",
+            StaticType::Char => "lox_print_char",
+            StaticType::String => "lox_print_string",
+            StaticType::Nil | StaticType::Void => "lox_print_nil",
+            other => {
+                return Err(state.context.error(&format!(
+                    "Native code cannot print a value of type {}.",
+                    other
+                )))
+            }
+        };
+        state.emit("mov rdi, rax");
+        state.call(&format!("{}@PLT", function));
+        Ok(())
+    }
+
+    /// Emits the expression, leaving its value in `rax`, returns its type.
+    fn expression(
+        &self,
+        state: &mut TempState,
+        expression: &Expresion,
+    ) -> CodegenResult<StaticType> {
+        let static_type = match expression {
+            Expresion::Unknown(literal, _) => {
+                return Err(state
+                    .context
+                    .error(&format!("Unknown expression '{}'.", literal)))
+            }
+            Expresion::Integer(value, _) => {
+                state.emit(&format!("mov rax, {}", value));
+                StaticType::Int
+            }
+            Expresion::Float(value, _) => {
+                state.emit(&format!("mov rax, {:#x}", value.to_bits()));
+                StaticType::Float
+            }
+            Expresion::Str(value, _) => {
+                let label = state.string(value);
+                state.emit(&format!("lea rax, [rip + {}]", label));
+                StaticType::String
+            }
+            Expresion::Char(value, _) => {
+                state.emit(&format!("mov eax, {}", *value as u32));
+                StaticType::Char
+            }
+            Expresion::Bool(value, _) => {
+                state.emit(&format!("mov eax, {}", *value as u8));
+                StaticType::Bool
+            }
+            Expresion::Nil(_) => {
+                state.emit("xor eax, eax");
+                StaticType::Nil
+            }
+            Expresion::Grouping(expression, _) => self.expression(state, expression)?,
+            Expresion::Variable(name, span) => {
+                state.context.location = *span;
+                let variable = state.lookup(name)?;
+                state.emit(&format!("mov rax, {}", variable.storage.operand()));
+                variable.static_type
+            }
+            Expresion::Assign(name, value, span) => {
+                let found = self.expression(state, value)?;
+                state.context.location = *span;
+                let variable = state.lookup(name)?;
+                state.expect(&variable.static_type, &found)?;
+                state.widen(&found, &variable.static_type);
+                state.emit(&format!("mov {}, rax", variable.storage.operand()));
+                variable.static_type
+            }
+            Expresion::Postfix(name, operator, span) => {
+                state.context.location = *span;
+                let variable = state.lookup(name)?;
+                Self::postfix(state, &variable, operator)?;
+                variable.static_type
+            }
+            Expresion::Ternary(condition, then_branch, else_branch, _) => {
+                self.ternary(state, condition, then_branch, else_branch)?
+            }
+            Expresion::Unary(operator, right, span) => {
+                let found = self.expression(state, right)?;
+                state.context.location = *span;
+                Self::unary(state, operator, found)?
+            }
+            Expresion::Binary(left, operator, right, span) => {
+                self.binary(state, left, operator, right, *span)?
+            }
+            Expresion::Call(callee, arguments, span) => {
+                self.call(state, callee, arguments, *span)?
+            }
+        };
+        Ok(static_type)
+    }
+
+    fn postfix(state: &mut TempState, variable: &Variable, operator: &str) -> CodegenResult {
+        let operand = variable.storage.operand();
+        let instruction = if operator == "++" { "add" } else { "sub" };
+
+        state.emit(&format!("mov rax, {}", operand));
+        match variable.static_type {
+            StaticType::Int => {
+                state.emit("mov rcx, rax");
+                state.emit(&format!("{} rcx, 1", instruction));
+                state.check("jo", OVERFLOW);
+            }
+            StaticType::Float => {
+                state.emit("movq xmm0, rax");
+                state.emit(&format!("mov rcx, {:#x}", FLOAT_ONE));
+                state.emit("movq xmm1, rcx");
+                state.emit(&format!("{}sd xmm0, xmm1", instruction));
+                state.emit("movq rcx, xmm0");
+            }
+            ref other => {
+                return Err(state.context.error(&format!(
+                    "Operator '{}' cannot be applied to {}.",
+                    operator, other
+                )))
+            }
+        }
+        state.emit(&format!("mov {}, rcx", operand));
+        Ok(())
+    }
+
+    fn ternary(
+        &self,
+        state: &mut TempState,
+        condition: &Expresion,
+        then_branch: &Expresion,
+        else_branch: &Expresion,
+    ) -> CodegenResult<StaticType> {
+        let (otherwise, end) = (state.label(), state.label());
+        self.condition(state, condition, &otherwise)?;
+
+        // Both branches are emitted before their common type is known, the
+        // int one is widened afterwards when the other is a float.
+        let then_type = self.expression(state, then_branch)?;
+        let then_widening = state.label();
+        state.emit(&format!("jmp {}", then_widening));
+        state.place(&otherwise);
+        let else_type = self.expression(state, else_branch)?;
+
+        let static_type = state
+            .context
+            .ternary(&then_type, &else_type, state.context.location)?;
+
+        state.widen(&else_type, &static_type);
+        state.emit(&format!("jmp {}", end));
+        state.place(&then_widening);
+        state.widen(&then_type, &static_type);
+        state.place(&end);
+        Ok(static_type)
+    }
+
+    fn unary(
+        state: &mut TempState,
+        operator: &str,
+        right: StaticType,
+    ) -> CodegenResult<StaticType> {
+        let result = lowering::unary(operator, &right, state.context.location)?;
+
+        match (operator, &right) {
+            ("-", StaticType::Int) => {
+                state.emit("neg rax");
+                state.check("jo", OVERFLOW);
+            }
+            ("-", _) => state.emit("btc rax, 63"),
+            ("!", _) => state.emit("xor eax, 1"),
+            _ => state.emit("not rax"),
+        }
+        Ok(result)
+    }
+
+    fn binary(
+        &self,
+        state: &mut TempState,
+        left: &Expresion,
+        operator: &str,
+        right: &Expresion,
+        span: Span,
+    ) -> CodegenResult<StaticType> {
+        let left_type = self.expression(state, left)?;
+
+        if operator == "&&" || operator == "||" {
+            // Booleans are 0 or 1, the left value is the result when it
+            // decides the outcome.
+            let end = state.label();
+            state.context.location = span;
+            state.expect(&StaticType::Bool, &left_type)?;
+            state.emit("test rax, rax");
+            state.emit(&format!(
+                "{} {}",
+                if operator == "&&" { "jz" } else { "jnz" },
+                end
+            ));
+            let right_type = self.expression(state, right)?;
+            state.context.location = span;
+            state.expect(&StaticType::Bool, &right_type)?;
+            state.place(&end);
+            return Ok(StaticType::Bool);
+        }
+
+        state.push("rax");
+        let right_type = self.expression(state, right)?;
+        state.emit("mov rcx, rax");
+        state.pop("rax");
+        state.context.location = span;
+
+        let result = lowering::binary(&left_type, operator, &right_type, span)?;
+
+        use StaticType::*;
+        match (&left_type, &right_type) {
+            (Int, Int) => Self::integer(state, operator),
+            (a, b) if a.is_numeric() && b.is_numeric() => Self::float(state, operator, a, b),
+            (String, String) if operator == "+" => Self::runtime(state, "lox_concat"),
+            (String, Char) => Self::runtime(state, "lox_concat_char"),
+            (Char, String) => Self::runtime(state, "lox_char_concat"),
+            (String, String) => {
+                Self::runtime(state, "lox_compare_strings");
+                state.emit("xor ecx, ecx");
+                Self::compare(state, operator, "rax", "rcx");
+            }
+            (Char, Char) | (Bool, Bool) => Self::integer(state, operator),
+            (Nil | Void, Nil | Void) => {
+                state.emit(&format!("mov eax, {}", (operator == "==") as u8))
+            }
+            // Values of different types are never equal.
+            _ => state.emit(&format!("mov eax, {}", (operator == "!=") as u8)),
+        }
+        Ok(result)
+    }
+
+    /// Calls a runtime function on the operands in `rax` and `rcx`.
+    fn runtime(state: &mut TempState, function: &str) {
+        state.emit("mov rdi, rax");
+        state.emit("mov rsi, rcx");
+        state.call(&format!("{}@PLT", function));
+    }
+
+    /// Sets `rax` to the comparison of `left` with `right`.
+    fn compare(state: &mut TempState, operator: &str, left: &str, right: &str) {
+        let condition = match operator {
+            "==" => "e",
+            "!=" => "ne",
+            "<" => "l",
+            "<=" => "le",
+            ">" => "g",
+            _ => "ge",
+        };
+        state.emit(&format!("cmp {}, {}", left, right));
+        state.emit(&format!("set{} al", condition));
+        state.emit("movzx eax, al");
+    }
+
+    /// Integer operators on `rax` and `rcx`, also used for the bitwise
+    /// operators of booleans and the comparisons of chars.
+    fn integer(state: &mut TempState, operator: &str) {
+        match operator {
+            "+" | "-" | "*" => {
+                let instruction = match operator {
+                    "+" => "add",
+                    "-" => "sub",
+                    _ => "imul",
+                };
+                state.emit(&format!("{} rax, rcx", instruction));
+                state.check("jo", OVERFLOW);
+            }
+            "/" | "%" => {
+                state.emit("test rcx, rcx");
+                state.check("jz", DIVISION_BY_ZERO);
+                // Dividing the smallest integer by -1 overflows, negating
+                // it is the one negation that does too.
+                let divide = state.label();
+                state.emit("cmp rcx, -1");
+                state.emit(&format!("jne {}", divide));
+                state.emit("mov rdx, rax");
+                state.emit("neg rdx");
+                state.check("jo", OVERFLOW);
+                state.place(&divide);
+                state.emit("cqo");
+                state.emit("idiv rcx");
+                if operator == "%" {
+                    state.emit("mov rax, rdx");
+                }
+            }
+            "**" => {
+                let location = state.context.location;
+                state.emit("mov rdi, rax");
+                state.emit("mov rsi, rcx");
+                state.emit(&format!("mov rdx, {}", location.line));
+                state.emit(&format!("mov rcx, {}", location.column));
+                state.call("lox_power@PLT");
+            }
+            "&" | "|" | "^" => {
+                let instruction = match operator {
+                    "&" => "and",
+                    "|" => "or",
+                    _ => "xor",
+                };
+                state.emit(&format!("{} rax, rcx", instruction));
+            }
+            "<<" | ">>" => {
+                // Negative amounts are above 63 as unsigned numbers.
+                state.emit("cmp rcx, 63");
+                state.check("ja", SHIFT_OUT_OF_RANGE);
+                let instruction = if operator == "<<" { "shl" } else { "sar" };
+                state.emit(&format!("{} rax, cl", instruction));
+            }
+            _ => Self::compare(state, operator, "rax", "rcx"),
+        }
+    }
+
+    /// Float operators on `rax` and `rcx`, integers are converted first.
+    fn float(state: &mut TempState, operator: &str, left: &StaticType, right: &StaticType) {
+        for (register, value, static_type) in [("xmm0", "rax", left), ("xmm1", "rcx", right)] {
+            let instruction = match static_type {
+                StaticType::Int => "cvtsi2sd",
+                _ => "movq",
+            };
+            state.emit(&format!("{} {}, {}", instruction, register, value));
+        }
+
+        match operator {
+            "+" | "-" | "*" | "/" => {
+                let instruction = match operator {
+                    "+" => "addsd",
+                    "-" => "subsd",
+                    "*" => "mulsd",
+                    _ => "divsd",
+                };
+                state.emit(&format!("{} xmm0, xmm1", instruction));
+                state.emit("movq rax, xmm0");
+            }
+            "%" | "**" => {
+                state.call(if operator == "%" {
+                    "fmod@PLT"
+                } else {
+                    "pow@PLT"
+                });
+                state.emit("movq rax, xmm0");
+            }
+            // An unordered comparison, with a NaN, sets the zero, parity
+            // and carry flags.
+            "==" => {
+                state.emit("ucomisd xmm0, xmm1");
+                state.emit("sete al");
+                state.emit("setnp cl");
+                state.emit("and al, cl");
+                state.emit("movzx eax, al");
+            }
+            "!=" => {
+                state.emit("ucomisd xmm0, xmm1");
+                state.emit("setne al");
+                state.emit("setp cl");
+                state.emit("or al, cl");
+                state.emit("movzx eax, al");
+            }
+            _ => {
+                // `a < b` is tested as `b > a`, which is false for NaN.
+                let (first, second) = match operator {
+                    "<" | "<=" => ("xmm1", "xmm0"),
+                    _ => ("xmm0", "xmm1"),
+                };
+                let condition = match operator {
+                    "<" | ">" => "a",
+                    _ => "ae",
+                };
+                state.emit(&format!("ucomisd {}, {}", first, second));
+                state.emit(&format!("set{} al", condition));
+                state.emit("movzx eax, al");
+            }
+        }
+    }
+
+    fn call(
+        &self,
+        state: &mut TempState,
+        callee: &Expresion,
+        arguments: &[Expresion],
+        span: Span,
+    ) -> CodegenResult<StaticType> {
+        state.context.location = span;
+        let function = match callee {
+            Expresion::Variable(name, _)
+                if !state.frame.scopes.iter().any(|s| s.contains_key(name)) =>
+            {
+                state.context.functions.get(name).cloned()
+            }
+            _ => None,
+        };
+        let Some(function) = function else {
+            return Err(state.context.indirect_call(span));
+        };
+
+        let signature = &function.signature;
+        if arguments.len() != signature.parameters.len() {
+            let expected = signature.parameters.len();
+            return Err(lowering::argument_count(expected, arguments.len(), span));
+        }
+
+        for (argument, expected) in arguments.iter().zip(&signature.parameters) {
+            let found = self.expression(state, argument)?;
+            state.context.location = argument.span();
+            state.expect(expected, &found)?;
+            state.widen(&found, expected);
+            state.push("rax");
+        }
+
+        let registers = Self::arguments(state, signature)?;
+        for register in registers.iter().rev() {
+            match register {
+                Argument::Integer(register) => state.pop(register),
+                Argument::Float(index) => {
+                    state.pop("rax");
+                    state.emit(&format!("movq xmm{}, rax", index));
+                }
+            }
+        }
+
+        // Deep recursion stops where the interpreter's would, the counter
+        // lives in the runtime.
+        state.context.location = span;
+        state.emit("inc qword ptr [rip + lox_call_depth]");
+        state.emit(&format!(
+            "cmp qword ptr [rip + lox_call_depth], {}",
+            MAX_CALL_DEPTH
+        ));
+        state.check("jg", STACK_OVERFLOW);
+        state.call(&function.label);
+        state.emit("dec qword ptr [rip + lox_call_depth]");
+        if signature.return_type == StaticType::Float {
+            state.emit("movq rax, xmm0");
+        }
+        Ok(signature.return_type.clone())
+    }
+}
+
+/// Escapes a string for an `.asciz` directive, bytes other than printable
+/// ASCII are written in octal.
+fn escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'"' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            }
+            b' '..=b'~' => escaped.push(byte as char),
+            _ => {
+                let _ = write!(escaped, "\\{:03o}", byte);
+            }
+        }
+    }
+    escaped
+}
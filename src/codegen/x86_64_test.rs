@@ -0,0 +1,179 @@
+#![allow(unused_imports, dead_code)]
+
+use super::{codegen_error::CodegenError, toolchain, x86_64::CodeGenerator};
+use crate::{
+    interpreter::evaluator::MAX_CALL_DEPTH,
+    test_support::{interpret_output, parse, run_executable},
+    types::span::Span,
+};
+
+fn generate(input: &str) -> Result<String, CodegenError> {
+    CodeGenerator::with_source_name("test.lox").generate(&parse(input))
+}
+
+/// Builds and runs the program, `None` when no C compiler is installed.
+fn run_native(input: &str) -> Option<(i32, String, String)> {
+    if !cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        return None;
+    }
+    run_executable("lox-native-test", |executable| {
+        toolchain::link(&generate(input).unwrap(), executable).unwrap()
+    })
+}
+
+#[test]
+fn emits_functions_and_runtime_calls() {
+    let assembly = generate("int twice(int n) { return n * 2; }\nprint twice(4);").unwrap();
+
+    assert!(assembly.starts_with("    .intel_syntax noprefix\n"));
+    assert!(assembly.contains("\nmain:\n"));
+    assert!(assembly.contains("# fun twice\nlox_fn_0:\n"));
+    assert!(assembly.contains("    call lox_fn_0\n"));
+    assert!(assembly.contains("    imul rax, rcx\n    jo .L"));
+    assert!(assembly.contains("    call lox_print_int@PLT\n"));
+    assert!(assembly.contains("lox_source_name:\n    .asciz \"test.lox\"\n"));
+    assert!(assembly.contains("    .asciz \"Integer overflow.\"\n"));
+}
+
+#[test]
+fn counts_calls_against_the_interpreter_limit() {
+    let assembly = generate("fun f() {}\nf();").unwrap();
+
+    assert!(assembly.contains(&format!(
+        "    inc qword ptr [rip + lox_call_depth]\n    cmp qword ptr [rip + lox_call_depth], {}\n",
+        MAX_CALL_DEPTH
+    )));
+    assert!(assembly.contains("    call lox_fn_0\n    dec qword ptr [rip + lox_call_depth]\n"));
+    assert!(toolchain::HEADER.contains(&format!("#define LOX_MAX_CALL_DEPTH {}\n", MAX_CALL_DEPTH)));
+}
+
+#[test]
+fn stores_globals_and_escapes_strings() {
+    let assembly = generate("string s = \"a\\\"b\\n\\u{e9}\"; print s;").unwrap();
+
+    assert!(assembly.contains("    .asciz \"a\\\"b\\012\\303\\251\"\n"));
+    assert!(assembly.contains("    mov qword ptr [rip + lox_global_0], rax\n"));
+    assert!(assembly.contains("    .bss\n    .p2align 3\nlox_global_0:\n    .zero 8\n"));
+}
+
+#[test]
+fn rejects_what_native_code_cannot_express() {
+    let cases = [
+        (
+            "fun f(a) { print a; }",
+            "Parameter 'a' needs a type to compile to native code.",
+            Span::new(6, 7, 1, 7),
+        ),
+        (
+            "var a;",
+            "Variable 'a' needs a type or an initializer to compile to native code.",
+            Span::new(4, 5, 1, 5),
+        ),
+        (
+            "fun outer() {\n  fun inner() {}\n}",
+            "Native code only supports functions declared at the top level.",
+            Span::new(20, 25, 2, 7),
+        ),
+        (
+            "fun f() {}\nvar g = f;",
+            "Function 'f' can only be called in native code, not used as a value.",
+            Span::new(19, 20, 2, 9),
+        ),
+    ];
+
+    for (program, message, span) in cases {
+        assert_eq!(
+            generate(program),
+            Err(CodegenError::new(message, span)),
+            "{}",
+            program
+        );
+    }
+}
+
+#[test]
+fn native_programs_match_the_interpreter() {
+    let programs = [
+        "print 1 + 2 * 3; print 7 / 2; print 7 / 2.0; print -7 % 3; print 2 ** 10;
+         print 7.5 % 2; print 2 ** 0.5; print 6 & 3; print 6 | 3; print 6 ^ 3; print ~0;
+         print 1 << 4; print -16 >> 2; print true ^ false; print 1 > 2 ? \"yes\" : \"no\";",
+        "print 0.1 + 0.2; print 1e20; print 1.5e-7; print -0.0; print 1.0 / 3; print 100.0;
+         print 0.0 / 0 == 0.0 / 0; print 0.0 / 0 != 0.0 / 0; print 1 / 0.0; print -1 / 0.0;
+         print 1 == 1.0; print 2 > 1.5; print 1 <= 1.0;",
+        "int x = 10; x += 5; x -= 3; x *= 2; x /= 4; print x; print x++; print x; print ++x;
+         print x--; print --x; float f = 1; f++; print f; f = f / 4; print f;",
+        "string s = \"hello\"; print s + \" world\"; print s + '!' + \"\"; print '\\u{e9}' + s;
+         print \"b\" > \"a\"; print \"a\" == \"a\"; print s != \"hello\"; print 'a' < 'b';
+         string empty; char nul; bool b; print empty + \"|\"; print b; print nul == '\\0';",
+        "int sum = 0; for (int i = 0; i < 10; i++) { if (i == 2) continue; if (i == 7) break;
+         sum += i; } print sum; var n = 3; while (n > 0) { n--; { int n = 100; } } print n;
+         print true && false || !false; print nil; print 1 == true; print nil == nil;",
+        "int fib(int n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); }
+         float half(float x) { return x / 2; }
+         string join(string a, char c, string b) { return a + c + b; }
+         float mix(int a, float b, int c, float d) { return a + b + c + d; }
+         fun nothing() {}
+         print fib(20); print 1 + half(3); print join(\"a\", '-', \"b\"); print mix(1, 2, 3, 4.5);
+         print nothing(); int counter = 0;
+         fun bump() { counter = counter + 1; } bump(); bump(); print counter;",
+        "print 1; return; print 2;",
+        "print 0 ** 4294967296; print 1 ** 4294967297; print (-1) ** 4294967297;
+         print (-1) ** 4294967296; print 2 ** -1.0;",
+        "int d(int n) { if (n == 0) return 0; return 1 + d(n - 1); } print d(1023);",
+        "float f = 1.5; f = 3; print f / 2;",
+        "print (true ? 1 : 2.5) / 2; print (false ? 2.5 : 1) / 2;",
+    ];
+
+    for program in programs {
+        let Some((code, output, _)) = run_native(program) else {
+            return;
+        };
+        assert_eq!(code, 0, "{}", program);
+        assert_eq!(output, interpret_output(program).0, "{}", program);
+    }
+}
+
+#[test]
+fn native_runtime_errors_match_the_interpreter() {
+    let cases = [
+        ("int x = 1;\nprint x / 0;", "Division by zero.", "2:9"),
+        ("print 1 << 64;", "Shift amount out of range.", "1:9"),
+        ("print 2 ** 63;", "Integer overflow.", "1:9"),
+        ("print 2 ** 4294967296;", "Integer overflow.", "1:9"),
+        (
+            "int x = 2 ** -1;\nprint x;",
+            "Negative exponent of an integer power.",
+            "1:11",
+        ),
+        (
+            "int x = 9223372036854775807;\nx++;",
+            "Integer overflow.",
+            "2:1",
+        ),
+        (
+            "int f(int n) { return -n; }\nprint f(-9223372036854775807 - 1);",
+            "Integer overflow.",
+            "1:23",
+        ),
+        ("fun f() { f(); }\nf();", "Stack overflow.", "1:11"),
+        (
+            "int d(int n) { if (n == 0) return 0; return 1 + d(n - 1); }\nprint d(1024);",
+            "Stack overflow.",
+            "1:49",
+        ),
+    ];
+
+    for (program, message, location) in cases {
+        let Some((code, output, errors)) = run_native(program) else {
+            return;
+        };
+        assert_eq!(code, 70, "{}", program);
+        assert_eq!(output, interpret_output(program).0, "{}", program);
+        assert_eq!(
+            errors,
+            format!("error[E0300]: {}\n --> test.lox:{}\n", message, location),
+            "{}",
+            program
+        );
+    }
+}
@@ -0,0 +1,38 @@
+//! Error codes shown as `error[E0001]`, grouped by compiler stage.
+
+// Lexical errors.
+pub const UNEXPECTED_CHARACTER: &str = "E0001";
+pub const UNTERMINATED_COMMENT: &str = "E0002";
+pub const UNTERMINATED_STRING: &str = "E0003";
+pub const MALFORMED_NUMBER: &str = "E0004";
+pub const INVALID_ESCAPE: &str = "E0005";
+pub const UNTERMINATED_CHARACTER: &str = "E0006";
+pub const INVALID_CHARACTER_LITERAL: &str = "E0007";
+pub const NUMBER_OUT_OF_RANGE: &str = "E0008";
+
+// Syntax errors.
+pub const SYNTAX_ERROR: &str = "E0100";
+pub const INVALID_ASSIGNMENT: &str = "E0101";
+pub const LOOP_CONTROL_OUTSIDE_LOOP: &str = "E0102";
+
+// Type errors.
+pub const MISMATCHED_TYPES: &str = "E0200";
+pub const INVALID_OPERANDS: &str = "E0201";
+pub const NOT_CALLABLE: &str = "E0202";
+pub const ARGUMENT_COUNT: &str = "E0203";
+pub const INVALID_RETURN: &str = "E0204";
+
+// Name resolution errors.
+pub const UNDECLARED_VARIABLE: &str = "E0250";
+pub const DUPLICATE_DECLARATION: &str = "E0251";
+pub const USE_BEFORE_INITIALIZATION: &str = "E0252";
+
+// Code generation errors.
+pub const COMPILE_ERROR: &str = "E0400";
+pub const UNSUPPORTED_BY_BACKEND: &str = "E0401";
+
+// Runtime errors.
+pub const RUNTIME_ERROR: &str = "E0300";
+
+// Warnings.
+pub const SHADOWED_VARIABLE: &str = "W0001";
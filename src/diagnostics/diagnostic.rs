@@ -0,0 +1,230 @@
+#![allow(dead_code)]
+
+use std::fmt;
+
+use super::codes;
+use crate::{
+    ast::parse_error::{ParseError, ParseErrorKind},
+    bytecode::compile_error::CompileError,
+    codegen::codegen_error::CodegenError,
+    interpreter::runtime_error::RuntimeError,
+    lexer::lex_error::{LexError, LexErrorKind},
+    semantic::{
+        resolve_error::{ResolveError, ResolveErrorKind},
+        type_error::{TypeError, TypeErrorKind},
+    },
+    types::span::Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// A message about a source range, rendered by `Renderer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+    /// Text printed next to the underline.
+    pub label: Option<String>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: &str, span: Span) -> Self {
+        Self {
+            severity,
+            code,
+            message: message.to_owned(),
+            span,
+            label: None,
+            notes: vec![],
+            help: vec![],
+        }
+    }
+
+    pub fn error(code: &'static str, message: &str, span: Span) -> Self {
+        Self::new(Severity::Error, code, message, span)
+    }
+
+    pub fn warning(code: &'static str, message: &str, span: Span) -> Self {
+        Self::new(Severity::Warning, code, message, span)
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_owned());
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_owned());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help.push(help.to_owned());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl From<&LexError> for Diagnostic {
+    fn from(error: &LexError) -> Self {
+        let message = error.message();
+
+        match &error.kind {
+            LexErrorKind::UnexpectedCharacter(_) => {
+                Self::error(codes::UNEXPECTED_CHARACTER, &message, error.span)
+            }
+            LexErrorKind::UnterminatedString => {
+                Self::error(codes::UNTERMINATED_STRING, &message, error.span)
+                    .with_label("string starts here")
+                    .with_help("close the string with `\"` on the same line, or use `\"\"\"` for multi-line strings")
+            }
+            LexErrorKind::UnterminatedComment => {
+                Self::error(codes::UNTERMINATED_COMMENT, &message, error.span)
+                    .with_label("comment starts here")
+                    .with_help("close the comment with `*/`")
+            }
+            LexErrorKind::MalformedNumber(_) => {
+                Self::error(codes::MALFORMED_NUMBER, &message, error.span)
+                    .with_note("numbers cannot be directly followed by letters")
+            }
+            LexErrorKind::NumberOutOfRange(_, "int") => {
+                Self::error(codes::NUMBER_OUT_OF_RANGE, &message, error.span)
+                    .with_note("integers are 64-bit signed values at runtime")
+            }
+            LexErrorKind::NumberOutOfRange(_, _) => {
+                Self::error(codes::NUMBER_OUT_OF_RANGE, &message, error.span)
+                    .with_help("use a wider type suffix such as `i64`, `u64` or `f64`")
+            }
+            LexErrorKind::InvalidEscape(_) => {
+                Self::error(codes::INVALID_ESCAPE, &message, error.span)
+                    .with_help("valid escapes are \\n, \\t, \\r, \\0, \\\\, \\\", \\' and \\u{...}")
+            }
+            LexErrorKind::UnterminatedCharacter => {
+                Self::error(codes::UNTERMINATED_CHARACTER, &message, error.span)
+                    .with_label("character literal starts here")
+                    .with_help("close the character literal with `'`")
+            }
+            LexErrorKind::InvalidCharacterLiteral(_) => {
+                Self::error(codes::INVALID_CHARACTER_LITERAL, &message, error.span)
+                    .with_help("use double quotes for strings")
+            }
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        let message = format!("expected {}, found {}", error.expected, error.found);
+
+        match &error.kind {
+            ParseErrorKind::Unexpected => Self::error(codes::SYNTAX_ERROR, &message, error.span)
+                .with_label(&format!("expected {}", error.expected)),
+            ParseErrorKind::InvalidAssignmentTarget => Self::error(
+                codes::INVALID_ASSIGNMENT,
+                "invalid assignment target",
+                error.span,
+            )
+            .with_label("cannot assign to this expression")
+            .with_note(&message),
+            ParseErrorKind::LoopControlOutsideLoop => Self::error(
+                codes::LOOP_CONTROL_OUTSIDE_LOOP,
+                &format!("{} outside of a loop", error.found),
+                error.span,
+            )
+            .with_label(&format!("{} is only allowed inside a loop", error.found)),
+        }
+    }
+}
+
+impl From<&ResolveError> for Diagnostic {
+    fn from(error: &ResolveError) -> Self {
+        let message = error.message();
+
+        match &error.kind {
+            ResolveErrorKind::UndeclaredVariable(_) => {
+                Self::error(codes::UNDECLARED_VARIABLE, &message, error.span)
+                    .with_label("not found in this scope")
+            }
+            ResolveErrorKind::DuplicateDeclaration(_) => {
+                Self::error(codes::DUPLICATE_DECLARATION, &message, error.span)
+                    .with_label("declared again here")
+            }
+            ResolveErrorKind::UseBeforeInitialization(_) => {
+                Self::error(codes::USE_BEFORE_INITIALIZATION, &message, error.span)
+                    .with_label("read before the declaration is complete")
+            }
+            ResolveErrorKind::ShadowedVariable(_) => {
+                Self::warning(codes::SHADOWED_VARIABLE, &message, error.span)
+                    .with_help("rename the variable if the outer one is still needed")
+            }
+        }
+    }
+}
+
+impl From<&TypeError> for Diagnostic {
+    fn from(error: &TypeError) -> Self {
+        let message = error.message();
+
+        match &error.kind {
+            TypeErrorKind::Mismatch { expected, found } => {
+                Self::error(codes::MISMATCHED_TYPES, "mismatched types", error.span)
+                    .with_label(&format!("expected {}, found {}", expected, found))
+            }
+            TypeErrorKind::InvalidOperands(_, _, _) | TypeErrorKind::InvalidOperand(_, _) => {
+                Self::error(codes::INVALID_OPERANDS, &message, error.span)
+            }
+            TypeErrorKind::NotCallable(_) => Self::error(codes::NOT_CALLABLE, &message, error.span)
+                .with_note("only functions can be called"),
+            TypeErrorKind::ArgumentCount { .. } => {
+                Self::error(codes::ARGUMENT_COUNT, &message, error.span)
+            }
+            TypeErrorKind::ReturnValueInVoid => {
+                Self::error(codes::INVALID_RETURN, &message, error.span)
+                    .with_help("remove the value, or declare the function's return type")
+            }
+            TypeErrorKind::MissingReturnValue(_) => {
+                Self::error(codes::INVALID_RETURN, &message, error.span)
+            }
+        }
+    }
+}
+
+impl From<&CompileError> for Diagnostic {
+    fn from(error: &CompileError) -> Self {
+        Self::error(codes::COMPILE_ERROR, &error.message, error.span)
+    }
+}
+
+impl From<&CodegenError> for Diagnostic {
+    fn from(error: &CodegenError) -> Self {
+        Self::error(codes::UNSUPPORTED_BY_BACKEND, &error.message, error.span)
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(error: &RuntimeError) -> Self {
+        Self::error(codes::RUNTIME_ERROR, &error.message, error.span)
+    }
+}
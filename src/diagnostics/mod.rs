@@ -0,0 +1,5 @@
+pub mod codes;
+pub mod diagnostic;
+pub mod renderer;
+#[cfg(test)]
+mod renderer_test;
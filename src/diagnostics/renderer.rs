@@ -0,0 +1,187 @@
+#![allow(dead_code)]
+
+use std::io::IsTerminal;
+
+use super::diagnostic::{Diagnostic, Severity};
+use crate::{
+    source::{source_file::SourceFile, source_map::SourceMap},
+    types::span,
+};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[1;32m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// Formats diagnostics like rustc, with the source line and an underline.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Renderer {
+    colored: bool,
+}
+
+impl Renderer {
+    pub fn plain() -> Self {
+        Self { colored: false }
+    }
+
+    pub fn colored() -> Self {
+        Self { colored: true }
+    }
+
+    /// Colored output when stderr is a terminal and `NO_COLOR` is not set.
+    pub fn for_stderr() -> Self {
+        Self::for_terminal(std::io::stderr().is_terminal())
+    }
+
+    pub fn for_stdout() -> Self {
+        Self::for_terminal(std::io::stdout().is_terminal())
+    }
+
+    fn for_terminal(is_terminal: bool) -> Self {
+        Self {
+            colored: is_terminal && std::env::var_os("NO_COLOR").is_none(),
+        }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic, source: &str) -> String {
+        self.render_at(diagnostic, source, &diagnostic.span.to_string())
+    }
+
+    /// Renders with the location prefixed by the file name, `file.lox:3:5`.
+    pub fn render_file(&self, diagnostic: &Diagnostic, file: &SourceFile) -> String {
+        self.render_at(diagnostic, &file.text, &file.location(diagnostic.span))
+    }
+
+    fn render_at(&self, diagnostic: &Diagnostic, source: &str, location: &str) -> String {
+        let span = diagnostic.span;
+        let line_number = span.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let severity_color = self.severity_color(diagnostic.severity);
+
+        let mut output = format!(
+            "{}{}[{}]{}{}: {}{}\n",
+            self.paint(severity_color),
+            diagnostic.severity,
+            diagnostic.code,
+            self.paint(RESET),
+            self.paint(BOLD),
+            diagnostic.message,
+            self.paint(RESET),
+        );
+        output += &format!(
+            "{}{}-->{} {}\n",
+            gutter,
+            self.paint(BLUE),
+            self.paint(RESET),
+            location
+        );
+
+        if let Some(text) = Self::source_line(source, span.start) {
+            let (offset, width) = Self::underline(text, source, span.start, span.end);
+
+            output += &format!("{} {}|{}\n", gutter, self.paint(BLUE), self.paint(RESET));
+            output += &format!(
+                "{}{} |{} {}\n",
+                self.paint(BLUE),
+                line_number,
+                self.paint(RESET),
+                text
+            );
+            output += &format!(
+                "{} {}|{} {}{}{}",
+                gutter,
+                self.paint(BLUE),
+                self.paint(RESET),
+                " ".repeat(offset),
+                self.paint(severity_color),
+                "^".repeat(width),
+            );
+            if let Some(label) = &diagnostic.label {
+                output += &format!(" {}", label);
+            }
+            output += &format!("{}\n", self.paint(RESET));
+        }
+
+        for note in diagnostic.notes.iter() {
+            output += &format!(
+                "{} {}={} note: {}\n",
+                gutter,
+                self.paint(BLUE),
+                self.paint(RESET),
+                note
+            );
+        }
+        for help in diagnostic.help.iter() {
+            output += &format!(
+                "{} {}={} {}help{}: {}\n",
+                gutter,
+                self.paint(BLUE),
+                self.paint(RESET),
+                self.paint(GREEN),
+                self.paint(RESET),
+                help
+            );
+        }
+
+        output
+    }
+
+    pub fn render_all(&self, diagnostics: &[Diagnostic], source: &str) -> String {
+        diagnostics
+            .iter()
+            .map(|d| self.render(d, source))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Renders each diagnostic against the file its span refers to.
+    pub fn render_all_in(&self, diagnostics: &[Diagnostic], sources: &SourceMap) -> String {
+        diagnostics
+            .iter()
+            .map(|d| self.render_file(d, sources.get(d.span.file)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Full text of the line containing byte `offset`, `None` when the
+    /// source text is not available.
+    fn source_line(source: &str, offset: usize) -> Option<&str> {
+        if source.is_empty() || offset > source.len() {
+            return None;
+        }
+
+        let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let end = source[offset..]
+            .find('\n')
+            .map_or(source.len(), |i| offset + i);
+        Some(source[start..end].trim_end_matches('\r'))
+    }
+
+    /// Column offset and width of the underline within `line`.
+    fn underline(line: &str, source: &str, start: usize, end: usize) -> (usize, usize) {
+        let line_start = line.as_ptr() as usize - source.as_ptr() as usize;
+        let line_end = line_start + line.len();
+
+        let offset = span::columns(&source[line_start..start]);
+        let width = span::columns(&source[start..end.clamp(start, line_end)]);
+        (offset, width.max(1))
+    }
+
+    fn severity_color(&self, severity: Severity) -> &'static str {
+        match severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note => BLUE,
+        }
+    }
+
+    fn paint(&self, color: &'static str) -> &'static str {
+        if self.colored {
+            color
+        } else {
+            ""
+        }
+    }
+}
@@ -0,0 +1,109 @@
+#![allow(unused_imports, dead_code)]
+
+use super::{
+    codes,
+    diagnostic::{Diagnostic, Severity},
+    renderer::Renderer,
+};
+use crate::{ast::tree::AbstractSyntaxTree, lexer::scanner::Scanner, types::span::Span};
+
+#[test]
+fn plain_error_with_notes() {
+    let source = "int x = 10;\nint y = x +* 2;\n";
+    let diagnostic = Diagnostic::error(
+        codes::SYNTAX_ERROR,
+        "expected an expression, found '*'",
+        Span::new(23, 24, 2, 12),
+    )
+    .with_label("expected an expression")
+    .with_note("operators need two operands")
+    .with_help("remove the '*'");
+
+    let output = Renderer::plain().render(&diagnostic, source);
+
+    assert_eq!(
+        output,
+        "error[E0100]: expected an expression, found '*'
+ --> 2:12
+  |
+2 | int y = x +* 2;
+  |            ^ expected an expression
+  = note: operators need two operands
+  = help: remove the '*'
+"
+    );
+}
+
+#[test]
+fn underline_covers_span() {
+    let source = "print foo + bar;";
+    let diagnostic = Diagnostic::warning("W0001", "suspicious", Span::new(6, 15, 1, 7));
+
+    let output = Renderer::plain().render(&diagnostic, source);
+
+    assert!(output.starts_with("warning[W0001]: suspicious\n"));
+    assert!(output.ends_with("1 | print foo + bar;\n  |       ^^^^^^^^^\n"));
+}
+
+#[test]
+fn wide_gutter_and_unicode() {
+    let mut source = "\n".repeat(11);
+    source += "string s = \"žluťoučký\" + 1;";
+    let start = source.find('+').unwrap();
+    let diagnostic =
+        Diagnostic::error("E0300", "bad operands", Span::new(start, start + 1, 12, 24));
+
+    let output = Renderer::plain().render(&diagnostic, &source);
+
+    assert!(output.contains("  --> 12:24\n   |\n12 | string s"));
+    assert!(output.ends_with("   |                        ^\n"));
+}
+
+#[test]
+fn combining_characters() {
+    let source = "string cafe\u{301} = 1 @ 2;";
+    let errors = Scanner::new(source).get_tokens().unwrap_err();
+
+    let output = Renderer::plain().render(&Diagnostic::from(&errors[0]), source);
+
+    assert!(output.contains(" --> 1:17\n"));
+    assert!(output.ends_with("1 | string cafe\u{301} = 1 @ 2;\n  |                 ^\n"));
+}
+
+#[test]
+fn end_of_input_caret() {
+    let source = "print (1 +";
+    let tokens = Scanner::new(source).get_tokens().unwrap();
+    let errors = AbstractSyntaxTree::new()
+        .accept_tokens(&tokens)
+        .unwrap_err();
+
+    let output = Renderer::plain().render(&Diagnostic::from(&errors[0]), source);
+
+    assert!(output.ends_with("1 | print (1 +\n  |           ^ expected an expression\n"));
+}
+
+#[test]
+fn parse_error_kinds() {
+    let source = "1 = 2;\nbreak;";
+    let tokens = Scanner::new(source).get_tokens().unwrap();
+    let errors = AbstractSyntaxTree::new()
+        .accept_tokens(&tokens)
+        .unwrap_err();
+
+    let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+    assert_eq!(diagnostics[0].code, codes::INVALID_ASSIGNMENT);
+    assert_eq!(diagnostics[0].message, "invalid assignment target");
+    assert_eq!(diagnostics[1].code, codes::LOOP_CONTROL_OUTSIDE_LOOP);
+    assert_eq!(diagnostics[1].message, "'break' outside of a loop");
+}
+
+#[test]
+fn colored_output() {
+    let diagnostic = Diagnostic::error("E0001", "oops", Span::new(0, 1, 1, 1));
+
+    let output = Renderer::colored().render(&diagnostic, "@");
+
+    assert!(output.starts_with("\x1b[1;31merror[E0001]\x1b[0m\x1b[1m: oops\x1b[0m\n"));
+    assert_eq!(diagnostic.severity, Severity::Error);
+}
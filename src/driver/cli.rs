@@ -0,0 +1,496 @@
+#![allow(dead_code)]
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use super::repl;
+use crate::{
+    ast::{tree::AbstractSyntaxTree, tree_types::Statement},
+    bytecode::{
+        chunk::CompiledFunction,
+        compiler::Compiler,
+        disassembler::Disassembler,
+        loxc::{self, BytecodeFile},
+        vm::Vm,
+    },
+    codegen::{c99::CTranspiler, toolchain, x86_64::CodeGenerator},
+    diagnostics::{diagnostic::Diagnostic, renderer::Renderer},
+    interpreter::evaluator::Interpreter,
+    lexer::scanner::Scanner,
+    semantic::{resolution::Resolution, resolver::Resolver, type_checker::TypeChecker},
+    source::{source_file::FileId, source_map::SourceMap},
+};
+
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_USAGE: i32 = 64;
+pub const EXIT_LEXICAL_ERROR: i32 = 65;
+pub const EXIT_PARSE_ERROR: i32 = 66;
+pub const EXIT_TYPE_ERROR: i32 = 67;
+pub const EXIT_RESOLVE_ERROR: i32 = 68;
+pub const EXIT_COMPILE_ERROR: i32 = 69;
+pub const EXIT_RUNTIME_ERROR: i32 = 70;
+pub const EXIT_LOAD_ERROR: i32 = 71;
+pub const EXIT_LINK_ERROR: i32 = 72;
+pub const EXIT_IO_ERROR: i32 = 74;
+
+const USAGE: &str = "Usage: compiler <command> <file>
+
+Commands:
+    tokens <file>    Print the tokens of a source file
+    ast <file>       Print the syntax tree of a source file
+    check <file>     Report lexical, syntax and type errors without running
+    run <file>       Compile a source file to bytecode and execute it, or
+                     execute a compiled .loxc file
+    interpret <file> Execute a source file with the tree-walking interpreter
+    build <file>     Compile a source file to a .loxc file next to it
+    disasm <file>    Print the bytecode of a source or .loxc file
+    asm <file>       Print the x86-64 assembly of a source file
+    native <file>    Compile a source file to an executable next to it,
+                     using the system C compiler to assemble and link
+    c <file>         Translate a source file to a C99 .c file next to it,
+                     along with the runtime and header to compile it with
+    repl             Start an interactive session
+
+Use `-` as the file to read the source from standard input.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Tokens(String),
+    Ast(String),
+    Check(String),
+    Run(String),
+    Interpret(String),
+    Build(String),
+    Disasm(String),
+    Asm(String),
+    Native(String),
+    C(String),
+    Repl,
+}
+
+impl Command {
+    /// Parses the arguments following the program name.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let (name, path) = match args {
+            [name] if name == "repl" => return Ok(Command::Repl),
+            [name, path] => (name.as_str(), path.clone()),
+            [name] => return Err(format!("Missing file for command '{}'.", name)),
+            [] => return Err("Missing command.".to_owned()),
+            _ => return Err("Too many arguments.".to_owned()),
+        };
+
+        match name {
+            "tokens" => Ok(Command::Tokens(path)),
+            "ast" => Ok(Command::Ast(path)),
+            "check" => Ok(Command::Check(path)),
+            "run" => Ok(Command::Run(path)),
+            "interpret" => Ok(Command::Interpret(path)),
+            "build" => Ok(Command::Build(path)),
+            "disasm" => Ok(Command::Disasm(path)),
+            "asm" => Ok(Command::Asm(path)),
+            "native" => Ok(Command::Native(path)),
+            "c" => Ok(Command::C(path)),
+            _ => Err(format!("Unknown command '{}'.", name)),
+        }
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Command::Tokens(path)
+            | Command::Ast(path)
+            | Command::Check(path)
+            | Command::Run(path)
+            | Command::Interpret(path)
+            | Command::Build(path)
+            | Command::Disasm(path)
+            | Command::Asm(path)
+            | Command::Native(path)
+            | Command::C(path) => Some(path),
+            Command::Repl => None,
+        }
+    }
+}
+
+/// Runs the command line and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    let command = match Command::parse(args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    let path = match command.path() {
+        Some(path) => path,
+        None => {
+            repl::run(std::io::stdin().lock(), std::io::stdout());
+            return EXIT_SUCCESS;
+        }
+    };
+
+    if Path::new(path)
+        .extension()
+        .is_some_and(|e| e == loxc::EXTENSION)
+    {
+        return match std::fs::read(path) {
+            Ok(bytes) => execute_bytecode(
+                &command,
+                path,
+                &bytes,
+                Renderer::for_stderr(),
+                &mut std::io::stdout(),
+                &mut std::io::stderr(),
+            ),
+            Err(error) => {
+                eprintln!("error: cannot read '{}': {}", path, error);
+                EXIT_IO_ERROR
+            }
+        };
+    }
+
+    let mut sources = SourceMap::new();
+    let file = if path == "-" {
+        sources.load_stdin()
+    } else {
+        sources.load(path)
+    };
+    let file = match file {
+        Ok(file) => file,
+        Err(error) => {
+            eprintln!("error: cannot read '{}': {}", path, error);
+            return EXIT_IO_ERROR;
+        }
+    };
+
+    execute_file(
+        &command,
+        &sources,
+        file,
+        Renderer::for_stderr(),
+        &mut std::io::stdout(),
+        &mut std::io::stderr(),
+    )
+}
+
+/// Runs the command on source text that has no file name.
+pub fn execute(
+    command: &Command,
+    source: &str,
+    renderer: Renderer,
+    out: &mut impl Write,
+    err: &mut impl Write,
+) -> i32 {
+    let mut sources = SourceMap::new();
+    let file = sources.add_anonymous(source.to_owned());
+    execute_file(command, &sources, file, renderer, out, err)
+}
+
+/// Runs the command on a file of `sources`, diagnostics name the file.
+pub fn execute_file(
+    command: &Command,
+    sources: &SourceMap,
+    file: FileId,
+    renderer: Renderer,
+    out: &mut impl Write,
+    err: &mut impl Write,
+) -> i32 {
+    let scanner = Scanner::for_file(sources.get(file));
+
+    if let Command::Tokens(_) = command {
+        return match scanner.get_tokens() {
+            Ok(tokens) => {
+                for token in tokens.iter() {
+                    let _ = writeln!(out, "{}", token);
+                }
+                EXIT_SUCCESS
+            }
+            Err(errors) => {
+                let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+                report(&diagnostics, sources, renderer, err);
+                EXIT_LEXICAL_ERROR
+            }
+        };
+    }
+
+    // The parser pulls tokens straight from the scanner, lexical errors are
+    // set aside and reported first since they usually cause the parse errors.
+    let mut lex_errors = vec![];
+    let tokens = scanner
+        .tokens()
+        .filter_map(|item| item.map_err(|error| lex_errors.push(error)).ok());
+
+    let mut ast = AbstractSyntaxTree::new();
+    let parsed = ast.accept(tokens);
+
+    if !lex_errors.is_empty() {
+        let diagnostics = lex_errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+        report(&diagnostics, sources, renderer, err);
+        return EXIT_LEXICAL_ERROR;
+    }
+
+    if let Err(errors) = parsed {
+        let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+        report(&diagnostics, sources, renderer, err);
+        return EXIT_PARSE_ERROR;
+    }
+
+    if let Command::Ast(_) = command {
+        let json = serde_json::to_string_pretty(ast.get()).unwrap_or_default();
+        let _ = writeln!(out, "{}", json);
+        return EXIT_SUCCESS;
+    }
+
+    // Warnings are shown, errors stop before type checking.
+    let (mut resolution, errors) = Resolver::new().resolve(ast.get());
+    if !errors.is_empty() {
+        let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+        report(&diagnostics, sources, renderer, err);
+        if diagnostics.iter().any(Diagnostic::is_error) {
+            return EXIT_RESOLVE_ERROR;
+        }
+    }
+
+    match TypeChecker::new().check(ast.get()) {
+        Ok(widened) => resolution.widen(widened),
+        Err(errors) => {
+            let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+            report(&diagnostics, sources, renderer, err);
+            return EXIT_TYPE_ERROR;
+        }
+    }
+
+    match command {
+        Command::Run(_) => {
+            let Some(function) = compile(&resolution, ast.get(), sources, renderer, err) else {
+                return EXIT_COMPILE_ERROR;
+            };
+            run_bytecode(function, sources, renderer, out, err)
+        }
+        Command::Interpret(_) => {
+            let mut interpreter = Interpreter::new(&mut *out);
+            interpreter.resolve(resolution);
+            match interpreter.interpret(ast.get()) {
+                Ok(()) => EXIT_SUCCESS,
+                Err(error) => {
+                    report(&[Diagnostic::from(&error)], sources, renderer, err);
+                    EXIT_RUNTIME_ERROR
+                }
+            }
+        }
+        Command::Build(path) => {
+            let Some(function) = compile(&resolution, ast.get(), sources, renderer, err) else {
+                return EXIT_COMPILE_ERROR;
+            };
+            let name = sources.get(file).name.clone();
+            let bytes = BytecodeFile::new(name, function).encode();
+
+            // Compiling standard input writes the program to standard output.
+            let (target, written) = if path == "-" {
+                ("<stdout>".into(), out.write_all(&bytes))
+            } else {
+                let target = Path::new(path).with_extension(loxc::EXTENSION);
+                let written = std::fs::write(&target, &bytes);
+                (target.display().to_string(), written)
+            };
+            match written {
+                Ok(()) => EXIT_SUCCESS,
+                Err(error) => {
+                    let _ = writeln!(err, "error: cannot write '{}': {}", target, error);
+                    EXIT_IO_ERROR
+                }
+            }
+        }
+        Command::Disasm(_) => {
+            let Some(function) = compile(&resolution, ast.get(), sources, renderer, err) else {
+                return EXIT_COMPILE_ERROR;
+            };
+            let disassembler = Disassembler::with_source(&sources.get(file).text);
+            let _ = write!(out, "{}", disassembler.disassemble(&function));
+            EXIT_SUCCESS
+        }
+        Command::Asm(_) => {
+            let Some(assembly) = assemble(ast.get(), sources, file, renderer, err) else {
+                return EXIT_COMPILE_ERROR;
+            };
+            let _ = write!(out, "{}", assembly);
+            EXIT_SUCCESS
+        }
+        Command::Native(path) => {
+            let Some(assembly) = assemble(ast.get(), sources, file, renderer, err) else {
+                return EXIT_COMPILE_ERROR;
+            };
+            let target = executable_path(path);
+            match toolchain::link(&assembly, &target) {
+                Ok(()) => EXIT_SUCCESS,
+                Err(error) => {
+                    let _ = writeln!(err, "error: cannot link '{}': {}", target.display(), error);
+                    EXIT_LINK_ERROR
+                }
+            }
+        }
+        Command::C(path) => {
+            let Some(source) = transpile(ast.get(), sources, file, renderer, err) else {
+                return EXIT_COMPILE_ERROR;
+            };
+
+            // Translating standard input writes the C to standard output.
+            let (target, written) = if path == "-" {
+                ("<stdout>".into(), out.write_all(source.as_bytes()))
+            } else {
+                let target = Path::new(path).with_extension("c");
+                let header = target.with_file_name(toolchain::RUNTIME_HEADER);
+                let runtime = target.with_file_name(toolchain::RUNTIME_SOURCE);
+                let written = std::fs::write(&target, &source)
+                    .and_then(|()| std::fs::write(header, toolchain::HEADER))
+                    .and_then(|()| std::fs::write(runtime, toolchain::RUNTIME));
+                (target.display().to_string(), written)
+            };
+            match written {
+                Ok(()) => EXIT_SUCCESS,
+                Err(error) => {
+                    let _ = writeln!(err, "error: cannot write '{}': {}", target, error);
+                    EXIT_IO_ERROR
+                }
+            }
+        }
+        _ => EXIT_SUCCESS,
+    }
+}
+
+/// Where `native` writes the executable: the source path without its
+/// extension, or `a.out` for standard input like C compilers.
+pub fn executable_path(path: &str) -> PathBuf {
+    let source = Path::new(path);
+    match source.extension() {
+        _ if path == "-" => PathBuf::from("a.out"),
+        Some(_) => source.with_extension(""),
+        None => source.with_extension("out"),
+    }
+}
+
+/// Runs the command on a compiled `.loxc` program named `name`.
+pub fn execute_bytecode(
+    command: &Command,
+    name: &str,
+    bytes: &[u8],
+    renderer: Renderer,
+    out: &mut impl Write,
+    err: &mut impl Write,
+) -> i32 {
+    let program = match BytecodeFile::decode(bytes) {
+        Ok(program) => program,
+        Err(error) => {
+            let _ = writeln!(err, "error: cannot load '{}': {}", name, error);
+            return EXIT_LOAD_ERROR;
+        }
+    };
+
+    match command {
+        Command::Run(_) => {
+            // Diagnostics point at the original source, without its text.
+            let mut sources = SourceMap::new();
+            sources.add(
+                program.source_name.as_deref().unwrap_or(name),
+                String::new(),
+            );
+            run_bytecode(program.script, &sources, renderer, out, err)
+        }
+        Command::Disasm(_) => {
+            let _ = write!(out, "{}", Disassembler::new().disassemble(&program.script));
+            EXIT_SUCCESS
+        }
+        _ => {
+            let _ = writeln!(
+                err,
+                "error: '{}' is a compiled program, only `run` and `disasm` accept it",
+                name
+            );
+            EXIT_USAGE
+        }
+    }
+}
+
+fn compile(
+    resolution: &Resolution,
+    statements: &[Statement],
+    sources: &SourceMap,
+    renderer: Renderer,
+    err: &mut impl Write,
+) -> Option<CompiledFunction> {
+    match Compiler::new(resolution).compile(statements) {
+        Ok(function) => Some(function),
+        Err(error) => {
+            report(&[Diagnostic::from(&error)], sources, renderer, err);
+            None
+        }
+    }
+}
+
+fn assemble(
+    statements: &[Statement],
+    sources: &SourceMap,
+    file: FileId,
+    renderer: Renderer,
+    err: &mut impl Write,
+) -> Option<String> {
+    let generator = match &sources.get(file).name {
+        Some(name) => CodeGenerator::with_source_name(name),
+        None => CodeGenerator::new(),
+    };
+    match generator.generate(statements) {
+        Ok(assembly) => Some(assembly),
+        Err(error) => {
+            report(&[Diagnostic::from(&error)], sources, renderer, err);
+            None
+        }
+    }
+}
+
+fn transpile(
+    statements: &[Statement],
+    sources: &SourceMap,
+    file: FileId,
+    renderer: Renderer,
+    err: &mut impl Write,
+) -> Option<String> {
+    let transpiler = match &sources.get(file).name {
+        Some(name) => CTranspiler::with_source_name(name),
+        None => CTranspiler::new(),
+    };
+    match transpiler.transpile(statements) {
+        Ok(source) => Some(source),
+        Err(error) => {
+            report(&[Diagnostic::from(&error)], sources, renderer, err);
+            None
+        }
+    }
+}
+
+fn run_bytecode(
+    function: CompiledFunction,
+    sources: &SourceMap,
+    renderer: Renderer,
+    out: &mut impl Write,
+    err: &mut impl Write,
+) -> i32 {
+    let mut vm = Vm::new(&mut *out);
+    match vm.run(function) {
+        Ok(()) => EXIT_SUCCESS,
+        Err(error) => {
+            report(&[Diagnostic::from(&error)], sources, renderer, err);
+            EXIT_RUNTIME_ERROR
+        }
+    }
+}
+
+fn report(
+    diagnostics: &[Diagnostic],
+    sources: &SourceMap,
+    renderer: Renderer,
+    err: &mut impl Write,
+) {
+    let _ = write!(err, "{}", renderer.render_all_in(diagnostics, sources));
+}
@@ -0,0 +1,317 @@
+#![allow(unused_imports, dead_code)]
+
+use std::path::Path;
+
+use super::cli::*;
+use crate::{diagnostics::renderer::Renderer, source::source_map::SourceMap};
+
+fn execute_source(command: Command, source: &str) -> (i32, String, String) {
+    let mut out = Vec::new();
+    let mut err = Vec::new();
+
+    let code = execute(&command, source, Renderer::plain(), &mut out, &mut err);
+
+    (
+        code,
+        String::from_utf8(out).unwrap(),
+        String::from_utf8(err).unwrap(),
+    )
+}
+
+fn args(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn parse_commands() {
+    assert_eq!(
+        Command::parse(&args(&["run", "file.lox"])),
+        Ok(Command::Run("file.lox".to_owned()))
+    );
+    assert_eq!(
+        Command::parse(&args(&["tokens", "a.lox"])),
+        Ok(Command::Tokens("a.lox".to_owned()))
+    );
+    assert_eq!(
+        Command::parse(&args(&["interpret", "a.lox"])),
+        Ok(Command::Interpret("a.lox".to_owned()))
+    );
+    assert_eq!(
+        Command::parse(&args(&["disasm", "a.loxc"])),
+        Ok(Command::Disasm("a.loxc".to_owned()))
+    );
+    assert_eq!(
+        Command::parse(&args(&["native", "a.lox"])),
+        Ok(Command::Native("a.lox".to_owned()))
+    );
+    assert_eq!(
+        Command::parse(&args(&["c", "a.lox"])),
+        Ok(Command::C("a.lox".to_owned()))
+    );
+    assert_eq!(Command::parse(&args(&["repl"])), Ok(Command::Repl));
+    assert!(Command::parse(&args(&["run"])).is_err());
+    assert!(Command::parse(&args(&["compile", "a.lox"])).is_err());
+    assert!(Command::parse(&args(&[])).is_err());
+}
+
+#[test]
+fn usage_and_io_errors() {
+    assert_eq!(run(&args(&["frobnicate"])), EXIT_USAGE);
+    assert_eq!(run(&args(&["run", "missing/file.lox"])), EXIT_IO_ERROR);
+}
+
+#[test]
+fn run_program() {
+    let (code, out, _) = execute_source(Command::Run(String::new()), "int x = 2; print x * 21;");
+
+    assert_eq!(code, EXIT_SUCCESS);
+    assert_eq!(out, "42\n");
+
+    let (code, out, _) = execute_source(
+        Command::Interpret(String::new()),
+        "int x = 2; print x * 21;",
+    );
+
+    assert_eq!(code, EXIT_SUCCESS);
+    assert_eq!(out, "42\n");
+}
+
+#[test]
+fn compile_errors() {
+    let locals = (0..300).map(|i| format!("var v{};", i)).collect::<String>();
+    let (code, _, err) = execute_source(Command::Run(String::new()), &format!("{{ {} }}", locals));
+
+    assert_eq!(code, EXIT_COMPILE_ERROR);
+    assert!(err.starts_with("error[E0400]: Too many local variables in function."));
+}
+
+#[test]
+fn assembly_output() {
+    let (code, out, _) = execute_source(Command::Asm(String::new()), "print 1 + 2;");
+
+    assert_eq!(code, EXIT_SUCCESS);
+    assert!(out.contains("\nmain:\n"));
+    assert!(out.contains("    call lox_print_int@PLT\n"));
+
+    let (code, _, err) = execute_source(Command::Asm(String::new()), "var a;");
+
+    assert_eq!(code, EXIT_COMPILE_ERROR);
+    assert!(err.starts_with(
+        "error[E0401]: Variable 'a' needs a type or an initializer to compile to native code."
+    ));
+
+    assert_eq!(executable_path("dir/prog.lox"), Path::new("dir/prog"));
+    assert_eq!(executable_path("prog"), Path::new("prog.out"));
+    assert_eq!(executable_path("-"), Path::new("a.out"));
+}
+
+#[test]
+fn c_output() {
+    let (code, out, _) = execute_source(Command::C("-".to_owned()), "print 1 + 2;");
+
+    assert_eq!(code, EXIT_SUCCESS);
+    assert!(out.contains("#include \"lox_runtime.h\"\n"));
+    assert!(out.contains("    lox_print_int(lox_add(1, 2, 1, 9));\n"));
+
+    let (code, _, err) = execute_source(Command::C("-".to_owned()), "fun f(a) {}");
+
+    assert_eq!(code, EXIT_COMPILE_ERROR);
+    assert!(err.starts_with("error[E0401]: Parameter 'a' needs a type"));
+}
+
+#[test]
+fn exit_codes() {
+    let (code, _, err) = execute_source(Command::Check(String::new()), "int x = 1 @ 2;");
+    assert_eq!(code, EXIT_LEXICAL_ERROR);
+    assert!(err.starts_with("error[E0001]: unexpected character '@'"));
+
+    let (code, _, _) = execute_source(Command::Check(String::new()), "int x = ;");
+    assert_eq!(code, EXIT_PARSE_ERROR);
+
+    let (code, _, _) = execute_source(Command::Check(String::new()), "print y;");
+    assert_eq!(code, EXIT_RESOLVE_ERROR);
+
+    let (code, _, _) = execute_source(Command::Run(String::new()), "print 1 / 0;");
+    assert_eq!(code, EXIT_RUNTIME_ERROR);
+
+    let (code, out, _) = execute_source(Command::Check(String::new()), "print 1 / 0;");
+    assert_eq!(code, EXIT_SUCCESS);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn tokens_and_ast_output() {
+    let (code, out, _) = execute_source(Command::Tokens(String::new()), "print 1;");
+    assert_eq!(code, EXIT_SUCCESS);
+    assert_eq!(out.lines().count(), 3);
+
+    let (code, out, _) = execute_source(Command::Ast(String::new()), "print 1;");
+    assert_eq!(code, EXIT_SUCCESS);
+    assert!(out.contains("\"Print\""));
+}
+
+#[test]
+fn errors_render_source() {
+    let (code, _, err) = execute_source(
+        Command::Check(String::new()),
+        "int x = 1;\nint y = 2\nprint y;",
+    );
+
+    assert_eq!(code, EXIT_PARSE_ERROR);
+    assert_eq!(
+        err,
+        "error[E0100]: expected ';' after statement, found 'print'
+ --> 3:1
+  |
+3 | print y;
+  | ^^^^^ expected ';' after statement
+"
+    );
+}
+
+#[test]
+fn type_errors_stop_before_running() {
+    let (code, out, err) = execute_source(
+        Command::Run(String::new()),
+        "print \"start\";\nint x = \"ten\";",
+    );
+
+    assert_eq!(code, EXIT_TYPE_ERROR);
+    assert!(out.is_empty());
+    assert_eq!(
+        err,
+        "error[E0200]: mismatched types
+ --> 2:9
+  |
+2 | int x = \"ten\";
+  |         ^^^^^ expected int, found string
+"
+    );
+}
+
+#[test]
+fn diagnostics_name_the_file() {
+    let mut sources = SourceMap::new();
+    sources.add("file0.lox", "print 0;".to_owned());
+    let file = sources.add("file1.lox", "var a = 1;\n\nprint a +;".to_owned());
+    let mut out = Vec::new();
+    let mut err = Vec::new();
+
+    let code = execute_file(
+        &Command::Check("file1.lox".to_owned()),
+        &sources,
+        file,
+        Renderer::plain(),
+        &mut out,
+        &mut err,
+    );
+
+    assert_eq!(code, EXIT_PARSE_ERROR);
+    assert!(String::from_utf8(err)
+        .unwrap()
+        .contains(" --> file1.lox:3:10\n"));
+}
+
+#[test]
+fn warnings_do_not_stop_running() {
+    let (code, out, err) = execute_source(
+        Command::Run(String::new()),
+        "int x = 1;\n{ int x = 2; print x; }\nprint x;",
+    );
+
+    assert_eq!(code, EXIT_SUCCESS);
+    assert_eq!(out, "2\n1\n");
+    assert!(
+        err.starts_with("warning[W0001]: declaration of 'x' shadows an outer variable\n --> 2:7\n")
+    );
+}
+
+#[test]
+fn build_run_and_disassemble_bytecode() {
+    let mut bytes = vec![];
+    let mut err = vec![];
+    let code = execute(
+        &Command::Build("-".to_owned()),
+        "print 6 * 7;",
+        Renderer::plain(),
+        &mut bytes,
+        &mut err,
+    );
+    assert_eq!(code, EXIT_SUCCESS);
+
+    let mut out = vec![];
+    let code = execute_bytecode(
+        &Command::Run(String::new()),
+        "a.loxc",
+        &bytes,
+        Renderer::plain(),
+        &mut out,
+        &mut err,
+    );
+    assert_eq!(code, EXIT_SUCCESS);
+    assert_eq!(String::from_utf8(out).unwrap(), "42\n");
+
+    let mut out = vec![];
+    let code = execute_bytecode(
+        &Command::Disasm(String::new()),
+        "a.loxc",
+        &bytes,
+        Renderer::plain(),
+        &mut out,
+        &mut err,
+    );
+    assert_eq!(code, EXIT_SUCCESS);
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("1 Constant            0 6\n"));
+
+    let (code, out, _) = execute_source(Command::Disasm(String::new()), "print 6 * 7;");
+    assert_eq!(code, EXIT_SUCCESS);
+    assert!(out.starts_with("== script ==\n          ; print 6 * 7;\n"));
+}
+
+#[test]
+fn bytecode_errors() {
+    let mut err = vec![];
+    let code = execute_bytecode(
+        &Command::Run(String::new()),
+        "a.loxc",
+        b"print 1;",
+        Renderer::plain(),
+        &mut vec![],
+        &mut err,
+    );
+    assert_eq!(code, EXIT_LOAD_ERROR);
+    assert_eq!(
+        String::from_utf8(err).unwrap(),
+        "error: cannot load 'a.loxc': not a compiled program\n"
+    );
+
+    let mut sources = SourceMap::new();
+    let file = sources.add("div.lox", "print 1;\nprint 1 / 0;".to_owned());
+    let mut bytes = vec![];
+    let code = execute_file(
+        &Command::Build("-".to_owned()),
+        &sources,
+        file,
+        Renderer::plain(),
+        &mut bytes,
+        &mut vec![],
+    );
+    assert_eq!(code, EXIT_SUCCESS);
+
+    let mut err = vec![];
+    let code = execute_bytecode(
+        &Command::Run(String::new()),
+        "div.loxc",
+        &bytes,
+        Renderer::plain(),
+        &mut vec![],
+        &mut err,
+    );
+    assert_eq!(code, EXIT_RUNTIME_ERROR);
+    assert_eq!(
+        String::from_utf8(err).unwrap(),
+        "error[E0300]: Division by zero.\n --> div.lox:2:9\n"
+    );
+}
@@ -0,0 +1,6 @@
+pub mod cli;
+#[cfg(test)]
+mod cli_test;
+pub mod repl;
+#[cfg(test)]
+mod repl_test;
@@ -0,0 +1,165 @@
+#![allow(dead_code)]
+
+use std::io::{BufRead, Write};
+
+use crate::{
+    ast::{tree::AbstractSyntaxTree, tree_types::Statement},
+    diagnostics::{diagnostic::Diagnostic, renderer::Renderer},
+    interpreter::{evaluator::Interpreter, value::Value},
+    lexer::{scanner::Scanner, token::Token},
+    semantic::{resolver::Resolver, type_checker::TypeChecker},
+    source::source_map::SourceMap,
+    types::token_type::TokenType,
+};
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = "... ";
+
+/// Interactive session keeping definitions alive between inputs.
+pub struct Repl<W: Write> {
+    interpreter: Interpreter<W>,
+    resolver: Resolver,
+    checker: TypeChecker,
+    /// Every complete input, each its own file so spans stay unique.
+    sources: SourceMap,
+    renderer: Renderer,
+    buffer: String,
+}
+
+impl<W: Write> Repl<W> {
+    pub fn new(output: W) -> Self {
+        Self {
+            interpreter: Interpreter::new(output),
+            resolver: Resolver::new(),
+            checker: TypeChecker::new(),
+            sources: SourceMap::new(),
+            renderer: Renderer::plain(),
+            buffer: String::new(),
+        }
+    }
+
+    pub fn prompt(&self) -> &'static str {
+        if self.buffer.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        }
+    }
+
+    /// Feeds one line of input, returns `true` while a block is still open.
+    pub fn feed(&mut self, line: &str) -> bool {
+        self.buffer.push_str(line);
+        self.buffer.push('\n');
+
+        let source = std::mem::take(&mut self.buffer);
+        let tokens = match Scanner::new(&source).get_tokens() {
+            Ok(tokens) => tokens,
+            Err(errors) => {
+                let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+                self.report(&diagnostics, &source);
+                return false;
+            }
+        };
+        let depth = tokens.iter().fold(0, |depth, t| match t.token_type {
+            TokenType::LeftBrace => depth + 1,
+            TokenType::RightBrace => depth - 1,
+            _ => depth,
+        });
+        if depth > 0 {
+            self.buffer = source;
+            return true;
+        }
+
+        let file = self.sources.add_anonymous(source.clone());
+        let tokens = tokens.into_iter().map(|token| Token {
+            span: token.span.in_file(file),
+            ..token
+        });
+
+        let mut ast = AbstractSyntaxTree::new();
+        if let Err(errors) = ast.accept(tokens) {
+            let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+            self.report(&diagnostics, &source);
+            return false;
+        }
+
+        // Declarations of a rejected input must not be seen by later ones,
+        // so both passes work on copies kept only when the whole input passes.
+        let mut resolver = self.resolver.clone();
+        let (mut resolution, errors) = resolver.resolve(ast.get());
+        if !errors.is_empty() {
+            let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+            self.report(&diagnostics, &source);
+            if diagnostics.iter().any(Diagnostic::is_error) {
+                return false;
+            }
+        }
+
+        let mut checker = self.checker.clone();
+        match checker.check(ast.get()) {
+            Ok(widened) => resolution.widen(widened),
+            Err(errors) => {
+                let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+                self.report(&diagnostics, &source);
+                return false;
+            }
+        }
+        self.resolver = resolver;
+        self.checker = checker;
+        self.interpreter.resolve(resolution);
+
+        // A lone expression echoes its value, like `1 + 2` printing `3`.
+        if let [Statement::Expression(expression, _)] = ast.get() {
+            match self.interpreter.evaluate_expression(expression) {
+                Ok(Value::Nil) => {}
+                Ok(value) => self.write_line(&value.to_string()),
+                Err(error) => self.report(&[Diagnostic::from(&error)], &source),
+            }
+            return false;
+        }
+
+        if let Err(error) = self.interpreter.interpret(ast.get()) {
+            self.report(&[Diagnostic::from(&error)], &source);
+        }
+        false
+    }
+
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.renderer = renderer;
+    }
+
+    fn report(&mut self, diagnostics: &[Diagnostic], source: &str) {
+        let text = self.renderer.render_all(diagnostics, source);
+        let _ = write!(self.interpreter.output_mut(), "{}", text);
+    }
+
+    pub fn into_output(self) -> W {
+        self.interpreter.into_output()
+    }
+
+    fn write_line(&mut self, text: &str) {
+        let _ = writeln!(self.interpreter.output_mut(), "{}", text);
+    }
+
+    fn write_prompt(&mut self) {
+        let prompt = self.prompt();
+        let output = self.interpreter.output_mut();
+        let _ = write!(output, "{}", prompt);
+        let _ = output.flush();
+    }
+}
+
+/// Reads lines until end of input.
+pub fn run(input: impl BufRead, output: impl Write) {
+    let mut repl = Repl::new(output);
+    repl.set_renderer(Renderer::for_stdout());
+    repl.write_prompt();
+
+    for line in input.lines() {
+        let Ok(line) = line else { break };
+        repl.feed(&line);
+        repl.write_prompt();
+    }
+
+    repl.write_line("");
+}
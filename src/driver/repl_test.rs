@@ -0,0 +1,99 @@
+#![allow(unused_imports, dead_code)]
+
+use super::repl::*;
+
+fn session(lines: &[&str]) -> String {
+    let mut repl = Repl::new(Vec::new());
+    for line in lines {
+        repl.feed(line);
+    }
+    String::from_utf8(repl.into_output()).unwrap()
+}
+
+#[test]
+fn keeps_definitions() {
+    let output = session(&[
+        "int x = 40;",
+        "fun add(a) { return a + x; }",
+        "print add(2);",
+    ]);
+
+    assert_eq!(output, "42\n");
+}
+
+#[test]
+fn echoes_expressions() {
+    let output = session(&["1 + 2", "int x = 5;", "x * 2;", "nil"]);
+
+    assert_eq!(output, "3\n10\n");
+}
+
+#[test]
+fn multi_line_continuation() {
+    let mut repl = Repl::new(Vec::new());
+
+    assert!(repl.feed("fun greet() {"));
+    assert_eq!(repl.prompt(), "... ");
+    assert!(repl.feed("  if (true) {"));
+    assert!(repl.feed("    print \"hi\";"));
+    assert!(repl.feed("  }"));
+    assert!(!repl.feed("}"));
+    assert_eq!(repl.prompt(), "> ");
+    assert!(!repl.feed("greet();"));
+
+    assert_eq!(String::from_utf8(repl.into_output()).unwrap(), "hi\n");
+}
+
+#[test]
+fn errors_do_not_end_session() {
+    let output = session(&["print y;", "int = 1;", "int y = 1;", "print y;"]);
+
+    let errors = output
+        .lines()
+        .filter(|l| l.starts_with("error["))
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        errors,
+        vec![
+            "error[E0250]: undeclared variable 'y'",
+            "error[E0100]: expected an expression, found 'int'"
+        ]
+    );
+    assert!(output.contains("1 | print y;\n  |       ^ not found in this scope\n"));
+    assert!(output.ends_with("\n1\n"));
+}
+
+#[test]
+fn rejected_inputs_declare_nothing() {
+    let output = session(&["int y = 1;", "string y = 5;", "y = 2;", "print y;"]);
+
+    let errors = output
+        .lines()
+        .filter(|l| l.starts_with("error["))
+        .collect::<Vec<&str>>();
+    assert_eq!(errors, vec!["error[E0200]: mismatched types"]);
+    assert!(output.ends_with("\n2\n"));
+
+    let output = session(&["int z = \"s\";", "print z;", "z = 5;"]);
+
+    let errors = output
+        .lines()
+        .filter(|l| l.starts_with("error["))
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        errors,
+        vec![
+            "error[E0200]: mismatched types",
+            "error[E0250]: undeclared variable 'z'",
+            "error[E0250]: undeclared variable 'z'",
+        ]
+    );
+}
+
+#[test]
+fn run_with_prompts() {
+    let mut output = Vec::new();
+    run("print 1;\n{\nprint 2;\n}\n".as_bytes(), &mut output);
+
+    assert_eq!(String::from_utf8(output).unwrap(), "> 1\n> ... ... 2\n> \n");
+}
@@ -0,0 +1,73 @@
+#![allow(dead_code)]
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::value::Value;
+
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_owned(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.enclosing.as_ref()?.borrow().get(name),
+        }
+    }
+
+    /// Assigns to the nearest scope defining `name`, returns `false` if none does.
+    pub fn assign(&mut self, name: &str, value: Value) -> bool {
+        if let Some(slot) = self.values.get_mut(name) {
+            *slot = value;
+            return true;
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+
+    /// Value of `name` in the scope `depth` levels out, as resolved.
+    pub fn get_at(&self, depth: usize, name: &str) -> Option<Value> {
+        if depth == 0 {
+            return self.values.get(name).cloned();
+        }
+        self.enclosing.as_ref()?.borrow().get_at(depth - 1, name)
+    }
+
+    pub fn assign_at(&mut self, depth: usize, name: &str, value: Value) -> bool {
+        if depth == 0 {
+            return match self.values.get_mut(name) {
+                Some(slot) => {
+                    *slot = value;
+                    true
+                }
+                None => false,
+            };
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign_at(depth - 1, name, value),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,431 @@
+#![allow(dead_code)]
+
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+use super::{
+    environment::Environment,
+    operators,
+    runtime_error::RuntimeError,
+    value::{Function, Value},
+};
+use crate::{
+    ast::tree_types::*,
+    semantic::resolution::{Binding, Resolution},
+    types::span::Span,
+};
+
+type ValueResult = Result<Value, RuntimeError>;
+
+/// How a statement finished executing.
+#[derive(Debug, Clone, PartialEq)]
+enum Flow {
+    Next,
+    Return(Value),
+    Break,
+    Continue,
+}
+
+type ExecResult = Result<Flow, RuntimeError>;
+
+/// Calls that can be nested before a stack overflow, the same for every
+/// backend.
+pub const MAX_CALL_DEPTH: usize = 1024;
+
+pub struct Interpreter<W: Write> {
+    globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    /// Where each variable use refers to, uses without a binding are looked
+    /// up by name through the enclosing scopes.
+    resolution: Resolution,
+    output: W,
+    depth: usize,
+}
+
+impl<W: Write> Interpreter<W> {
+    pub fn new(output: W) -> Self {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+
+        Self {
+            environment: globals.clone(),
+            globals,
+            resolution: Resolution::new(),
+            output,
+            depth: 0,
+        }
+    }
+
+    /// Uses the resolver's bindings for the statements interpreted next.
+    pub fn resolve(&mut self, resolution: Resolution) {
+        self.resolution.extend(resolution);
+    }
+
+    /// Executes statements in the global scope, which persists between calls.
+    pub fn interpret(&mut self, statements: &[Statement]) -> Result<(), RuntimeError> {
+        for statement in statements {
+            if let Flow::Return(_) = self.execute(statement)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    fn execute(&mut self, statement: &Statement) -> ExecResult {
+        match statement {
+            Statement::Expression(expression, _) => {
+                self.evaluate(expression)?;
+                Ok(Flow::Next)
+            }
+            Statement::Variable {
+                data_type,
+                name,
+                initializer,
+                ..
+            } => self.define_variable(name, *data_type, initializer.as_ref()),
+            Statement::Block(statements, _) => {
+                let environment = Environment::with_enclosing(self.environment.clone());
+                self.execute_block(statements, Rc::new(RefCell::new(environment)))
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                if self.evaluate(condition)?.is_truthy() {
+                    self.execute(then_branch)
+                } else if let Some(else_branch) = else_branch {
+                    self.execute(else_branch)
+                } else {
+                    Ok(Flow::Next)
+                }
+            }
+            Statement::While {
+                condition, body, ..
+            } => self.execute_while(condition, body),
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                let environment = Environment::with_enclosing(self.environment.clone());
+                let previous =
+                    std::mem::replace(&mut self.environment, Rc::new(RefCell::new(environment)));
+                let result = self.execute_for(initializer, condition, increment, body);
+                self.environment = previous;
+                result
+            }
+            Statement::Return(value, _) => {
+                let value = match value {
+                    Some(expression) => self.evaluate(expression)?,
+                    None => Value::Nil,
+                };
+                Ok(Flow::Return(value))
+            }
+            Statement::Break(_) => Ok(Flow::Break),
+            Statement::Continue(_) => Ok(Flow::Continue),
+            Statement::Print(expression, _) => self.print(expression),
+            Statement::Function { .. } => self.define_function(statement),
+        }
+    }
+
+    fn define_variable(
+        &mut self,
+        name: &str,
+        data_type: Option<DataType>,
+        initializer: Option<&Expresion>,
+    ) -> ExecResult {
+        let value = match (initializer, data_type) {
+            (Some(expression), _) => self.evaluate(expression)?.coerce(data_type),
+            (None, Some(data_type)) => Value::default_for(data_type),
+            (None, None) => Value::Nil,
+        };
+        self.environment.borrow_mut().define(name, value);
+        Ok(Flow::Next)
+    }
+
+    fn define_function(&mut self, statement: &Statement) -> ExecResult {
+        if let Statement::Function {
+            name,
+            parameters,
+            return_type,
+            body,
+            ..
+        } = statement
+        {
+            let function = Function {
+                name: name.clone(),
+                parameters: parameters.clone(),
+                return_type: *return_type,
+                body: body.clone(),
+                closure: self.environment.clone(),
+            };
+            self.environment
+                .borrow_mut()
+                .define(name, Value::Function(Rc::new(function)));
+        }
+        Ok(Flow::Next)
+    }
+
+    fn print(&mut self, expression: &Expresion) -> ExecResult {
+        let value = self.evaluate(expression)?;
+        writeln!(self.output, "{}", value)
+            .map_err(|e| RuntimeError::new(&e.to_string(), Span::default()))?;
+        Ok(Flow::Next)
+    }
+
+    fn execute_while(&mut self, condition: &Expresion, body: &Statement) -> ExecResult {
+        while self.evaluate(condition)?.is_truthy() {
+            match self.execute(body)? {
+                Flow::Break => break,
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                Flow::Next | Flow::Continue => {}
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn execute_for(
+        &mut self,
+        initializer: &Option<Box<Statement>>,
+        condition: &Option<Expresion>,
+        increment: &Option<Expresion>,
+        body: &Statement,
+    ) -> ExecResult {
+        if let Some(initializer) = initializer {
+            self.execute(initializer)?;
+        }
+
+        loop {
+            if let Some(condition) = condition {
+                if !self.evaluate(condition)?.is_truthy() {
+                    return Ok(Flow::Next);
+                }
+            }
+
+            match self.execute(body)? {
+                Flow::Break => return Ok(Flow::Next),
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                Flow::Next | Flow::Continue => {}
+            }
+
+            if let Some(increment) = increment {
+                self.evaluate(increment)?;
+            }
+        }
+    }
+
+    fn execute_block(
+        &mut self,
+        statements: &[Statement],
+        environment: Rc<RefCell<Environment>>,
+    ) -> ExecResult {
+        let previous = std::mem::replace(&mut self.environment, environment);
+
+        let mut result = Ok(Flow::Next);
+        for statement in statements {
+            result = self.execute(statement);
+            if !matches!(result, Ok(Flow::Next)) {
+                break;
+            }
+        }
+
+        self.environment = previous;
+        result
+    }
+
+    /// Evaluates a single expression in the current scope.
+    pub fn evaluate_expression(&mut self, expression: &Expresion) -> ValueResult {
+        self.evaluate(expression)
+    }
+
+    fn evaluate(&mut self, expression: &Expresion) -> ValueResult {
+        match expression {
+            Expresion::Unknown(literal, span) => Err(RuntimeError::new(
+                &format!("Unknown expression '{}'.", literal),
+                *span,
+            )),
+            Expresion::Integer(value, _) => Ok(Value::Int(*value)),
+            Expresion::Float(value, _) => Ok(Value::Float(*value)),
+            Expresion::Str(value, _) => Ok(Value::String(value.clone())),
+            Expresion::Char(value, _) => Ok(Value::Char(*value)),
+            Expresion::Bool(value, _) => Ok(Value::Bool(*value)),
+            Expresion::Nil(_) => Ok(Value::Nil),
+            Expresion::Grouping(expression, _) => self.evaluate(expression),
+            Expresion::Variable(name, span) => self.variable(name, *span),
+            Expresion::Assign(name, value, span) => self.assign(name, value, *span),
+            Expresion::Postfix(name, operator, span) => self.postfix(name, operator, *span),
+            Expresion::Ternary(condition, then_branch, else_branch, span) => {
+                let value = if self.evaluate(condition)?.is_truthy() {
+                    self.evaluate(then_branch)?
+                } else {
+                    self.evaluate(else_branch)?
+                };
+                Ok(self.widen(value, *span))
+            }
+            Expresion::Unary(operator, right, span) => {
+                let right = self.evaluate(right)?;
+                operators::unary(operator, right).map_err(|e| RuntimeError::new(&e, *span))
+            }
+            Expresion::Binary(left, operator, right, span) => {
+                self.binary_expression(left, operator, right, *span)
+            }
+            Expresion::Call(callee, arguments, span) => {
+                self.call_expression(callee, arguments, *span)
+            }
+        }
+    }
+
+    // The cases of `evaluate` live in their own functions to keep its stack
+    // frame small, it is part of every level of recursion in a program.
+
+    fn variable(&mut self, name: &str, span: Span) -> ValueResult {
+        self.lookup(name, span)
+            .ok_or_else(|| RuntimeError::new(&format!("Undefined variable '{}'.", name), span))
+    }
+
+    fn lookup(&self, name: &str, span: Span) -> Option<Value> {
+        match self.resolution.get(span) {
+            Some(Binding::Local { depth, .. }) => self.environment.borrow().get_at(depth, name),
+            Some(Binding::Global) => self.globals.borrow().get_at(0, name),
+            None => self.environment.borrow().get(name),
+        }
+    }
+
+    fn assign(&mut self, name: &str, value: &Expresion, span: Span) -> ValueResult {
+        let value = self.evaluate(value)?;
+        let value = self.widen(value, span);
+        if self.store(name, value.clone(), span) {
+            Ok(value)
+        } else {
+            Err(RuntimeError::new(
+                &format!("Undefined variable '{}'.", name),
+                span,
+            ))
+        }
+    }
+
+    /// Widens an integer computed by a `float` typed assignment or ternary.
+    fn widen(&self, value: Value, span: Span) -> Value {
+        if self.resolution.widens(span) {
+            value.coerce(Some(DataType::Float))
+        } else {
+            value
+        }
+    }
+
+    fn store(&mut self, name: &str, value: Value, span: Span) -> bool {
+        match self.resolution.get(span) {
+            Some(Binding::Local { depth, .. }) => {
+                self.environment.borrow_mut().assign_at(depth, name, value)
+            }
+            Some(Binding::Global) => self.globals.borrow_mut().assign_at(0, name, value),
+            None => self.environment.borrow_mut().assign(name, value),
+        }
+    }
+
+    fn binary_expression(
+        &mut self,
+        left: &Expresion,
+        operator: &str,
+        right: &Expresion,
+        span: Span,
+    ) -> ValueResult {
+        let left = self.evaluate(left)?;
+
+        match operator {
+            "&&" if !left.is_truthy() => return Ok(Value::Bool(false)),
+            "||" if left.is_truthy() => return Ok(Value::Bool(true)),
+            "&&" | "||" => return Ok(Value::Bool(self.evaluate(right)?.is_truthy())),
+            _ => {}
+        }
+
+        let right = self.evaluate(right)?;
+        operators::binary(left, operator, right).map_err(|e| RuntimeError::new(&e, span))
+    }
+
+    fn call_expression(
+        &mut self,
+        callee: &Expresion,
+        arguments: &[Expresion],
+        span: Span,
+    ) -> ValueResult {
+        let callee = self.evaluate(callee)?;
+
+        let mut values = vec![];
+        for argument in arguments {
+            values.push(self.evaluate(argument)?);
+        }
+
+        self.call(callee, values, span)
+    }
+
+    /// Updates the variable and returns its previous value.
+    fn postfix(&mut self, name: &str, operator: &str, span: Span) -> ValueResult {
+        let old = self.variable(name, span)?;
+        let new = operators::binary(old.clone(), &operator[..1], Value::Int(1))
+            .map_err(|e| RuntimeError::new(&e, span))?;
+        self.store(name, new, span);
+        Ok(old)
+    }
+
+    fn call(&mut self, callee: Value, arguments: Vec<Value>, span: Span) -> ValueResult {
+        let function = match callee {
+            Value::Function(function) => function,
+            other => {
+                return Err(RuntimeError::new(
+                    &format!("Can only call functions, not {}.", other.type_name()),
+                    span,
+                ))
+            }
+        };
+
+        if arguments.len() != function.parameters.len() {
+            return Err(RuntimeError::new(
+                &format!(
+                    "Function '{}' expects {} arguments but got {}.",
+                    function.name,
+                    function.parameters.len(),
+                    arguments.len()
+                ),
+                span,
+            ));
+        }
+
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new("Stack overflow.", span));
+        }
+
+        let mut environment = Environment::with_enclosing(function.closure.clone());
+        for (parameter, argument) in function.parameters.iter().zip(arguments) {
+            environment.define(&parameter.name, argument.coerce(parameter.data_type));
+        }
+
+        self.depth += 1;
+        let result = self.execute_block(&function.body, Rc::new(RefCell::new(environment)));
+        self.depth -= 1;
+
+        let value = match result? {
+            Flow::Return(value) => value,
+            _ => Value::Nil,
+        };
+        Ok(value.coerce(function.return_type))
+    }
+
+    pub fn globals(&self) -> Rc<RefCell<Environment>> {
+        self.globals.clone()
+    }
+}
@@ -0,0 +1,218 @@
+#![allow(unused_imports, dead_code)]
+
+use super::runtime_error::RuntimeError;
+use crate::{test_support::interpret as run, types::span::Span};
+
+#[test]
+fn arithmetic() {
+    let output = run("print 1 + 2 * 3; print (1 + 2) * 3; print 7 / 2; print 7 / 2.0; print -4;");
+
+    assert_eq!(output.unwrap(), "7\n9\n3\n3.5\n-4\n");
+}
+
+#[test]
+fn number_literals() {
+    let output = run(
+        "print 3_000_000_000 * 2; print 0xFF + 0b1 + 0o10; print 1e3 / 8; print 2.5f;
+             print 3.14f; print 0.1f32 + 0.2f; print 9223372036854775807u64;",
+    );
+
+    assert_eq!(
+        output.unwrap(),
+        "6000000000\n264\n125\n2.5\n3.14\n0.30000000000000004\n9223372036854775807\n"
+    );
+}
+
+#[test]
+fn extended_operators() {
+    let output = run(r#"
+        print 17 % 5;
+        print 7.5 % 2;
+        print 2 ** 10;
+        print 2 ** -1.0;
+        print 6 & 3;
+        print 6 | 3;
+        print 6 ^ 3;
+        print ~0;
+        print 1 << 4;
+        print -16 >> 2;
+        print true ^ false;
+        print 1 > 2 ? "yes" : "no";
+    "#);
+
+    assert_eq!(
+        output.unwrap(),
+        "2\n1.5\n1024\n0.5\n2\n7\n5\n-1\n16\n-4\ntrue\nno\n"
+    );
+}
+
+#[test]
+fn compound_assignment_and_increment() {
+    let output = run(r#"
+        int x = 10;
+        x += 5;
+        x -= 3;
+        x *= 2;
+        x /= 4;
+        print x;
+        print x++;
+        print x;
+        print ++x;
+        print x--;
+        print --x;
+    "#);
+
+    assert_eq!(output.unwrap(), "6\n6\n7\n8\n8\n6\n");
+}
+
+#[test]
+fn strings_and_booleans() {
+    let output = run(r#"
+        string s = "hello";
+        print s + " world";
+        print 1 < 2 && "a" != "b";
+        print !true || nil == nil;
+    "#);
+
+    assert_eq!(output.unwrap(), "hello world\ntrue\ntrue\n");
+}
+
+#[test]
+fn chars_and_escapes() {
+    let output = run(r#"
+        char c = 'b';
+        char d;
+        print "\"" + c + '\u{21}' + "\"";
+        print c > 'a';
+        print c == 'b';
+        print d == '\0';
+    "#);
+
+    assert_eq!(output.unwrap(), "\"b!\"\ntrue\ntrue\ntrue\n");
+}
+
+#[test]
+fn variables_and_scopes() {
+    let output = run(r#"
+        int x = 10;
+        float y = 2;
+        bool z;
+        {
+            int x = 20;
+            print x;
+            y = y / 4;
+        }
+        print x;
+        print y;
+        print z;
+    "#);
+
+    assert_eq!(output.unwrap(), "20\n10\n0.5\nfalse\n");
+}
+
+#[test]
+fn control_flow() {
+    let output = run(r#"
+        int sum = 0;
+        for (int i = 0; i < 5; i = i + 1) {
+            if (i == 2) print "two"; else sum = sum + i;
+        }
+        while (sum > 0) sum = sum - 3;
+        print sum;
+    "#);
+
+    assert_eq!(output.unwrap(), "two\n-1\n");
+}
+
+#[test]
+fn functions_and_recursion() {
+    let output = run(r#"
+        int fib(int n) {
+            if (n < 2) return n;
+            return fib(n - 1) + fib(n - 2);
+        }
+        fun counter() {
+            int count = 0;
+            fun next() {
+                count = count + 1;
+                return count;
+            }
+            return next;
+        }
+        print fib(15);
+        int next = counter();
+        next();
+        print next();
+    "#);
+
+    assert_eq!(output.unwrap(), "610\n2\n");
+}
+
+#[test]
+fn break_and_continue() {
+    let output = run(r#"
+        var total = 0;
+        for (int i = 0; i < 10; i = i + 1) {
+            if (i == 2) continue;
+            if (i == 5) break;
+            total = total + i;
+        }
+        print total;
+
+        int n = 0;
+        while (true) {
+            n = n + 1;
+            if (n < 3) { continue; }
+            break;
+        }
+        print n;
+    "#);
+
+    assert_eq!(output.unwrap(), "8\n3\n");
+}
+
+#[test]
+fn var_declarations() {
+    let output = run("var a; var b = 1.5; print a; print b; a = \"x\"; print a;");
+
+    assert_eq!(output.unwrap(), "nil\n1.5\nx\n");
+}
+
+#[test]
+fn runtime_errors_have_lines() {
+    let error = run("int x = 1;\nprint x + \"a\";").unwrap_err();
+    assert_eq!(error.span, Span::new(19, 20, 2, 9));
+
+    let error = run("print 1;\n\nprint y;").unwrap_err();
+    assert_eq!(
+        error,
+        RuntimeError::new("Undefined variable 'y'.", Span::new(16, 17, 3, 7))
+    );
+
+    let error = run("print 10 / 0;").unwrap_err();
+    assert_eq!(error.message, "Division by zero.");
+
+    let error = run("print 10 % 0;").unwrap_err();
+    assert_eq!(error.message, "Division by zero.");
+
+    let error = run("print 1 << 64;").unwrap_err();
+    assert_eq!(error.message, "Shift amount out of range.");
+
+    let error = run("print 2 ** 63;").unwrap_err();
+    assert_eq!(error.message, "Integer overflow.");
+
+    let error = run("int x = 2 ** -1;\nprint x;").unwrap_err();
+    assert_eq!(
+        error,
+        RuntimeError::new(
+            "Negative exponent of an integer power.",
+            Span::new(10, 12, 1, 11)
+        )
+    );
+
+    let error = run("fun f(a) { return a; }\nf(1, 2);").unwrap_err();
+    assert_eq!(error.span, Span::new(23, 30, 2, 1));
+
+    let error = run("fun f() { return f(); }\nf();").unwrap_err();
+    assert_eq!(error.message, "Stack overflow.");
+}
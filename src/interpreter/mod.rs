@@ -0,0 +1,7 @@
+pub mod environment;
+pub mod evaluator;
+#[cfg(test)]
+mod evaluator_test;
+pub mod operators;
+pub mod runtime_error;
+pub mod value;
@@ -0,0 +1,120 @@
+#![allow(dead_code)]
+
+use super::value::Value;
+
+/// Outcome of applying an operator, the error is the runtime message without
+/// a location so each backend can attach its own.
+pub type OperatorResult = Result<Value, String>;
+
+pub fn unary(operator: &str, right: Value) -> OperatorResult {
+    match (operator, right) {
+        ("!", right) => Ok(Value::Bool(!right.is_truthy())),
+        ("-", Value::Int(value)) => value
+            .checked_neg()
+            .map(Value::Int)
+            .ok_or_else(|| "Integer overflow.".to_owned()),
+        ("-", Value::Float(value)) => Ok(Value::Float(-value)),
+        ("~", Value::Int(value)) => Ok(Value::Int(!value)),
+        (operator, right) => Err(format!(
+            "Operator '{}' cannot be applied to {}.",
+            operator,
+            right.type_name()
+        )),
+    }
+}
+
+/// Applies every binary operator except the short-circuiting `&&` and `||`.
+pub fn binary(left: Value, operator: &str, right: Value) -> OperatorResult {
+    let overflow = || "Integer overflow.".to_owned();
+
+    let value = match (operator, &left, &right) {
+        ("==", _, _) => Value::Bool(left == right),
+        ("!=", _, _) => Value::Bool(left != right),
+
+        ("/" | "%", Value::Int(_), Value::Int(0)) => return Err("Division by zero.".to_owned()),
+        ("+", Value::Int(a), Value::Int(b)) => Value::Int(a.checked_add(*b).ok_or_else(overflow)?),
+        ("-", Value::Int(a), Value::Int(b)) => Value::Int(a.checked_sub(*b).ok_or_else(overflow)?),
+        ("*", Value::Int(a), Value::Int(b)) => Value::Int(a.checked_mul(*b).ok_or_else(overflow)?),
+        ("/", Value::Int(a), Value::Int(b)) => Value::Int(a.checked_div(*b).ok_or_else(overflow)?),
+        ("%", Value::Int(a), Value::Int(b)) => Value::Int(a.checked_rem(*b).ok_or_else(overflow)?),
+        ("**", Value::Int(_), Value::Int(b)) if *b < 0 => {
+            return Err("Negative exponent of an integer power.".to_owned())
+        }
+        ("**", Value::Int(a), Value::Int(b)) => match (u32::try_from(*b), a) {
+            (Ok(b), _) => Value::Int(a.checked_pow(b).ok_or_else(overflow)?),
+            // Only 0, 1 and -1 stay in range for exponents past `u32`.
+            (Err(_), 0 | 1) => Value::Int(*a),
+            (Err(_), -1) => Value::Int(if b % 2 == 0 { 1 } else { -1 }),
+            (Err(_), _) => return Err(overflow()),
+        },
+
+        ("&", Value::Int(a), Value::Int(b)) => Value::Int(a & b),
+        ("|", Value::Int(a), Value::Int(b)) => Value::Int(a | b),
+        ("^", Value::Int(a), Value::Int(b)) => Value::Int(a ^ b),
+        ("<<" | ">>", Value::Int(a), Value::Int(b)) => {
+            let shift = u32::try_from(*b)
+                .ok()
+                .filter(|b| *b < i64::BITS)
+                .ok_or_else(|| "Shift amount out of range.".to_owned())?;
+            Value::Int(if operator == "<<" {
+                a << shift
+            } else {
+                a >> shift
+            })
+        }
+        ("&", Value::Bool(a), Value::Bool(b)) => Value::Bool(a & b),
+        ("|", Value::Bool(a), Value::Bool(b)) => Value::Bool(a | b),
+        ("^", Value::Bool(a), Value::Bool(b)) => Value::Bool(a ^ b),
+
+        ("+", Value::String(a), Value::String(b)) => Value::String(format!("{}{}", a, b)),
+
+        (">", Value::String(a), Value::String(b)) => Value::Bool(a > b),
+        (">=", Value::String(a), Value::String(b)) => Value::Bool(a >= b),
+        ("<", Value::String(a), Value::String(b)) => Value::Bool(a < b),
+        ("<=", Value::String(a), Value::String(b)) => Value::Bool(a <= b),
+
+        ("+", Value::String(a), Value::Char(b)) => Value::String(format!("{}{}", a, b)),
+        ("+", Value::Char(a), Value::String(b)) => Value::String(format!("{}{}", a, b)),
+
+        (">", Value::Char(a), Value::Char(b)) => Value::Bool(a > b),
+        (">=", Value::Char(a), Value::Char(b)) => Value::Bool(a >= b),
+        ("<", Value::Char(a), Value::Char(b)) => Value::Bool(a < b),
+        ("<=", Value::Char(a), Value::Char(b)) => Value::Bool(a <= b),
+
+        _ => match (number(&left), number(&right)) {
+            (Some(a), Some(b)) => match operator {
+                "+" => Value::Float(a + b),
+                "-" => Value::Float(a - b),
+                "*" => Value::Float(a * b),
+                "/" => Value::Float(a / b),
+                "%" => Value::Float(a % b),
+                "**" => Value::Float(a.powf(b)),
+                ">" => Value::Bool(a > b),
+                ">=" => Value::Bool(a >= b),
+                "<" => Value::Bool(a < b),
+                "<=" => Value::Bool(a <= b),
+                _ => return Err(operand_error(&left, operator, &right)),
+            },
+            _ => return Err(operand_error(&left, operator, &right)),
+        },
+    };
+
+    Ok(value)
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(value) => Some(*value as f64),
+        Value::Float(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn operand_error(left: &Value, operator: &str, right: &Value) -> String {
+    format!(
+        "Operator '{}' cannot be applied to {} and {}.",
+        operator,
+        left.type_name(),
+        right.type_name()
+    )
+}
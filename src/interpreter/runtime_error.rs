@@ -0,0 +1,28 @@
+#![allow(dead_code)]
+
+use std::{error::Error, fmt};
+
+use crate::types::span::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl RuntimeError {
+    pub fn new(message: &str, span: Span) -> Self {
+        Self {
+            message: message.to_owned(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.message)
+    }
+}
+
+impl Error for RuntimeError {}
@@ -0,0 +1,121 @@
+#![allow(dead_code)]
+
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use super::environment::Environment;
+use crate::{
+    ast::tree_types::{DataType, Parameter, Statement},
+    bytecode::object::Closure,
+    types::token_value::TokenValue,
+};
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Nil,
+    Int(i64),
+    Float(f64),
+    String(String),
+    Char(char),
+    Bool(bool),
+    Function(Rc<Function>),
+    /// A function compiled for the virtual machine.
+    Closure(Rc<Closure>),
+}
+
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Option<DataType>,
+    pub body: Vec<Statement>,
+    pub closure: Rc<RefCell<Environment>>,
+}
+
+impl Value {
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Nil => false,
+            Value::Bool(value) => *value,
+            _ => true,
+        }
+    }
+
+    /// Value a declaration of the given type holds before it is assigned.
+    pub fn default_for(data_type: DataType) -> Self {
+        match data_type {
+            DataType::Int => Value::Int(0),
+            DataType::Float => Value::Float(0.0),
+            DataType::String => Value::String(String::new()),
+            DataType::Char => Value::Char('\0'),
+            DataType::Bool => Value::Bool(false),
+            DataType::Void => Value::Nil,
+        }
+    }
+
+    /// Widens integers stored into `float` declarations.
+    pub fn coerce(self, data_type: Option<DataType>) -> Self {
+        match (self, data_type) {
+            (Value::Int(value), Some(DataType::Float)) => Value::Float(value as f64),
+            (value, _) => value,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Char(_) => "char",
+            Value::Bool(_) => "boolean",
+            Value::Function(_) | Value::Closure(_) => "function",
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Int(a), Value::Float(b)) | (Value::Float(b), Value::Int(a)) => *a as f64 == *b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Char(a), Value::Char(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::Closure(a), Value::Closure(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl From<&TokenValue> for Value {
+    fn from(value: &TokenValue) -> Self {
+        match value {
+            TokenValue::None => Value::Nil,
+            TokenValue::Int(value) => Value::Int(*value),
+            TokenValue::UInt(value) => Value::Int(*value as i64),
+            TokenValue::Float(value) => Value::Float(*value),
+            TokenValue::Float32(value) => Value::Float(TokenValue::widen_f32(*value)),
+            TokenValue::String(value) => Value::String(value.clone()),
+            TokenValue::Char(value) => Value::Char(*value),
+            TokenValue::Bool(value) => Value::Bool(*value),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Char(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Function(function) => write!(f, "<fn {}>", function.name),
+            Value::Closure(closure) => write!(f, "<fn {}>", closure.function.name),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+#![allow(dead_code)]
+
+use std::{error::Error, fmt};
+
+use crate::types::span::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexErrorKind {
+    UnexpectedCharacter(String),
+    UnterminatedString,
+    UnterminatedComment,
+    MalformedNumber(String),
+    /// The literal and the type it does not fit in.
+    NumberOutOfRange(String, &'static str),
+    InvalidEscape(String),
+    UnterminatedCharacter,
+    /// A quoted literal that does not hold exactly one character.
+    InvalidCharacterLiteral(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span: Span,
+}
+
+impl LexError {
+    pub fn new(kind: LexErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    pub fn message(&self) -> String {
+        match &self.kind {
+            LexErrorKind::UnexpectedCharacter(literal) => {
+                format!("unexpected character '{}'", literal)
+            }
+            LexErrorKind::UnterminatedString => "unterminated string literal".to_owned(),
+            LexErrorKind::UnterminatedComment => "unterminated block comment".to_owned(),
+            LexErrorKind::MalformedNumber(literal) => {
+                format!("malformed number literal '{}'", literal)
+            }
+            LexErrorKind::NumberOutOfRange(literal, target) => {
+                format!(
+                    "number literal '{}' is out of range for {}",
+                    literal, target
+                )
+            }
+            LexErrorKind::InvalidEscape(sequence) => {
+                format!("unknown escape sequence '{}'", sequence)
+            }
+            LexErrorKind::UnterminatedCharacter => "unterminated character literal".to_owned(),
+            LexErrorKind::InvalidCharacterLiteral(literal) => {
+                format!(
+                    "character literal {} must hold exactly one character",
+                    literal
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}.", self.span, self.message())
+    }
+}
+
+impl Error for LexError {}
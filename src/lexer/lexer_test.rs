@@ -1,8 +1,11 @@
 #![allow(unused_imports)]
 
 use crate::{
-    lexer::scanner::Scanner,
-    types::{token_type::*, token_value::TokenValue},
+    lexer::{
+        lex_error::{LexError, LexErrorKind},
+        scanner::Scanner,
+    },
+    types::{span::Span, token_type::*, token_value::TokenValue},
 };
 
 #[test]
@@ -10,7 +13,7 @@ fn length() {
     let input = "int i = 10;";
     let scanner = Scanner::new(input);
 
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
     assert_eq!(tokens.len(), 5);
 }
 
@@ -19,7 +22,7 @@ fn int_expression() {
     let input = r"int i = 10;";
     let scanner = Scanner::new(input);
 
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens.len(), 5);
     assert_eq!(tokens[0].token_type, TokenType::Int);
@@ -30,13 +33,15 @@ fn new_line() {
     let input = r"
     int";
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens[0].token_type, TokenType::Int);
-    assert_eq!(tokens[0].line, 1);
+    assert_eq!(tokens[0].span.line, 2);
+    assert_eq!(tokens[0].span.column, 5);
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn emelent_count() {
     let input = r"
         int x = 10;
@@ -49,7 +54,7 @@ fn emelent_count() {
         }
     ";
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let (tokens, _) = scanner.scan();
 
     assert_eq!(
         tokens
@@ -79,21 +84,21 @@ fn element_lines() {
         }
     ";
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let (tokens, _) = scanner.scan();
 
     let result = tokens
         .iter()
         .filter(|e| e.token_type == TokenType::Semicolon)
-        .map(|e| e.line)
-        .collect::<Vec<i32>>();
-    assert_eq!(result, vec![1, 2, 3, 6]);
+        .map(|e| e.span.line)
+        .collect::<Vec<usize>>();
+    assert_eq!(result, vec![2, 3, 4, 7]);
 }
 
 #[test]
 fn eq_tokens() {
     let input = r"    == <= >= != =    ";
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     println!("{:?}", tokens);
     assert_eq!(tokens.len(), 5);
@@ -108,7 +113,7 @@ fn eq_tokens() {
 fn math_tokens() {
     let input = r"5 + 4 - 7*10/2";
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     println!("{:?}", tokens);
     assert_eq!(tokens.len(), 9);
@@ -122,7 +127,7 @@ fn math_tokens() {
 fn commas() {
     let input = r#""hello",5,5.45"#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     println!("{:?}", tokens);
     assert_eq!(tokens.len(), 5);
@@ -137,7 +142,7 @@ fn commas() {
 fn dots() {
     let input = r#"CLASS_NAME.FUNCTION,5,5.45"#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     println!("{:?}", tokens);
     assert_eq!(tokens.len(), 7);
@@ -154,7 +159,7 @@ fn dots() {
 fn equals() {
     let input = r#"==="#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens.len(), 2);
     assert_eq!(tokens[0].literal, "==");
@@ -163,20 +168,22 @@ fn equals() {
 
 #[test]
 fn and_or() {
-    let input = r#" || && "#;
+    let input = r#" || && or and "#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
-    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens.len(), 4);
     assert_eq!(tokens[0].token_type, TokenType::Or);
     assert_eq!(tokens[1].token_type, TokenType::And);
+    assert_eq!(tokens[2].token_type, TokenType::Identifier);
+    assert_eq!(tokens[3].token_type, TokenType::Identifier);
 }
 
 #[test]
 fn true_false() {
     let input = r#" true  false "#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens.len(), 2);
     assert_eq!(tokens[0].value, TokenValue::Bool(true));
@@ -187,7 +194,7 @@ fn true_false() {
 fn string() {
     let input = r#" "Hello".,"World" "#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens.len(), 4);
     assert_eq!(tokens[0].value, TokenValue::String("Hello".to_owned()));
@@ -198,9 +205,9 @@ fn string() {
 
 #[test]
 fn int() {
-    let input = r#" 123.456Hello 123 Hello123"#;
+    let input = r#" 123.456 Hello 123 Hello123"#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens[0].literal, "123.456");
     assert_eq!(tokens[0].token_type, TokenType::Real);
@@ -221,7 +228,7 @@ fn int() {
 fn name() {
     let input = r#" variable variable1 5000 variable2variable "#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens.len(), 4);
     assert_eq!(tokens[0].literal, "variable");
@@ -243,9 +250,9 @@ fn while_loop() {
     }
     "#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
-    assert_eq!(tokens[0].token_type, TokenType::Boolean);
+    assert_eq!(tokens[0].token_type, TokenType::Bool);
     assert_eq!(tokens[1].token_type, TokenType::Identifier);
     assert_eq!(tokens[2].token_type, TokenType::Equal);
     assert_eq!(tokens[3].token_type, TokenType::True);
@@ -268,18 +275,23 @@ fn error_standard_strings() {
     "
     "#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let (tokens, errors) = scanner.scan();
 
-    assert_eq!(tokens[0].token_type, TokenType::Unknown);
-    assert_eq!(tokens[1].token_type, TokenType::Identifier);
-    assert_eq!(tokens[2].token_type, TokenType::Unknown);
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token_type, TokenType::Identifier);
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].kind, LexErrorKind::UnterminatedString);
+    assert_eq!(errors[0].span.line, 2);
+    assert_eq!(errors[1].kind, LexErrorKind::UnterminatedString);
+    assert_eq!(errors[1].span.line, 4);
 }
 
 #[test]
 fn assign() {
     let input = r#"int i=10+20;"#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens[0].token_type, TokenType::Int);
     assert_eq!(tokens[1].token_type, TokenType::Identifier);
@@ -293,7 +305,7 @@ fn assign() {
 fn brackets() {
     let input = r#"{[()]}"#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens.len(), 6);
     assert_eq!(tokens[0].token_type, TokenType::LeftBrace);
@@ -308,7 +320,7 @@ fn brackets() {
 fn minus_number() {
     let input = r#"float i  = -5.123;"#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens[0].token_type, TokenType::Float);
     assert_eq!(tokens[1].token_type, TokenType::Identifier);
@@ -325,7 +337,7 @@ fn comment() {
     int y = 5;
     "#;
     let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
+    let tokens = scanner.get_tokens().unwrap();
 
     assert_eq!(tokens[0].token_type, TokenType::Int);
     assert_eq!(tokens[1].token_type, TokenType::Identifier);
@@ -339,3 +351,519 @@ fn comment() {
     assert_eq!(tokens[8].token_type, TokenType::Integer);
     assert_eq!(tokens[9].token_type, TokenType::Semicolon);
 }
+
+#[test]
+fn block_comment() {
+    let input = r#"/*
+     * Header comment with "quotes" and // slashes
+     */
+    int i = 5; /* trailing */ int y /* inline */ = 6;
+    "#;
+    let scanner = Scanner::new(input);
+    let tokens = scanner.get_tokens().unwrap();
+
+    assert_eq!(tokens.len(), 10);
+    assert_eq!(tokens[0].token_type, TokenType::Int);
+    assert_eq!(tokens[0].span.line, 4);
+    assert_eq!(tokens[5].token_type, TokenType::Int);
+    assert_eq!(tokens[7].token_type, TokenType::Equal);
+    assert_eq!(tokens[7].span.line, 4);
+}
+
+#[test]
+fn nested_block_comment() {
+    let input = r#"1 /* outer /* inner */
+    still comment */ 2"#;
+    let scanner = Scanner::new(input);
+    let tokens = scanner.get_tokens().unwrap();
+
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[0].value, TokenValue::Int(1));
+    assert_eq!(tokens[1].value, TokenValue::Int(2));
+    assert_eq!(tokens[1].span.line, 2);
+}
+
+#[test]
+fn comment_after_multiplication() {
+    let tokens = Scanner::new("a*/*c*/b").get_tokens().unwrap();
+
+    let types = tokens.iter().map(|t| t.token_type).collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            TokenType::Identifier,
+            TokenType::Star,
+            TokenType::Identifier
+        ]
+    );
+}
+
+#[test]
+fn unterminated_block_comment() {
+    let input = r#"int x;
+    /* never /* closed */
+    int y;"#;
+    let scanner = Scanner::new(input);
+    let errors = scanner.get_tokens().unwrap_err();
+
+    assert_eq!(
+        errors,
+        vec![LexError::new(
+            LexErrorKind::UnterminatedComment,
+            Span::new(11, 13, 2, 5)
+        )]
+    );
+}
+
+#[test]
+fn spans() {
+    let input = "int x = 10;\n  print \"hi\";";
+    let scanner = Scanner::new(input);
+    let tokens = scanner.get_tokens().unwrap();
+
+    assert_eq!(tokens[0].span, Span::new(0, 3, 1, 1));
+    assert_eq!(tokens[3].span, Span::new(8, 10, 1, 9));
+    assert_eq!(tokens[5].span, Span::new(14, 19, 2, 3));
+    assert_eq!(tokens[6].span, Span::new(20, 24, 2, 9));
+    assert_eq!(&input[tokens[6].span.start..tokens[6].span.end], "\"hi\"");
+}
+
+#[test]
+fn unexpected_characters() {
+    let input = "int x = 1 @ 2; # $";
+    let scanner = Scanner::new(input);
+    let errors = scanner.get_tokens().unwrap_err();
+
+    let kinds = errors.iter().map(|e| e.kind.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            LexErrorKind::UnexpectedCharacter("@".to_owned()),
+            LexErrorKind::UnexpectedCharacter("#".to_owned()),
+            LexErrorKind::UnexpectedCharacter("$".to_owned()),
+        ]
+    );
+    assert_eq!(errors[0].span, Span::new(10, 11, 1, 11));
+}
+
+#[test]
+fn malformed_numbers() {
+    let input = "float y = 3.14q; int z = 12abc; int w = 7;";
+    let scanner = Scanner::new(input);
+    let (tokens, errors) = scanner.scan();
+
+    assert_eq!(
+        errors,
+        vec![
+            LexError::new(
+                LexErrorKind::MalformedNumber("3.14q".to_owned()),
+                Span::new(10, 15, 1, 11)
+            ),
+            LexError::new(
+                LexErrorKind::MalformedNumber("12abc".to_owned()),
+                Span::new(25, 30, 1, 26)
+            ),
+        ]
+    );
+    assert_eq!(tokens.last().unwrap().token_type, TokenType::Semicolon);
+    assert!(tokens.iter().any(|t| t.value == TokenValue::Int(7)));
+}
+
+#[test]
+fn string_escapes() {
+    let input = r#""a\"b" "line\n\ttab\\" "\u{48}\u{1F600}""#;
+    let scanner = Scanner::new(input);
+    let tokens = scanner.get_tokens().unwrap();
+
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(tokens[0].value, TokenValue::String("a\"b".to_owned()));
+    assert_eq!(tokens[0].literal, r#""a\"b""#);
+    assert_eq!(
+        tokens[1].value,
+        TokenValue::String("line\n\ttab\\".to_owned())
+    );
+    assert_eq!(tokens[2].value, TokenValue::String("H\u{1F600}".to_owned()));
+}
+
+#[test]
+fn invalid_escapes() {
+    let input = r#"string s = "a\qb"; string t = "\u{110000}"; int x;"#;
+    let scanner = Scanner::new(input);
+    let (tokens, errors) = scanner.scan();
+
+    assert_eq!(
+        errors,
+        vec![
+            LexError::new(
+                LexErrorKind::InvalidEscape("\\q".to_owned()),
+                Span::new(13, 15, 1, 14)
+            ),
+            LexError::new(
+                LexErrorKind::InvalidEscape("\\u{110000}".to_owned()),
+                Span::new(31, 41, 1, 32)
+            ),
+        ]
+    );
+    assert!(tokens.iter().all(|t| t.token_type != TokenType::Str));
+    assert_eq!(tokens.last().unwrap().token_type, TokenType::Semicolon);
+}
+
+#[test]
+fn char_literals() {
+    let input = r#"char c = 'a'; '\n' '\'' '\u{e9}'"#;
+    let scanner = Scanner::new(input);
+    let tokens = scanner.get_tokens().unwrap();
+
+    assert_eq!(tokens[0].token_type, TokenType::Char);
+    assert_eq!(tokens[3].token_type, TokenType::Character);
+    assert_eq!(tokens[3].value, TokenValue::Char('a'));
+    assert_eq!(tokens[3].span, Span::new(9, 12, 1, 10));
+    assert_eq!(tokens[5].value, TokenValue::Char('\n'));
+    assert_eq!(tokens[6].value, TokenValue::Char('\''));
+    assert_eq!(tokens[7].value, TokenValue::Char('é'));
+}
+
+#[test]
+fn invalid_char_literals() {
+    let input = "print('Hello, world!');\n'' 'x";
+    let scanner = Scanner::new(input);
+    let (tokens, errors) = scanner.scan();
+
+    assert_eq!(
+        errors,
+        vec![
+            LexError::new(
+                LexErrorKind::InvalidCharacterLiteral("'Hello, world!'".to_owned()),
+                Span::new(6, 21, 1, 7)
+            ),
+            LexError::new(
+                LexErrorKind::InvalidCharacterLiteral("''".to_owned()),
+                Span::new(24, 26, 2, 1)
+            ),
+            LexError::new(LexErrorKind::UnterminatedCharacter, Span::new(27, 28, 2, 4)),
+        ]
+    );
+    assert_eq!(tokens.len(), 4);
+}
+
+#[test]
+fn multi_line_strings() {
+    let input = "string sql = \"\"\"SELECT *\n  FROM t\n  WHERE a = \"x\";\"\"\";\nprint sql;";
+    let scanner = Scanner::new(input);
+    let tokens = scanner.get_tokens().unwrap();
+
+    assert_eq!(tokens[3].token_type, TokenType::Str);
+    assert_eq!(
+        tokens[3].value,
+        TokenValue::String("SELECT *\n  FROM t\n  WHERE a = \"x\";".to_owned())
+    );
+    assert_eq!(tokens[3].span, Span::new(13, 53, 1, 14));
+    assert_eq!(tokens[4].span, Span::new(53, 54, 3, 20));
+    assert_eq!(tokens[5].span, Span::new(55, 60, 4, 1));
+}
+
+#[test]
+fn raw_strings() {
+    let input = "r\"C:\\dir\\n\" r#\"{\"key\": \"va\nlue\"}\"# raw";
+    let scanner = Scanner::new(input);
+    let tokens = scanner.get_tokens().unwrap();
+
+    assert_eq!(tokens.len(), 3);
+    assert_eq!(tokens[0].value, TokenValue::String("C:\\dir\\n".to_owned()));
+    assert_eq!(
+        tokens[1].value,
+        TokenValue::String("{\"key\": \"va\nlue\"}".to_owned())
+    );
+    assert_eq!(tokens[2].token_type, TokenType::Identifier);
+    assert_eq!(tokens[2].span.line, 2);
+}
+
+#[test]
+fn unterminated_multi_line_strings() {
+    let input = "\"\"\"open\n\nint x;\nr#\"also \"open\"";
+    let scanner = Scanner::new(input);
+    let errors = scanner.get_tokens().unwrap_err();
+
+    assert_eq!(
+        errors,
+        vec![LexError::new(
+            LexErrorKind::UnterminatedString,
+            Span::new(0, 3, 1, 1)
+        )]
+    );
+
+    let input = "int x;\nr#\"also \"open\"";
+    let errors = Scanner::new(input).get_tokens().unwrap_err();
+    assert_eq!(
+        errors,
+        vec![LexError::new(
+            LexErrorKind::UnterminatedString,
+            Span::new(7, 10, 2, 1)
+        )]
+    );
+}
+
+#[test]
+fn number_literals() {
+    let input = "0xFF 0b1010 0o17 1_000_000 1e-9 2.5E+3 2.75f 7i32 8u64 9f64 0x_7fi64 1.";
+    let scanner = Scanner::new(input);
+    let tokens = scanner.get_tokens().unwrap();
+
+    let values = tokens.iter().map(|t| t.value.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        vec![
+            TokenValue::Int(255),
+            TokenValue::Int(10),
+            TokenValue::Int(15),
+            TokenValue::Int(1_000_000),
+            TokenValue::Float(1e-9),
+            TokenValue::Float(2500.0),
+            TokenValue::Float32(2.75),
+            TokenValue::Int(7),
+            TokenValue::UInt(8),
+            TokenValue::Float(9.0),
+            TokenValue::Int(127),
+            TokenValue::Int(1),
+            TokenValue::None,
+        ]
+    );
+    assert_eq!(tokens[6].token_type, TokenType::Real);
+    assert_eq!(tokens[6].literal, "2.75f");
+    assert_eq!(tokens[8].token_type, TokenType::Integer);
+    assert_eq!(tokens[12].token_type, TokenType::Dot);
+}
+
+#[test]
+fn invalid_number_literals() {
+    let input =
+        "9223372036854775808 3000000000i32 0xFFFFFFFFFFFFFFFFu64 1e999 1e39f 0b102 0x 1.5i32 1e \
+         9223372036854775807u64";
+    let scanner = Scanner::new(input);
+    let (tokens, errors) = scanner.scan();
+
+    let kinds = errors.iter().map(|e| e.kind.clone()).collect::<Vec<_>>();
+    let out_of_range =
+        |literal: &str, target| LexErrorKind::NumberOutOfRange(literal.to_owned(), target);
+    let malformed = |literal: &str| LexErrorKind::MalformedNumber(literal.to_owned());
+    assert_eq!(
+        kinds,
+        vec![
+            out_of_range("9223372036854775808", "i64"),
+            out_of_range("3000000000i32", "i32"),
+            out_of_range("0xFFFFFFFFFFFFFFFFu64", "int"),
+            out_of_range("1e999", "f64"),
+            out_of_range("1e39f", "f32"),
+            malformed("0b102"),
+            malformed("0x"),
+            malformed("1.5i32"),
+            malformed("1e"),
+        ]
+    );
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].value, TokenValue::UInt(i64::MAX as u64));
+    assert_eq!(errors[1].span, Span::new(20, 33, 1, 21));
+}
+
+#[test]
+fn unicode_identifiers() {
+    let input = "int _count = 1; float größe; string 変数; _ π2 naïve";
+    let scanner = Scanner::new(input);
+    let tokens = scanner.get_tokens().unwrap();
+
+    let identifiers = tokens
+        .iter()
+        .filter(|t| t.token_type == TokenType::Identifier)
+        .map(|t| t.literal.as_ref())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        identifiers,
+        vec!["_count", "größe", "変数", "_", "π2", "naïve"]
+    );
+}
+
+#[test]
+fn non_identifier_characters() {
+    let input = "int x = 1 🦀 ²;";
+    let scanner = Scanner::new(input);
+    let errors = scanner.get_tokens().unwrap_err();
+
+    let kinds = errors.iter().map(|e| e.kind.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            LexErrorKind::UnexpectedCharacter("🦀".to_owned()),
+            LexErrorKind::UnexpectedCharacter("²".to_owned()),
+        ]
+    );
+    assert_eq!(errors[1].span, Span::new(15, 17, 1, 13));
+}
+
+#[test]
+fn grapheme_columns() {
+    // "é" is written precomposed on the first line and as "e" followed by a
+    // combining accent on the second, both count as a single column.
+    let input = "\"é\" x;\n\"e\u{301}\" 🦀y 'ü' z";
+    let scanner = Scanner::new(input);
+    let (tokens, _) = scanner.scan();
+
+    assert_eq!(tokens[1].span, Span::new(5, 6, 1, 5));
+    assert_eq!(tokens[3].span, Span::new(8, 13, 2, 1));
+    assert_eq!(tokens[4].span, Span::new(18, 19, 2, 6));
+    assert_eq!(tokens[5].span.column, 8);
+    assert_eq!(tokens[6].span, Span::new(25, 26, 2, 12));
+}
+
+#[test]
+fn keyword_table() {
+    for (keyword, token_type) in KEYWORDS {
+        let tokens = Scanner::new(keyword).get_tokens().unwrap();
+
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token_type, *token_type, "{}", keyword);
+        assert!(token_type.is_keyword());
+    }
+
+    let tokens = Scanner::new("bool boolean void variable classy")
+        .get_tokens()
+        .unwrap();
+    let types = tokens.iter().map(|t| t.token_type).collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            TokenType::Bool,
+            TokenType::Bool,
+            TokenType::Void,
+            TokenType::Identifier,
+            TokenType::Identifier,
+        ]
+    );
+    assert!(TokenType::Bool.is_type() && !TokenType::True.is_type());
+}
+
+#[test]
+fn sample_file() {
+    let input = include_str!("../../source_files/file1.lox");
+    let tokens = Scanner::new(input).get_tokens().unwrap();
+
+    assert_eq!(tokens[0].token_type, TokenType::Var);
+    assert_eq!(tokens[5].token_type, TokenType::Var);
+}
+
+#[test]
+fn extended_operators() {
+    let input = "% += -= *= /= ++ -- -> :: ? : & | ^ ~ << >> ** && || - * / < >";
+    let scanner = Scanner::new(input);
+    let tokens = scanner.get_tokens().unwrap();
+
+    let types = tokens.iter().map(|t| t.token_type).collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            TokenType::Percent,
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+            TokenType::PlusPlus,
+            TokenType::MinusMinus,
+            TokenType::Arrow,
+            TokenType::ColonColon,
+            TokenType::Question,
+            TokenType::Colon,
+            TokenType::Ampersand,
+            TokenType::Pipe,
+            TokenType::Caret,
+            TokenType::Tilde,
+            TokenType::LessLess,
+            TokenType::GreaterGreater,
+            TokenType::StarStar,
+            TokenType::And,
+            TokenType::Or,
+            TokenType::Minus,
+            TokenType::Star,
+            TokenType::Slash,
+            TokenType::Less,
+            TokenType::Greater,
+        ]
+    );
+    assert_eq!(tokens[17].span, Span::new(44, 46, 1, 45));
+}
+
+#[test]
+fn token_stream() {
+    let input = "x = 1 @ \"a\\q\" + y;";
+    let items = Scanner::new(input)
+        .tokens()
+        .map(|item| match item {
+            Ok(token) => Ok((token.token_type, token.literal)),
+            Err(error) => Err(error.kind),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        items,
+        vec![
+            Ok((TokenType::Identifier, "x".into())),
+            Ok((TokenType::Equal, "=".into())),
+            Ok((TokenType::Integer, "1".into())),
+            Err(LexErrorKind::UnexpectedCharacter("@".to_owned())),
+            Err(LexErrorKind::InvalidEscape("\\q".to_owned())),
+            Ok((TokenType::Plus, "+".into())),
+            Ok((TokenType::Identifier, "y".into())),
+            Ok((TokenType::Semicolon, ";".into())),
+        ]
+    );
+}
+
+#[test]
+fn token_stream_is_lazy() {
+    // Scanning stops at the second token, the unterminated string after it
+    // is never reached.
+    let input = "a b \"never closed";
+    let mut tokens = Scanner::new(input).tokens();
+
+    assert_eq!(tokens.next().unwrap().unwrap().literal, "a");
+    let b = tokens.next().unwrap().unwrap();
+    assert_eq!(b.literal, "b");
+    assert!(std::ptr::eq(&*b.literal, &input[2..3]));
+
+    assert_eq!(
+        tokens.next().unwrap().unwrap_err().kind,
+        LexErrorKind::UnterminatedString
+    );
+    assert!(tokens.next().is_none());
+}
+
+/// Run with `cargo test --release -- --ignored --nocapture scanner_throughput`.
+#[test]
+#[ignore]
+fn scanner_throughput() {
+    let chunk = r#"// generated
+int counter = 0;
+fun add(int a, int b) {
+    /* block comment */
+    return a + b * 2 - (a / 3);
+}
+while (counter <= 1000 && counter != 5 || !false) {
+    counter = counter + 1;
+    string s = "hello world";
+    float f = 3.14159;
+    if (counter >= 10) { print s; } else { print add(counter, 2); }
+}
+"#;
+    let input = chunk.repeat(20_000);
+
+    let start = std::time::Instant::now();
+    let tokens = Scanner::new(&input).get_tokens().unwrap();
+    let elapsed = start.elapsed();
+
+    println!(
+        "{} tokens from {} bytes in {:?} ({:.1} MB/s)",
+        tokens.len(),
+        input.len(),
+        elapsed,
+        input.len() as f64 / elapsed.as_secs_f64() / 1_000_000.0
+    );
+    assert_eq!(tokens.len(), 20_000 * 82);
+}
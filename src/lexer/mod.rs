@@ -1,3 +1,6 @@
+pub mod lex_error;
+#[cfg(test)]
 mod lexer_test;
+pub mod number;
 pub mod scanner;
 pub mod token;
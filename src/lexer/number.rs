@@ -0,0 +1,104 @@
+#![allow(dead_code)]
+
+use std::{borrow::Cow, num::IntErrorKind};
+
+use super::lex_error::LexErrorKind;
+use crate::types::{token_type::TokenType, token_value::TokenValue};
+
+/// The parts of a numeric literal as split by the scanner.
+#[derive(Debug, Clone, Copy)]
+pub struct NumberLiteral<'a> {
+    /// The whole lexeme, used in error messages.
+    pub literal: &'a str,
+    /// Digits without the radix prefix, may contain `_` separators.
+    pub digits: &'a str,
+    pub radix: u32,
+    /// Set when the digits contain a fraction or an exponent.
+    pub is_float: bool,
+    pub suffix: &'a str,
+}
+
+impl NumberLiteral<'_> {
+    /// Checks the suffix and the range of the literal and computes its value.
+    pub fn parse(&self) -> Result<(TokenType, TokenValue), LexErrorKind> {
+        let digits = if self.digits.contains('_') {
+            Cow::Owned(self.digits.replace('_', ""))
+        } else {
+            Cow::Borrowed(self.digits)
+        };
+
+        if digits.is_empty() {
+            return Err(self.malformed());
+        }
+
+        if self.is_float || matches!(self.suffix, "f" | "f32" | "f64") {
+            self.parse_float(&digits)
+        } else {
+            self.parse_integer(&digits)
+        }
+    }
+
+    fn parse_float(&self, digits: &str) -> Result<(TokenType, TokenValue), LexErrorKind> {
+        if self.radix != 10 {
+            return Err(self.malformed());
+        }
+
+        let value: f64 = digits.parse().map_err(|_| self.malformed())?;
+
+        let value = match self.suffix {
+            "" | "f64" if value.is_finite() => TokenValue::Float(value),
+            "f" | "f32" if (value as f32).is_finite() => TokenValue::Float32(value as f32),
+            "" | "f64" => return Err(self.out_of_range("f64")),
+            "f" | "f32" => return Err(self.out_of_range("f32")),
+            _ => return Err(self.malformed()),
+        };
+        Ok((TokenType::Real, value))
+    }
+
+    fn parse_integer(&self, digits: &str) -> Result<(TokenType, TokenValue), LexErrorKind> {
+        let target = match self.suffix {
+            "" | "i64" => "i64",
+            "i32" => "i32",
+            "u32" => "u32",
+            "u64" => "u64",
+            _ => return Err(self.malformed()),
+        };
+
+        let value = u64::from_str_radix(digits, self.radix).map_err(|error| {
+            if *error.kind() == IntErrorKind::PosOverflow {
+                self.out_of_range(target)
+            } else {
+                self.malformed()
+            }
+        })?;
+
+        let value = match target {
+            "i64" => i64::try_from(value).ok().map(TokenValue::Int),
+            "i32" => i32::try_from(value)
+                .ok()
+                .map(|value| TokenValue::Int(value.into())),
+            "u32" => u32::try_from(value)
+                .ok()
+                .map(|value| TokenValue::UInt(value.into())),
+            _ => Some(TokenValue::UInt(value)),
+        };
+
+        match value {
+            // Every integer is an `int` at runtime, so unsigned literals
+            // must fit in one too.
+            Some(TokenValue::UInt(value)) if i64::try_from(value).is_err() => {
+                Err(self.out_of_range("int"))
+            }
+            Some(value) => Ok((TokenType::Integer, value)),
+            None => Err(self.out_of_range(target)),
+        }
+    }
+
+    fn malformed(&self) -> LexErrorKind {
+        LexErrorKind::MalformedNumber(self.literal.to_owned())
+    }
+
+    fn out_of_range(&self, target: &'static str) -> LexErrorKind {
+        LexErrorKind::NumberOutOfRange(self.literal.to_owned(), target)
+    }
+}
@@ -1,161 +1,567 @@
 #![allow(dead_code)]
 
-use std::collections::HashMap;
+use std::collections::VecDeque;
 
-use crate::types::token_type::TokenType;
+use unicode_ident::{is_xid_continue, is_xid_start};
 
-use super::token::Token;
-use regex::Regex;
+use crate::{
+    source::source_file::{FileId, SourceFile},
+    types::{
+        span::{self, Span},
+        token_type::TokenType,
+        token_value::TokenValue,
+    },
+};
 
-#[derive(Debug, Clone)]
-pub struct Scanner {
-    source: String,
-    reg: Regex,
+use super::{
+    lex_error::{LexError, LexErrorKind},
+    number::NumberLiteral,
+    token::Token,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Scanner<'a> {
+    source: &'a str,
+    file: FileId,
 }
 
+/// Lazy stream of tokens and lexical errors, in source order. Each call to
+/// `next` scans only as far as needed to produce the next item.
 #[derive(Debug, Clone)]
-struct TempState {
-    current_line: i32,
-    ignore_next: bool,
-    pair_elements: HashMap<&'static str, &'static str>,
+pub struct Tokens<'a> {
+    scanner: Scanner<'a>,
+    state: TempState<'a>,
 }
 
-impl TempState {
-    fn new() -> Self {
-        let mut pairs = HashMap::new();
-        pairs.insert("/*", "*/");
-        pairs.insert("//", "\n");
+/// Cursor over the source, one character at a time.
+#[derive(Debug, Clone)]
+struct TempState<'a> {
+    source: &'a str,
+    file: FileId,
+    start: usize,
+    current: usize,
+    current_line: usize,
+    line_start: usize,
+    /// Byte offset on the current line whose column is `counted_column`,
+    /// so columns are counted incrementally instead of from `line_start`.
+    counted_offset: usize,
+    counted_column: usize,
+    start_line: usize,
+    start_column: usize,
+    /// Items produced by the last scanned lexeme, waiting to be yielded.
+    pending: VecDeque<Result<Token<'a>, LexError>>,
+}
 
+impl<'a> TempState<'a> {
+    fn new(source: &'a str, file: FileId) -> Self {
         Self {
-            current_line: 0,
-            ignore_next: false,
-            pair_elements: pairs,
+            source,
+            file,
+            start: 0,
+            current: 0,
+            current_line: 1,
+            line_start: 0,
+            counted_offset: 0,
+            counted_column: 1,
+            start_line: 1,
+            start_column: 1,
+            pending: VecDeque::new(),
+        }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.current >= self.source.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.current..].chars().next()
+    }
+
+    fn peek_next(&self) -> Option<char> {
+        let mut chars = self.source[self.current..].chars();
+        chars.next();
+        chars.next()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.current += c.len_utf8();
+
+        if c == '\n' {
+            self.current_line += 1;
+            self.line_start = self.current;
+            self.counted_offset = self.current;
+            self.counted_column = 1;
+        }
+        Some(c)
+    }
+
+    fn matches(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            true
+        } else {
+            false
         }
     }
+
+    fn matches_str(&mut self, expected: &str) -> bool {
+        if self.source[self.current..].starts_with(expected) {
+            for _ in expected.chars() {
+                self.advance();
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    fn advance_while(&mut self, predicate: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&predicate) {
+            self.advance();
+        }
+    }
+
+    /// Marks the beginning of the next token.
+    fn begin(&mut self) {
+        self.start = self.current;
+        self.start_line = self.current_line;
+        self.start_column = self.column_at(self.current);
+    }
+
+    fn lexeme(&self) -> &'a str {
+        &self.source[self.start..self.current]
+    }
+
+    fn span(&self) -> Span {
+        Span::new(self.start, self.current, self.start_line, self.start_column).in_file(self.file)
+    }
+
+    fn add_token(&mut self, token_type: TokenType) {
+        let token = Token::new(token_type, self.lexeme(), self.span());
+        self.pending.push_back(Ok(token));
+    }
+
+    fn add_token_with_value(&mut self, token_type: TokenType, value: TokenValue) {
+        let token = Token::with_value(token_type, self.lexeme(), value, self.span());
+        self.pending.push_back(Ok(token));
+    }
+
+    /// Column of byte `offset`, which must be on the current line and not
+    /// before an offset asked for earlier.
+    fn column_at(&mut self, offset: usize) -> usize {
+        self.counted_column += span::columns(&self.source[self.counted_offset..offset]);
+        self.counted_offset = offset;
+        self.counted_column
+    }
+
+    /// Span from `start` to the cursor, both on the current line.
+    fn span_from(&mut self, start: usize) -> Span {
+        let column = self.column_at(start);
+        Span::new(start, self.current, self.current_line, column).in_file(self.file)
+    }
+
+    fn add_error(&mut self, kind: LexErrorKind) {
+        self.push_error(LexError::new(kind, self.span()));
+    }
+
+    fn push_error(&mut self, error: LexError) {
+        self.pending.push_back(Err(error));
+    }
 }
 
-impl Scanner {
-    pub fn new(src: &str) -> Self {
-        let expresion = r#"(?:[0-9]*\.?[0-9]+|"(.*?)"|\w+|\(|\)|\{|\}|\[|\]|\-|\+|\*|//|\/|==|<=|>=|\n|\n\r|<|>|!=|!|=|;|\,|\.|\|\||&&|\S)"#;
-        let re = Regex::new(expresion);
+impl<'a> Scanner<'a> {
+    pub fn new(src: &'a str) -> Self {
+        Self {
+            source: src,
+            file: FileId::default(),
+        }
+    }
 
+    /// Scanner whose spans refer to `file`.
+    pub fn for_file(file: &'a SourceFile) -> Self {
         Self {
-            source: src.to_owned(),
-            reg: re.unwrap(),
+            source: &file.text,
+            file: file.id,
         }
     }
 
-    pub fn get_tokens(&self) -> Vec<Token> {
-        let mut state = TempState::new();
+    /// Streams the tokens of the source, borrowing their lexemes from it.
+    pub fn tokens(&self) -> Tokens<'a> {
+        Tokens {
+            scanner: *self,
+            state: TempState::new(self.source, self.file),
+        }
+    }
 
-        let tokens: Vec<Token> = self
-            .reg
-            .captures_iter(&self.source)
-            .filter_map(|element| self.get_token(element, &mut state))
-            .collect();
+    pub fn get_tokens(&self) -> Result<Vec<Token<'a>>, Vec<LexError>> {
+        let (tokens, errors) = self.scan();
 
-        tokens
+        if errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(errors)
+        }
     }
 
-    fn get_token(&self, element: regex::Captures<'_>, state: &mut TempState) -> Option<Token> {
-        let extracted = &element[0];
-        match extracted {
-            "\n\r" | "\n" => {
-                state.current_line += 1;
-                state.ignore_next = false;
-                None
-            }
-            "//" => {
-                state.ignore_next = true;
-                None
+    /// Scans the whole source, skipping over invalid input instead of stopping.
+    pub fn scan(&self) -> (Vec<Token<'a>>, Vec<LexError>) {
+        let mut tokens = vec![];
+        let mut errors = vec![];
+
+        for item in self.tokens() {
+            match item {
+                Ok(token) => tokens.push(token),
+                Err(error) => errors.push(error),
             }
-            _ => {
-                if !state.ignore_next {
-                    self.create_correct_token(extracted, state)
+        }
+
+        (tokens, errors)
+    }
+
+    fn scan_token(&self, state: &mut TempState) {
+        let Some(c) = state.advance() else {
+            return;
+        };
+
+        match c {
+            '(' => state.add_token(TokenType::LeftParen),
+            ')' => state.add_token(TokenType::RightParen),
+            '{' => state.add_token(TokenType::LeftBrace),
+            '}' => state.add_token(TokenType::RightBrace),
+            '[' => state.add_token(TokenType::LeftSquareBracket),
+            ']' => state.add_token(TokenType::RightSquareBracket),
+            ',' => state.add_token(TokenType::Comma),
+            '.' => state.add_token(TokenType::Dot),
+            ';' => state.add_token(TokenType::Semicolon),
+            '?' => state.add_token(TokenType::Question),
+            '^' => state.add_token(TokenType::Caret),
+            '~' => state.add_token(TokenType::Tilde),
+            '%' => state.add_token(TokenType::Percent),
+            '*' => self.operator(
+                state,
+                &[('*', TokenType::StarStar), ('=', TokenType::StarEqual)],
+                TokenType::Star,
+            ),
+            '-' => self.operator(
+                state,
+                &[
+                    ('=', TokenType::MinusEqual),
+                    ('-', TokenType::MinusMinus),
+                    ('>', TokenType::Arrow),
+                ],
+                TokenType::Minus,
+            ),
+            '+' => self.operator(
+                state,
+                &[('=', TokenType::PlusEqual), ('+', TokenType::PlusPlus)],
+                TokenType::Plus,
+            ),
+            '!' => self.operator(state, &[('=', TokenType::BangEqual)], TokenType::Bang),
+            '=' => self.operator(state, &[('=', TokenType::EqualEqual)], TokenType::Equal),
+            '<' => self.operator(
+                state,
+                &[('=', TokenType::LessEqual), ('<', TokenType::LessLess)],
+                TokenType::Less,
+            ),
+            '>' => self.operator(
+                state,
+                &[
+                    ('=', TokenType::GreaterEqual),
+                    ('>', TokenType::GreaterGreater),
+                ],
+                TokenType::Greater,
+            ),
+            '|' => self.operator(state, &[('|', TokenType::Or)], TokenType::Pipe),
+            '&' => self.operator(state, &[('&', TokenType::And)], TokenType::Ampersand),
+            ':' => self.operator(state, &[(':', TokenType::ColonColon)], TokenType::Colon),
+            '/' => {
+                if state.matches('/') {
+                    state.advance_while(|c| c != '\n');
+                } else if state.matches('*') {
+                    self.block_comment(state);
                 } else {
-                    None
+                    self.operator(state, &[('=', TokenType::SlashEqual)], TokenType::Slash);
+                }
+            }
+            '"' => self.string(state),
+            '\'' => self.character(state),
+            'r' if Self::is_raw_string(state) => self.raw_string(state),
+            c if c.is_whitespace() => {}
+            c if c.is_ascii_digit() => self.number(state, c),
+            c if c == '_' || is_xid_start(c) => self.identifier(state),
+            c => state.add_error(LexErrorKind::UnexpectedCharacter(c.to_string())),
+        }
+    }
+
+    /// Lexes the longest operator starting with the character just consumed,
+    /// `choices` lists the possible second characters.
+    fn operator(&self, state: &mut TempState, choices: &[(char, TokenType)], single: TokenType) {
+        let token_type = choices
+            .iter()
+            .find(|(next, _)| state.peek() == Some(*next))
+            .map_or(single, |(_, token_type)| *token_type);
+
+        if token_type != single {
+            state.advance();
+        }
+        state.add_token(token_type);
+    }
+
+    /// Block comments nest, so `/* a /* b */ c */` is a single comment.
+    fn block_comment(&self, state: &mut TempState) {
+        let opening = state.span();
+        let mut depth = 1;
+
+        while depth > 0 {
+            match state.advance() {
+                Some('/') if state.matches('*') => depth += 1,
+                Some('*') if state.matches('/') => depth -= 1,
+                Some(_) => {}
+                None => {
+                    state.push_error(LexError::new(LexErrorKind::UnterminatedComment, opening));
+                    return;
                 }
             }
         }
     }
 
-    fn create_correct_token(&self, element: &str, state: &TempState) -> Option<Token> {
-        let literal = element.to_string();
-        let line = state.current_line;
-
-        let token = match element {
-            "=" => Token::new(TokenType::Equal, literal, line),
-            "==" => Token::new(TokenType::EqualEqual, literal, line),
-            ">" => Token::new(TokenType::Greater, literal, line),
-            ">=" => Token::new(TokenType::GreaterEqual, literal, line),
-            "<=" => Token::new(TokenType::LessEqual, literal, line),
-            "<" => Token::new(TokenType::Less, literal, line),
-            "!=" => Token::new(TokenType::BangEqual, literal, line),
-            "!" => Token::new(TokenType::Bang, literal, line),
-            ";" => Token::new(TokenType::Semicolon, literal, line),
-            ")" => Token::new(TokenType::RightParen, literal, line),
-            "(" => Token::new(TokenType::LeftParen, literal, line),
-            "}" => Token::new(TokenType::RightBrace, literal, line),
-            "{" => Token::new(TokenType::LeftBrace, literal, line),
-            "]" => Token::new(TokenType::RightSquareBracket, literal, line),
-            "[" => Token::new(TokenType::LeftSquareBracket, literal, line),
-            "+" => Token::new(TokenType::Plus, literal, line),
-            "-" => Token::new(TokenType::Minus, literal, line),
-            "/" => Token::new(TokenType::Slash, literal, line),
-            "*" => Token::new(TokenType::Star, literal, line),
-            "." => Token::new(TokenType::Dot, literal, line),
-            "," => Token::new(TokenType::Comma, literal, line),
-            "||" => Token::new(TokenType::Or, literal, line),
-            "&&" => Token::new(TokenType::And, literal, line),
-            "true" => Token::new(TokenType::True, literal, line),
-            "false" => Token::new(TokenType::False, literal, line),
-            "int" => Token::new(TokenType::Int, literal, line),
-            "float" => Token::new(TokenType::Float, literal, line),
-            "string" => Token::new(TokenType::String, literal, line),
-            "bool" => Token::new(TokenType::Boolean, literal, line),
-            "if" => Token::new(TokenType::If, literal, line),
-            "else" => Token::new(TokenType::Else, literal, line),
-            "for" => Token::new(TokenType::For, literal, line),
-            "while" => Token::new(TokenType::While, literal, line),
-            "fun" => Token::new(TokenType::Fun, literal, line),
-            "return" => Token::new(TokenType::Return, literal, line),
-            "print" => Token::new(TokenType::Print, literal, line),
-            "nil" => Token::new(TokenType::Nil, literal, line),
-            _ => self.create_variable_token(element, line),
+    /// Plain strings end on the same line, an unclosed one skips the rest of
+    /// the line. Triple-quoted strings may span lines.
+    fn string(&self, state: &mut TempState) {
+        let closing = if state.matches_str("\"\"") {
+            "\"\"\""
+        } else {
+            "\""
         };
-        Some(token)
+        let opening = state.span();
+        let (value, valid) = self.quoted(state, closing, closing.len() > 1);
+
+        if !state.matches_str(closing) {
+            state.push_error(LexError::new(LexErrorKind::UnterminatedString, opening));
+        } else if valid {
+            state.add_token_with_value(TokenType::Str, TokenValue::String(value));
+        }
+    }
+
+    /// `r"..."`, or `r#"..."#` with any number of `#`, taken verbatim.
+    fn is_raw_string(state: &TempState) -> bool {
+        state.source[state.current..]
+            .trim_start_matches('#')
+            .starts_with('"')
+    }
+
+    fn raw_string(&self, state: &mut TempState) {
+        let mut closing = String::from("\"");
+        while state.matches('#') {
+            closing.push('#');
+        }
+        state.advance();
+
+        let opening = state.span();
+        let body = state.current;
+        while !state.is_at_end() && !state.source[state.current..].starts_with(&closing) {
+            state.advance();
+        }
+        let value = state.source[body..state.current].to_owned();
+
+        if state.matches_str(&closing) {
+            state.add_token_with_value(TokenType::Str, TokenValue::String(value));
+        } else {
+            state.push_error(LexError::new(LexErrorKind::UnterminatedString, opening));
+        }
     }
 
-    fn create_variable_token(&self, element: &str, line: i32) -> Token {
-        let literal = element.to_string();
+    fn character(&self, state: &mut TempState) {
+        let opening = state.span();
+        let (value, valid) = self.quoted(state, "'", false);
 
-        if self.is_string_literal(element) {
-            Token::new(TokenType::Str, literal, line)
-        } else if self.is_number(element) {
-            if element.contains(".") {
-                Token::new(TokenType::Real, literal, line)
-            } else {
-                Token::new(TokenType::Integer, literal, line)
+        if !state.matches('\'') {
+            state.push_error(LexError::new(LexErrorKind::UnterminatedCharacter, opening));
+            return;
+        }
+
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if valid => {
+                state.add_token_with_value(TokenType::Character, TokenValue::Char(c))
+            }
+            _ if valid => {
+                let literal = state.lexeme().to_owned();
+                state.add_error(LexErrorKind::InvalidCharacterLiteral(literal));
             }
-        } else if self.is_idetifier(element) {
-            Token::new(TokenType::Identifier, literal, line)
+            _ => {}
+        }
+    }
+
+    /// Decodes the body of a quoted literal up to, but not including, the
+    /// closing quote, or the end of the line unless `multiline` is set.
+    /// Returns false when an escape sequence was rejected.
+    fn quoted(&self, state: &mut TempState, closing: &str, multiline: bool) -> (String, bool) {
+        let mut value = String::new();
+        let mut valid = true;
+
+        while let Some(c) = state.peek() {
+            if state.source[state.current..].starts_with(closing) || (c == '\n' && !multiline) {
+                break;
+            }
+
+            let start = state.current;
+            state.advance();
+
+            if c != '\\' {
+                value.push(c);
+                continue;
+            }
+
+            match self.escape(state) {
+                Some(c) => value.push(c),
+                None => {
+                    valid = false;
+                    let sequence = state.source[start..state.current].to_owned();
+                    let error = LexError::new(
+                        LexErrorKind::InvalidEscape(sequence),
+                        state.span_from(start),
+                    );
+                    state.push_error(error);
+                }
+            }
+        }
+
+        (value, valid)
+    }
+
+    /// Reads the escape sequence following a backslash.
+    fn escape(&self, state: &mut TempState) -> Option<char> {
+        let c = match state.peek() {
+            Some('\n') | None => return None,
+            Some(c) => c,
+        };
+        state.advance();
+
+        match c {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '\\' | '"' | '\'' => Some(c),
+            'u' => self.unicode_escape(state),
+            _ => None,
+        }
+    }
+
+    /// Reads the `{...}` part of a `\u{...}` escape, one to six hex digits.
+    fn unicode_escape(&self, state: &mut TempState) -> Option<char> {
+        if !state.matches('{') {
+            return None;
+        }
+
+        let start = state.current;
+        state.advance_while(|c| c.is_ascii_hexdigit());
+        let digits = &state.source[start..state.current];
+
+        if !state.matches('}') || digits.is_empty() || digits.len() > 6 {
+            return None;
+        }
+
+        u32::from_str_radix(digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+    }
+
+    /// Numbers are `0x`, `0b` or `0o` prefixed integers, or decimals with an
+    /// optional fraction and exponent. Digits may be separated by `_` and a
+    /// type suffix such as `i64` or `f` may follow.
+    fn number(&self, state: &mut TempState, first: char) {
+        let radix = match (first, state.peek()) {
+            ('0', Some('x' | 'X')) => 16,
+            ('0', Some('o' | 'O')) => 8,
+            ('0', Some('b' | 'B')) => 2,
+            _ => 10,
+        };
+
+        let digits_start = if radix == 10 {
+            state.start
+        } else {
+            state.advance();
+            state.current
+        };
+
+        if radix != 10 {
+            state.advance_while(|c| c.is_ascii_hexdigit() || c == '_');
         } else {
-            Token::new(TokenType::Unknown, literal, line)
+            let is_digit = |c: char| c.is_ascii_digit() || c == '_';
+            state.advance_while(is_digit);
+
+            if state.peek() == Some('.') && state.peek_next().is_some_and(|c| c.is_ascii_digit()) {
+                state.advance();
+                state.advance_while(is_digit);
+            }
+
+            if Self::exponent_follows(state) {
+                state.advance();
+                if !state.matches('+') {
+                    state.matches('-');
+                }
+                state.advance_while(is_digit);
+            }
+        }
+
+        let digits = &state.source[digits_start..state.current];
+        let suffix_start = state.current;
+        state.advance_while(is_xid_continue);
+
+        let number = NumberLiteral {
+            literal: state.lexeme(),
+            digits,
+            radix,
+            is_float: radix == 10 && digits.contains(['.', 'e', 'E']),
+            suffix: &state.source[suffix_start..state.current],
+        };
+
+        match number.parse() {
+            Ok((token_type, value)) => state.add_token_with_value(token_type, value),
+            Err(kind) => state.add_error(kind),
         }
     }
 
-    fn is_string_literal(&self, element: &str) -> bool {
-        element.starts_with("\"") && element.ends_with("\"") && element.len() > 1
+    /// `e` or `E`, an optional sign, then at least one digit.
+    fn exponent_follows(state: &TempState) -> bool {
+        let mut chars = state.source[state.current..].chars();
+
+        if !matches!(chars.next(), Some('e' | 'E')) {
+            return false;
+        }
+
+        match chars.next() {
+            Some('+' | '-') => chars.next().is_some_and(|c| c.is_ascii_digit()),
+            next => next.is_some_and(|c| c.is_ascii_digit()),
+        }
     }
 
-    fn is_number(&self, element: &str) -> bool {
-        element.chars().next().is_some_and(|c| c.is_ascii_digit())
+    fn identifier(&self, state: &mut TempState) {
+        state.advance_while(is_xid_continue);
+        let word = state.lexeme();
+
+        let token_type = TokenType::keyword(word).unwrap_or(TokenType::Identifier);
+        state.add_token(token_type);
     }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Result<Token<'a>, LexError>;
 
-    fn is_idetifier(&self, element: &str) -> bool {
-        element
-            .chars()
-            .next()
-            .is_some_and(|c| c.is_ascii_alphabetic())
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.state.pending.pop_front() {
+                return Some(item);
+            }
+            if self.state.is_at_end() {
+                return None;
+            }
+
+            self.state.begin();
+            self.scanner.scan_token(&mut self.state);
+        }
     }
 }
@@ -1,52 +1,70 @@
 #![allow(dead_code)]
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
-use crate::types::{token_type::TokenType, token_value::TokenValue};
+use crate::types::{span::Span, token_type::TokenType, token_value::TokenValue};
 
 #[derive(Debug, Clone)]
-pub struct Token {
+pub struct Token<'a> {
     pub token_type: TokenType,
-    pub literal: String,
+    /// The lexeme, borrowed from the scanned source until the token is
+    /// turned into an owned one.
+    pub literal: Cow<'a, str>,
     pub value: TokenValue,
-    pub line: i32,
+    pub span: Span,
 }
 
-impl Token {
-    pub fn new(token_type: TokenType, literal: String, line: i32) -> Self {
+impl<'a> Token<'a> {
+    pub fn new(token_type: TokenType, literal: &'a str, span: Span) -> Self {
         Self {
             token_type,
-            literal: literal.clone(),
-            value: Self::get_token_value(token_type, &literal),
-            line,
+            literal: Cow::Borrowed(literal),
+            value: Self::get_token_value(token_type, literal),
+            span,
         }
     }
 
+    /// Builds a token whose value was already decoded by the scanner.
+    pub fn with_value(
+        token_type: TokenType,
+        literal: &'a str,
+        value: TokenValue,
+        span: Span,
+    ) -> Self {
+        Self {
+            token_type,
+            literal: Cow::Borrowed(literal),
+            value,
+            span,
+        }
+    }
+
+    /// Copies the lexeme so the token can outlive the source.
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            token_type: self.token_type,
+            literal: Cow::Owned(self.literal.into_owned()),
+            value: self.value,
+            span: self.span,
+        }
+    }
+
+    /// Values that follow from the token type alone, literals are decoded
+    /// by the scanner and built with `with_value`.
     fn get_token_value(token_type: TokenType, literal: &str) -> TokenValue {
-        if token_type == TokenType::True && literal == "true" {
-            TokenValue::Bool(true)
-        } else if token_type == TokenType::False && literal == "false" {
-            TokenValue::Bool(false)
-        } else if token_type == TokenType::Str
-            && literal.starts_with("\"")
-            && literal.ends_with("\"")
-        {
-            TokenValue::String(literal.trim_matches('\"').to_owned())
-        } else if token_type == TokenType::Real {
-            TokenValue::Float(literal.parse().unwrap_or_default())
-        } else if token_type == TokenType::Integer {
-            TokenValue::Int(literal.parse().unwrap_or_default())
-        } else {
-            TokenValue::None
+        match (token_type, literal) {
+            (TokenType::True, "true") => TokenValue::Bool(true),
+            (TokenType::False, "false") => TokenValue::Bool(false),
+            _ => TokenValue::None,
         }
     }
 }
 
-impl fmt::Display for Token {
+impl fmt::Display for Token<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "{} {} {} {}",
-            self.line, self.token_type, self.literal, self.value
+            self.span, self.token_type, self.literal, self.value
         )
     }
 }
@@ -1,25 +1,28 @@
-use lexer::scanner::Scanner;
-
 mod ast;
+mod bytecode;
+mod codegen;
+mod diagnostics;
+mod driver;
+mod interpreter;
 mod lexer;
+mod semantic;
+mod source;
+#[cfg(test)]
+mod test_support;
 mod types;
 
-fn main() {
-    let input = r#"
-        int x = 10;
-        float y = 3.14f;
-        boolean z = true;
-        string s = "hello";
-
-        void myMethod() {
-            print('Hello, world!');
-        }
-    "#;
+/// Stack of the thread running the compiler. The tree-walking interpreter
+/// recurses for every Lox call, so reaching its call depth limit takes more
+/// than the default main thread stack in unoptimized builds.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
 
-    let scanner = Scanner::new(input);
-    let tokens = scanner.get_tokens();
-
-    for token in tokens.iter() {
-        println!("{}", token);
-    }
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let code = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || driver::cli::run(&args))
+        .expect("the compiler thread starts")
+        .join()
+        .unwrap_or(101);
+    std::process::exit(code);
 }
@@ -0,0 +1,10 @@
+pub mod resolution;
+pub mod resolve_error;
+pub mod resolver;
+#[cfg(test)]
+mod resolver_test;
+pub mod static_type;
+pub mod type_checker;
+#[cfg(test)]
+mod type_checker_test;
+pub mod type_error;
@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+
+use crate::types::span::Span;
+
+/// Where the variable used at a span lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Local {
+        /// Number of scopes between the use and the declaration, 0 for the
+        /// innermost one. Function bodies share the scope of their parameters.
+        depth: usize,
+        /// Index of the declaration among the locals of its scope, in
+        /// declaration order starting with the parameters.
+        slot: usize,
+    },
+    /// Looked up by name among the globals.
+    Global,
+}
+
+/// Output of the resolver, keyed by the span of each variable use.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Resolution {
+    uses: HashMap<Span, Binding>,
+    /// Slot of each local declaration, keyed by the span of its name.
+    declarations: HashMap<Span, usize>,
+    /// Spans of the assignments and ternaries typed as `float` by the type
+    /// checker, whose integer values are widened.
+    widened: HashSet<Span>,
+}
+
+impl Resolution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind(&mut self, use_span: Span, binding: Binding) {
+        self.uses.insert(use_span, binding);
+    }
+
+    pub fn declare(&mut self, declaration_span: Span, slot: usize) {
+        self.declarations.insert(declaration_span, slot);
+    }
+
+    /// Binding of the variable used at `span`, `None` for code that was not
+    /// resolved.
+    pub fn get(&self, span: Span) -> Option<Binding> {
+        self.uses.get(&span).copied()
+    }
+
+    /// Slot of the local declared at `span`, `None` for globals.
+    pub fn slot(&self, span: Span) -> Option<usize> {
+        self.declarations.get(&span).copied()
+    }
+
+    /// Marks the expressions at `spans` as widening integers to `float`.
+    pub fn widen(&mut self, spans: impl IntoIterator<Item = Span>) {
+        self.widened.extend(spans);
+    }
+
+    pub fn widens(&self, span: Span) -> bool {
+        self.widened.contains(&span)
+    }
+
+    /// Adds the bindings of another resolution, such as a later REPL input.
+    pub fn extend(&mut self, other: Resolution) {
+        self.uses.extend(other.uses);
+        self.declarations.extend(other.declarations);
+        self.widened.extend(other.widened);
+    }
+}
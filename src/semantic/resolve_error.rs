@@ -0,0 +1,57 @@
+#![allow(dead_code)]
+
+use std::{error::Error, fmt};
+
+use crate::types::span::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveErrorKind {
+    UndeclaredVariable(String),
+    /// A second declaration of the name in the same local scope.
+    DuplicateDeclaration(String),
+    /// A local read in its own initializer.
+    UseBeforeInitialization(String),
+    /// Only a warning, the declaration hides one from an enclosing scope.
+    ShadowedVariable(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveError {
+    pub kind: ResolveErrorKind,
+    pub span: Span,
+}
+
+impl ResolveError {
+    pub fn new(kind: ResolveErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    pub fn is_warning(&self) -> bool {
+        matches!(self.kind, ResolveErrorKind::ShadowedVariable(_))
+    }
+
+    pub fn message(&self) -> String {
+        match &self.kind {
+            ResolveErrorKind::UndeclaredVariable(name) => {
+                format!("undeclared variable '{}'", name)
+            }
+            ResolveErrorKind::DuplicateDeclaration(name) => {
+                format!("'{}' is already declared in this scope", name)
+            }
+            ResolveErrorKind::UseBeforeInitialization(name) => {
+                format!("'{}' is used in its own initializer", name)
+            }
+            ResolveErrorKind::ShadowedVariable(name) => {
+                format!("declaration of '{}' shadows an outer variable", name)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}.", self.span, self.message())
+    }
+}
+
+impl Error for ResolveError {}
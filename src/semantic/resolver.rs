@@ -0,0 +1,295 @@
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    resolution::{Binding, Resolution},
+    resolve_error::{ResolveError, ResolveErrorKind},
+};
+use crate::{ast::tree_types::*, types::span::Span};
+
+/// Binds every variable use to its declaration. Scopes mirror the
+/// interpreter's environments: blocks, `for` statements and function calls.
+#[derive(Debug, Clone, Default)]
+pub struct Resolver {
+    /// Globals declared so far, kept between calls for the REPL.
+    globals: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Local {
+    slot: usize,
+    /// False while the initializer of the declaration is resolved.
+    initialized: bool,
+}
+
+#[derive(Debug, Clone)]
+struct TempState {
+    scopes: Vec<HashMap<String, Local>>,
+    /// Globals declared anywhere at the top level, function bodies run
+    /// later and may use those declared after them.
+    hoisted: HashSet<String>,
+    function_depth: usize,
+    resolution: Resolution,
+    errors: Vec<ResolveError>,
+}
+
+impl TempState {
+    fn new(hoisted: HashSet<String>) -> Self {
+        Self {
+            scopes: vec![],
+            hoisted,
+            function_depth: 0,
+            resolution: Resolution::new(),
+            errors: vec![],
+        }
+    }
+
+    fn error(&mut self, kind: ResolveErrorKind, span: Span) {
+        self.errors.push(ResolveError::new(kind, span));
+    }
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self {
+            globals: HashSet::new(),
+        }
+    }
+
+    /// Resolves a program, returning the bindings along with the errors and
+    /// warnings found. The bindings are only complete when there are no
+    /// errors.
+    pub fn resolve(&mut self, statements: &[Statement]) -> (Resolution, Vec<ResolveError>) {
+        let hoisted = statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Variable { name, .. } | Statement::Function { name, .. } => {
+                    Some(name.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        let mut state = TempState::new(hoisted);
+
+        for statement in statements {
+            self.statement(&mut state, statement);
+        }
+
+        (state.resolution, state.errors)
+    }
+
+    fn statement(&mut self, state: &mut TempState, statement: &Statement) {
+        match statement {
+            Statement::Expression(expression, _) | Statement::Print(expression, _) => {
+                self.expression(state, expression);
+            }
+            Statement::Variable {
+                name,
+                initializer,
+                span,
+                ..
+            } => {
+                self.declare(state, name, *span);
+                if let Some(initializer) = initializer {
+                    self.expression(state, initializer);
+                }
+                self.define(state, name);
+            }
+            Statement::Block(statements, _) => {
+                state.scopes.push(HashMap::new());
+                for statement in statements {
+                    self.statement(state, statement);
+                }
+                state.scopes.pop();
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.expression(state, condition);
+                self.statement(state, then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.statement(state, else_branch);
+                }
+            }
+            Statement::While {
+                condition, body, ..
+            } => {
+                self.expression(state, condition);
+                self.statement(state, body);
+            }
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                state.scopes.push(HashMap::new());
+                if let Some(initializer) = initializer {
+                    self.statement(state, initializer);
+                }
+                for expression in condition.iter().chain(increment) {
+                    self.expression(state, expression);
+                }
+                self.statement(state, body);
+                state.scopes.pop();
+            }
+            Statement::Return(value, _) => {
+                if let Some(value) = value {
+                    self.expression(state, value);
+                }
+            }
+            Statement::Break(_) | Statement::Continue(_) => {}
+            Statement::Function {
+                name,
+                parameters,
+                body,
+                span,
+                ..
+            } => {
+                // Defined before the body so it can call itself.
+                self.declare(state, name, *span);
+                self.define(state, name);
+                self.function(state, parameters, body);
+            }
+        }
+    }
+
+    fn function(&mut self, state: &mut TempState, parameters: &[Parameter], body: &[Statement]) {
+        state.scopes.push(HashMap::new());
+        state.function_depth += 1;
+
+        for parameter in parameters {
+            self.declare(state, &parameter.name, parameter.span);
+            self.define(state, &parameter.name);
+        }
+        for statement in body {
+            self.statement(state, statement);
+        }
+
+        state.function_depth -= 1;
+        state.scopes.pop();
+    }
+
+    fn declare(&mut self, state: &mut TempState, name: &str, span: Span) {
+        let shadows = state
+            .scopes
+            .iter()
+            .rev()
+            .skip(1)
+            .any(|s| s.contains_key(name))
+            || self.globals.contains(name)
+            || state.hoisted.contains(name);
+
+        let Some(scope) = state.scopes.last_mut() else {
+            // Globals may be declared again, like redefinitions in the REPL.
+            return;
+        };
+
+        if scope.contains_key(name) {
+            state.error(
+                ResolveErrorKind::DuplicateDeclaration(name.to_owned()),
+                span,
+            );
+            return;
+        }
+
+        let slot = scope.len();
+        scope.insert(
+            name.to_owned(),
+            Local {
+                slot,
+                initialized: false,
+            },
+        );
+        state.resolution.declare(span, slot);
+
+        if shadows {
+            state.error(ResolveErrorKind::ShadowedVariable(name.to_owned()), span);
+        }
+    }
+
+    fn define(&mut self, state: &mut TempState, name: &str) {
+        match state.scopes.last_mut() {
+            Some(scope) => {
+                if let Some(local) = scope.get_mut(name) {
+                    local.initialized = true;
+                }
+            }
+            None => {
+                self.globals.insert(name.to_owned());
+            }
+        }
+    }
+
+    /// Binds a use of `name` to the innermost scope declaring it.
+    fn bind(&self, state: &mut TempState, name: &str, span: Span) {
+        let found = state
+            .scopes
+            .iter()
+            .rev()
+            .enumerate()
+            .find_map(|(depth, scope)| scope.get(name).map(|local| (depth, *local)));
+
+        match found {
+            Some((_, local)) if !local.initialized => {
+                let kind = ResolveErrorKind::UseBeforeInitialization(name.to_owned());
+                state.error(kind, span);
+            }
+            Some((depth, local)) => {
+                let binding = Binding::Local {
+                    depth,
+                    slot: local.slot,
+                };
+                state.resolution.bind(span, binding);
+            }
+            None if self.globals.contains(name)
+                || state.function_depth > 0 && state.hoisted.contains(name) =>
+            {
+                state.resolution.bind(span, Binding::Global);
+            }
+            None => state.error(ResolveErrorKind::UndeclaredVariable(name.to_owned()), span),
+        }
+    }
+
+    fn expression(&self, state: &mut TempState, expression: &Expresion) {
+        match expression {
+            Expresion::Variable(name, span) | Expresion::Postfix(name, _, span) => {
+                self.bind(state, name, *span)
+            }
+            Expresion::Assign(name, value, span) => {
+                self.expression(state, value);
+                self.bind(state, name, *span);
+            }
+            Expresion::Grouping(expression, _) | Expresion::Unary(_, expression, _) => {
+                self.expression(state, expression)
+            }
+            Expresion::Binary(left, _, right, _) => {
+                self.expression(state, left);
+                self.expression(state, right);
+            }
+            Expresion::Ternary(condition, then_branch, else_branch, _) => {
+                self.expression(state, condition);
+                self.expression(state, then_branch);
+                self.expression(state, else_branch);
+            }
+            Expresion::Call(callee, arguments, _) => {
+                self.expression(state, callee);
+                for argument in arguments {
+                    self.expression(state, argument);
+                }
+            }
+            Expresion::Unknown(_, _)
+            | Expresion::Integer(_, _)
+            | Expresion::Float(_, _)
+            | Expresion::Str(_, _)
+            | Expresion::Char(_, _)
+            | Expresion::Bool(_, _)
+            | Expresion::Nil(_) => {}
+        }
+    }
+}
@@ -0,0 +1,164 @@
+#![allow(unused_imports, dead_code)]
+
+use super::{
+    resolution::{Binding, Resolution},
+    resolve_error::{ResolveError, ResolveErrorKind},
+    resolver::Resolver,
+};
+use crate::{
+    ast::tree_types::Statement, interpreter::evaluator::Interpreter, test_support::parse,
+    types::span::Span,
+};
+
+fn resolve(input: &str) -> (Resolution, Vec<ResolveError>) {
+    Resolver::new().resolve(&parse(input))
+}
+
+fn kinds(input: &str) -> Vec<ResolveErrorKind> {
+    resolve(input).1.into_iter().map(|e| e.kind).collect()
+}
+
+/// Span of the `nth` occurrence of identifier `word` in single line `input`.
+fn find(input: &str, word: &str, nth: usize) -> Span {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let start = input
+        .match_indices(word)
+        .map(|(start, _)| start)
+        .filter(|start| {
+            !is_word(input[..*start].chars().next_back())
+                && !is_word(input[start + word.len()..].chars().next())
+        })
+        .nth(nth)
+        .unwrap();
+    Span::new(start, start + word.len(), 1, start + 1)
+}
+
+#[test]
+fn binds_depth_and_slot() {
+    let input = "fun f(a, b) { var c = a; { var d = b + c; print d; } } var g = 1; print g;";
+    let (resolution, errors) = resolve(input);
+
+    assert!(errors.is_empty());
+    assert_eq!(
+        resolution.get(find(input, "a", 1)),
+        Some(Binding::Local { depth: 0, slot: 0 })
+    );
+    assert_eq!(
+        resolution.get(find(input, "b", 1)),
+        Some(Binding::Local { depth: 1, slot: 1 })
+    );
+    assert_eq!(
+        resolution.get(find(input, "c", 1)),
+        Some(Binding::Local { depth: 1, slot: 2 })
+    );
+    assert_eq!(
+        resolution.get(find(input, "d", 1)),
+        Some(Binding::Local { depth: 0, slot: 0 })
+    );
+    assert_eq!(resolution.get(find(input, "g", 1)), Some(Binding::Global));
+
+    assert_eq!(resolution.slot(find(input, "c", 0)), Some(2));
+    assert_eq!(resolution.slot(find(input, "g", 0)), None);
+}
+
+#[test]
+fn for_loops_have_their_own_scope() {
+    let input = "for (var i = 0; i < 3; i++) { print i; }";
+    let (resolution, errors) = resolve(input);
+
+    assert!(errors.is_empty());
+    assert_eq!(
+        resolution.get(find(input, "i", 1)),
+        Some(Binding::Local { depth: 0, slot: 0 })
+    );
+    assert_eq!(
+        resolution.get(find(input, "i", 3)),
+        Some(Binding::Local { depth: 1, slot: 0 })
+    );
+}
+
+#[test]
+fn undeclared_variables() {
+    let errors = resolve("var a = 1;\nprint a + b;").1;
+
+    assert_eq!(
+        errors,
+        vec![ResolveError::new(
+            ResolveErrorKind::UndeclaredVariable("b".to_owned()),
+            Span::new(21, 22, 2, 11),
+        )]
+    );
+
+    // Functions may use globals declared after them, they run later.
+    assert_eq!(kinds("fun f() { return later; } var later = 1;"), vec![]);
+    assert_eq!(
+        kinds("print early; var early = 1;"),
+        vec![ResolveErrorKind::UndeclaredVariable("early".to_owned())]
+    );
+}
+
+#[test]
+fn duplicate_declarations() {
+    assert_eq!(
+        kinds("{ var a = 1; int a = 2; } fun f(x, x) { } var g; var g;"),
+        vec![
+            ResolveErrorKind::DuplicateDeclaration("a".to_owned()),
+            ResolveErrorKind::DuplicateDeclaration("x".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn use_before_initialization() {
+    assert_eq!(
+        kinds("{ var a = 1; { var b = b; } }"),
+        vec![ResolveErrorKind::UseBeforeInitialization("b".to_owned())]
+    );
+}
+
+#[test]
+fn shadowing_warnings() {
+    let errors = resolve("var x = 1; fun f(x) { } { var y; { var y; } }").1;
+
+    assert_eq!(
+        errors.iter().map(|e| e.kind.clone()).collect::<Vec<_>>(),
+        vec![
+            ResolveErrorKind::ShadowedVariable("x".to_owned()),
+            ResolveErrorKind::ShadowedVariable("y".to_owned()),
+        ]
+    );
+    assert!(errors.iter().all(ResolveError::is_warning));
+}
+
+#[test]
+fn globals_persist_between_calls() {
+    let mut resolver = Resolver::new();
+
+    assert!(resolver.resolve(&parse("var a = 1;")).1.is_empty());
+    assert!(resolver.resolve(&parse("print a;")).1.is_empty());
+}
+
+#[test]
+fn interpreter_uses_static_scope() {
+    let statements = parse(
+        r#"
+        var a = "global";
+        {
+            fun show() { print a; }
+            show();
+            var a = "block";
+            show();
+        }
+        "#,
+    );
+    let (resolution, _) = Resolver::new().resolve(&statements);
+
+    let mut interpreter = Interpreter::new(Vec::new());
+    interpreter.resolve(resolution);
+    interpreter.interpret(&statements).unwrap();
+
+    assert_eq!(
+        String::from_utf8(interpreter.into_output()).unwrap(),
+        "global\nglobal\n"
+    );
+}
@@ -0,0 +1,91 @@
+#![allow(dead_code)]
+
+use std::{fmt, rc::Rc};
+
+use crate::ast::tree_types::DataType;
+
+/// Type of an expression as far as it is known before running.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StaticType {
+    Int,
+    Float,
+    String,
+    Char,
+    Bool,
+    Nil,
+    /// Result of calling a function declared `void`.
+    Void,
+    Function(Rc<Signature>),
+    /// Untyped parameters and `var` declarations without a typed
+    /// initializer, checked only at runtime.
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub parameters: Vec<StaticType>,
+    pub return_type: StaticType,
+}
+
+impl StaticType {
+    /// Whether a value of type `value` can be stored where `self` is
+    /// declared. Integers widen to floats like they do at runtime.
+    pub fn accepts(&self, value: &StaticType) -> bool {
+        match (self, value) {
+            (StaticType::Any, _) | (_, StaticType::Any) => true,
+            (StaticType::Float, StaticType::Int) => true,
+            (declared, value) => declared == value,
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, StaticType::Int | StaticType::Float)
+    }
+}
+
+impl From<DataType> for StaticType {
+    fn from(data_type: DataType) -> Self {
+        match data_type {
+            DataType::Int => StaticType::Int,
+            DataType::Float => StaticType::Float,
+            DataType::String => StaticType::String,
+            DataType::Char => StaticType::Char,
+            DataType::Bool => StaticType::Bool,
+            DataType::Void => StaticType::Void,
+        }
+    }
+}
+
+impl From<Option<DataType>> for StaticType {
+    fn from(data_type: Option<DataType>) -> Self {
+        data_type.map_or(StaticType::Any, StaticType::from)
+    }
+}
+
+impl fmt::Display for StaticType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StaticType::Int => write!(f, "int"),
+            StaticType::Float => write!(f, "float"),
+            StaticType::String => write!(f, "string"),
+            StaticType::Char => write!(f, "char"),
+            StaticType::Bool => write!(f, "boolean"),
+            StaticType::Nil => write!(f, "nil"),
+            StaticType::Void => write!(f, "void"),
+            StaticType::Any => write!(f, "any"),
+            StaticType::Function(signature) => {
+                let parameters = signature
+                    .parameters
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "fun({}) -> {}",
+                    parameters.join(", "),
+                    signature.return_type
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,419 @@
+#![allow(dead_code)]
+
+use std::{collections::HashMap, rc::Rc};
+
+use super::{
+    static_type::{Signature, StaticType},
+    type_error::{TypeError, TypeErrorKind},
+};
+use crate::{ast::tree_types::*, types::span::Span};
+
+/// Checks typed declarations before a program runs. Names it does not know
+/// about are left to the interpreter and typed as `any`.
+///
+/// A `var` initialized with a value of known type is inferred to have that
+/// type for good, so `var x = 1; x = 2.5;` is rejected like `int x` would
+/// be. Only a `var` declared without a value or with `nil` stays untyped.
+#[derive(Debug, Clone, Default)]
+pub struct TypeChecker {
+    /// Global declarations, kept between calls like the interpreter's.
+    globals: HashMap<String, StaticType>,
+}
+
+#[derive(Debug, Clone)]
+struct TempState {
+    scopes: Vec<HashMap<String, StaticType>>,
+    /// Return types of the functions around the current statement.
+    functions: Vec<StaticType>,
+    /// Span of the closest statement, for expressions without one.
+    location: Span,
+    /// Assignments and ternaries typed as `float` whose value may be an
+    /// integer.
+    widened: Vec<Span>,
+    errors: Vec<TypeError>,
+}
+
+impl TempState {
+    fn new() -> Self {
+        Self {
+            scopes: vec![],
+            functions: vec![],
+            location: Span::default(),
+            widened: vec![],
+            errors: vec![],
+        }
+    }
+
+    fn error(&mut self, kind: TypeErrorKind, span: Span) {
+        self.errors.push(TypeError::new(kind, span));
+    }
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        Self {
+            globals: HashMap::new(),
+        }
+    }
+
+    /// Returns the spans of the assignments and ternaries whose integer
+    /// values the interpreters widen to `float`, see `Resolution::widen`.
+    pub fn check(&mut self, statements: &[Statement]) -> Result<Vec<Span>, Vec<TypeError>> {
+        let mut state = TempState::new();
+
+        for statement in statements {
+            self.statement(&mut state, statement);
+        }
+
+        if state.errors.is_empty() {
+            Ok(state.widened)
+        } else {
+            Err(state.errors)
+        }
+    }
+
+    fn declare(&mut self, state: &mut TempState, name: &str, static_type: StaticType) {
+        match state.scopes.last_mut() {
+            Some(scope) => scope.insert(name.to_owned(), static_type),
+            None => self.globals.insert(name.to_owned(), static_type),
+        };
+    }
+
+    fn lookup(&self, state: &TempState, name: &str) -> StaticType {
+        state
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .or_else(|| self.globals.get(name))
+            .cloned()
+            .unwrap_or(StaticType::Any)
+    }
+
+    fn scoped(&mut self, state: &mut TempState, statements: &[Statement]) {
+        state.scopes.push(HashMap::new());
+        for statement in statements {
+            self.statement(state, statement);
+        }
+        state.scopes.pop();
+    }
+
+    fn statement(&mut self, state: &mut TempState, statement: &Statement) {
+        match statement {
+            Statement::Expression(expression, _) | Statement::Print(expression, _) => {
+                self.expression(state, expression);
+            }
+            Statement::Variable {
+                data_type,
+                name,
+                initializer,
+                span,
+            } => {
+                state.location = *span;
+                let static_type = self.variable(state, *data_type, initializer.as_ref());
+                self.declare(state, name, static_type);
+            }
+            Statement::Block(statements, _) => self.scoped(state, statements),
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => {
+                state.location = *span;
+                self.condition(state, condition);
+                self.statement(state, then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.statement(state, else_branch);
+                }
+            }
+            Statement::While {
+                condition,
+                body,
+                span,
+            } => {
+                state.location = *span;
+                self.condition(state, condition);
+                self.statement(state, body);
+            }
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                span,
+            } => {
+                state.scopes.push(HashMap::new());
+                if let Some(initializer) = initializer {
+                    self.statement(state, initializer);
+                }
+                state.location = *span;
+                if let Some(condition) = condition {
+                    self.condition(state, condition);
+                }
+                if let Some(increment) = increment {
+                    self.expression(state, increment);
+                }
+                self.statement(state, body);
+                state.scopes.pop();
+            }
+            Statement::Return(value, span) => {
+                state.location = *span;
+                self.return_statement(state, value.as_ref(), *span);
+            }
+            Statement::Break(_) | Statement::Continue(_) => {}
+            Statement::Function {
+                name,
+                parameters,
+                return_type,
+                body,
+                span,
+            } => {
+                state.location = *span;
+                self.function(state, name, parameters, *return_type, body);
+            }
+        }
+    }
+
+    /// Type of the declared variable, inferred from the initializer for
+    /// `var`.
+    fn variable(
+        &mut self,
+        state: &mut TempState,
+        data_type: Option<DataType>,
+        initializer: Option<&Expresion>,
+    ) -> StaticType {
+        let value = initializer.map(|expression| (expression, self.expression(state, expression)));
+
+        match (data_type, value) {
+            (Some(data_type), Some((expression, found))) => {
+                let expected = StaticType::from(data_type);
+                self.expect(state, &expected, found, expression.span());
+                expected
+            }
+            (Some(data_type), None) => StaticType::from(data_type),
+            (None, Some((_, StaticType::Nil))) | (None, None) => StaticType::Any,
+            (None, Some((_, found))) => found,
+        }
+    }
+
+    fn function(
+        &mut self,
+        state: &mut TempState,
+        name: &str,
+        parameters: &[Parameter],
+        return_type: Option<DataType>,
+        body: &[Statement],
+    ) {
+        let signature = Signature {
+            parameters: parameters
+                .iter()
+                .map(|p| StaticType::from(p.data_type))
+                .collect(),
+            return_type: StaticType::from(return_type),
+        };
+        // Declared before the body is checked so it can call itself.
+        self.declare(state, name, StaticType::Function(Rc::new(signature)));
+
+        let scope = parameters
+            .iter()
+            .map(|p| (p.name.clone(), StaticType::from(p.data_type)))
+            .collect();
+        state.scopes.push(scope);
+        state.functions.push(StaticType::from(return_type));
+
+        for statement in body {
+            self.statement(state, statement);
+        }
+
+        state.functions.pop();
+        state.scopes.pop();
+    }
+
+    fn return_statement(&mut self, state: &mut TempState, value: Option<&Expresion>, span: Span) {
+        let found = value.map(|expression| self.expression(state, expression));
+        let Some(expected) = state.functions.last().cloned() else {
+            return;
+        };
+
+        match (expected, found) {
+            (StaticType::Void, Some(StaticType::Void)) | (StaticType::Void, None) => {}
+            (StaticType::Void, Some(_)) => {
+                let span = value.map_or(span, |v| v.span());
+                state.error(TypeErrorKind::ReturnValueInVoid, span);
+            }
+            (StaticType::Any, None) => {}
+            (expected, None) => state.error(TypeErrorKind::MissingReturnValue(expected), span),
+            (expected, Some(found)) => {
+                let span = value.map_or(span, |v| v.span());
+                self.expect(state, &expected, found, span);
+            }
+        }
+    }
+
+    /// Conditions of `if`, loops and `?:` must be booleans.
+    fn condition(&mut self, state: &mut TempState, condition: &Expresion) {
+        let found = self.expression(state, condition);
+        self.expect(state, &StaticType::Bool, found, condition.span());
+    }
+
+    fn expect(&self, state: &mut TempState, expected: &StaticType, found: StaticType, span: Span) {
+        if !expected.accepts(&found) {
+            let kind = TypeErrorKind::Mismatch {
+                expected: expected.clone(),
+                found,
+            };
+            state.error(kind, span);
+        }
+    }
+
+    fn expression(&mut self, state: &mut TempState, expression: &Expresion) -> StaticType {
+        match expression {
+            Expresion::Unknown(_, _) => StaticType::Any,
+            Expresion::Integer(_, _) => StaticType::Int,
+            Expresion::Float(_, _) => StaticType::Float,
+            Expresion::Str(_, _) => StaticType::String,
+            Expresion::Char(_, _) => StaticType::Char,
+            Expresion::Bool(_, _) => StaticType::Bool,
+            Expresion::Nil(_) => StaticType::Nil,
+            Expresion::Grouping(expression, _) => self.expression(state, expression),
+            Expresion::Variable(name, _) => self.lookup(state, name),
+            Expresion::Assign(name, value, span) => {
+                let expected = self.lookup(state, name);
+                let found = self.expression(state, value);
+                self.expect(state, &expected, found.clone(), value.span());
+                if expected == StaticType::Float && found != StaticType::Float {
+                    state.widened.push(*span);
+                }
+                match expected {
+                    StaticType::Any => found,
+                    expected => expected,
+                }
+            }
+            Expresion::Postfix(name, operator, span) => {
+                let operand = self.lookup(state, name);
+                if !operand.is_numeric() && operand != StaticType::Any {
+                    state.error(
+                        TypeErrorKind::InvalidOperand(operator, operand.clone()),
+                        *span,
+                    );
+                }
+                operand
+            }
+            Expresion::Ternary(condition, then_branch, else_branch, span) => {
+                self.condition(state, condition);
+                let then_type = self.expression(state, then_branch);
+                let else_type = self.expression(state, else_branch);
+                match (then_type, else_type) {
+                    (a, b) if a == b => a,
+                    (a, b) if a.is_numeric() && b.is_numeric() => {
+                        state.widened.push(*span);
+                        StaticType::Float
+                    }
+                    _ => StaticType::Any,
+                }
+            }
+            Expresion::Unary(operator, right, span) => {
+                let right = self.expression(state, right);
+                Self::unary(operator, right).unwrap_or_else(|right| {
+                    state.error(TypeErrorKind::InvalidOperand(operator, right), *span);
+                    StaticType::Any
+                })
+            }
+            Expresion::Binary(left, operator, right, span) => {
+                let left = self.expression(state, left);
+                let right = self.expression(state, right);
+                Self::binary(left, operator, right).unwrap_or_else(|(left, right)| {
+                    let kind = TypeErrorKind::InvalidOperands(operator, left, right);
+                    state.error(kind, *span);
+                    StaticType::Any
+                })
+            }
+            Expresion::Call(callee, arguments, span) => self.call(state, callee, arguments, *span),
+        }
+    }
+
+    fn call(
+        &mut self,
+        state: &mut TempState,
+        callee: &Expresion,
+        arguments: &[Expresion],
+        span: Span,
+    ) -> StaticType {
+        let callee = self.expression(state, callee);
+        let found = arguments
+            .iter()
+            .map(|argument| self.expression(state, argument))
+            .collect::<Vec<_>>();
+
+        let signature = match callee {
+            StaticType::Function(signature) => signature,
+            StaticType::Any => return StaticType::Any,
+            other => {
+                state.error(TypeErrorKind::NotCallable(other), span);
+                return StaticType::Any;
+            }
+        };
+
+        if found.len() != signature.parameters.len() {
+            let kind = TypeErrorKind::ArgumentCount {
+                expected: signature.parameters.len(),
+                found: found.len(),
+            };
+            state.error(kind, span);
+        } else {
+            for ((argument, found), expected) in
+                arguments.iter().zip(found).zip(&signature.parameters)
+            {
+                let span = argument.span();
+                self.expect(state, expected, found, span);
+            }
+        }
+
+        signature.return_type.clone()
+    }
+
+    /// Result type of a unary operator, or the operand type it rejects.
+    pub fn unary(operator: &str, right: StaticType) -> Result<StaticType, StaticType> {
+        match (operator, right) {
+            (_, StaticType::Any) => Ok(StaticType::Any),
+            ("!", StaticType::Bool) => Ok(StaticType::Bool),
+            ("-", right) if right.is_numeric() => Ok(right),
+            ("~", StaticType::Int) => Ok(StaticType::Int),
+            (_, right) => Err(right),
+        }
+    }
+
+    /// Result type of a binary operator, following the interpreter's rules,
+    /// or the operand types it rejects.
+    pub fn binary(
+        left: StaticType,
+        operator: &str,
+        right: StaticType,
+    ) -> Result<StaticType, (StaticType, StaticType)> {
+        use StaticType::*;
+
+        let result = match (operator, &left, &right) {
+            ("==" | "!=", _, _) => Bool,
+            ("&&" | "||", Bool | Any, Bool | Any) => Bool,
+            ("<" | "<=" | ">" | ">=", Any, _) | ("<" | "<=" | ">" | ">=", _, Any) => Bool,
+            (_, Any, _) | (_, _, Any) => Any,
+
+            ("+" | "-" | "*" | "/" | "%" | "**", Int, Int) => Int,
+            ("+" | "-" | "*" | "/" | "%" | "**", a, b) if a.is_numeric() && b.is_numeric() => Float,
+            ("+", String, String) | ("+", String, Char) | ("+", Char, String) => String,
+
+            ("<" | "<=" | ">" | ">=", a, b) if a.is_numeric() && b.is_numeric() => Bool,
+            ("<" | "<=" | ">" | ">=", String, String) | ("<" | "<=" | ">" | ">=", Char, Char) => {
+                Bool
+            }
+
+            ("&" | "|" | "^" | "<<" | ">>", Int, Int) => Int,
+            ("&" | "|" | "^", Bool, Bool) => Bool,
+
+            _ => return Err((left, right)),
+        };
+
+        Ok(result)
+    }
+}
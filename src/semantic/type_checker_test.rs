@@ -0,0 +1,188 @@
+#![allow(unused_imports, dead_code)]
+
+use super::{
+    static_type::StaticType,
+    type_checker::TypeChecker,
+    type_error::{TypeError, TypeErrorKind},
+};
+use crate::{test_support::parse, types::span::Span};
+
+fn check_with(checker: &mut TypeChecker, input: &str) -> Vec<TypeError> {
+    checker.check(&parse(input)).err().unwrap_or_default()
+}
+
+fn check(input: &str) -> Vec<TypeError> {
+    check_with(&mut TypeChecker::new(), input)
+}
+
+fn kinds(input: &str) -> Vec<TypeErrorKind> {
+    check(input).into_iter().map(|e| e.kind).collect()
+}
+
+fn mismatch(expected: StaticType, found: StaticType) -> TypeErrorKind {
+    TypeErrorKind::Mismatch { expected, found }
+}
+
+#[test]
+fn typed_declarations() {
+    let errors = check("int x = 10; float y = 1; string s = \"a\"; int z = \"ten\";");
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].kind,
+        mismatch(StaticType::Int, StaticType::String)
+    );
+    assert_eq!(errors[0].span, Span::new(49, 54, 1, 50));
+
+    assert_eq!(
+        kinds("int i = 2.5; bool b = nil; char c = 'c';"),
+        vec![
+            mismatch(StaticType::Int, StaticType::Float),
+            mismatch(StaticType::Bool, StaticType::Nil),
+        ]
+    );
+}
+
+#[test]
+fn assignments() {
+    let errors = check("int x = 1;\nx = x + 1;\nx = \"s\";");
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].kind,
+        mismatch(StaticType::Int, StaticType::String)
+    );
+    assert_eq!(errors[0].span, Span::new(26, 29, 3, 5));
+
+    // `var` takes the type of its initializer.
+    assert_eq!(
+        kinds("var a = 1; a = 2; a += 1.5; var b; b = 1; b = \"s\";"),
+        vec![mismatch(StaticType::Int, StaticType::Float)]
+    );
+
+    let errors = check("var x = 1; x = 2.5;");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, mismatch(StaticType::Int, StaticType::Float));
+    assert_eq!(errors[0].span, Span::new(15, 18, 1, 16));
+}
+
+#[test]
+fn operator_operands() {
+    assert_eq!(
+        kinds("print 1 + 2.5; print \"a\" + 'b'; print 'a' < 'b'; print true & false;"),
+        vec![]
+    );
+
+    let errors = check("print 1 + \"a\";");
+    assert_eq!(
+        errors[0].kind,
+        TypeErrorKind::InvalidOperands("+", StaticType::Int, StaticType::String)
+    );
+    assert_eq!(errors[0].span, Span::new(8, 9, 1, 9));
+
+    assert_eq!(
+        kinds("print -\"a\"; print !1; print 1.5 << 2; string s = \"\"; s++;"),
+        vec![
+            TypeErrorKind::InvalidOperand("-", StaticType::String),
+            TypeErrorKind::InvalidOperand("!", StaticType::Int),
+            TypeErrorKind::InvalidOperands("<<", StaticType::Float, StaticType::Int),
+            TypeErrorKind::InvalidOperand("++", StaticType::String),
+        ]
+    );
+}
+
+#[test]
+fn conditions() {
+    let errors = check("int n = 3;\nwhile (n) n = n - 1;");
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, mismatch(StaticType::Bool, StaticType::Int));
+    assert_eq!(errors[0].span, Span::new(18, 19, 2, 8));
+
+    assert_eq!(
+        kinds("if (1) {} for (;\"s\";) {} print 1.5 ? 1 : 2; if (1 < 2 && true) {}"),
+        vec![
+            mismatch(StaticType::Bool, StaticType::Int),
+            mismatch(StaticType::Bool, StaticType::String),
+            mismatch(StaticType::Bool, StaticType::Float),
+        ]
+    );
+}
+
+#[test]
+fn call_arguments() {
+    let errors = check(
+        "fun f(a: int, b: string) -> int { return a; }
+        f(1, \"s\"); f(\"s\", \"s\"); f(1); string s = f(1, \"s\"); 1();",
+    );
+
+    assert_eq!(
+        errors.iter().map(|e| e.kind.clone()).collect::<Vec<_>>(),
+        vec![
+            mismatch(StaticType::Int, StaticType::String),
+            TypeErrorKind::ArgumentCount {
+                expected: 2,
+                found: 1
+            },
+            mismatch(StaticType::String, StaticType::Int),
+            TypeErrorKind::NotCallable(StaticType::Int),
+        ]
+    );
+    assert_eq!(errors[1].span.line, 2);
+}
+
+#[test]
+fn return_types() {
+    assert_eq!(
+        kinds(
+            "void g() { return 1; }
+            fun h() -> int { return; }
+            fun k() -> string { return 1; }
+            float m() { return 1; }
+            void n() { return; }"
+        ),
+        vec![
+            TypeErrorKind::ReturnValueInVoid,
+            TypeErrorKind::MissingReturnValue(StaticType::Int),
+            mismatch(StaticType::String, StaticType::Int),
+        ]
+    );
+
+    assert_eq!(
+        kinds("void g() { } int x = g();"),
+        vec![mismatch(StaticType::Int, StaticType::Void)]
+    );
+}
+
+#[test]
+fn untyped_code_is_left_to_runtime() {
+    assert_eq!(
+        kinds(
+            "fun add(a, b) { return a + b; }
+            print add(1, \"s\");
+            var x = nil; x = 1; x = \"s\";
+            print undefined * 2;
+            fun fact(n: int) -> int { if (n < 2) return 1; return n * fact(n - 1); }"
+        ),
+        vec![]
+    );
+}
+
+#[test]
+fn scopes() {
+    assert_eq!(
+        kinds("int x = 1; { string x = \"s\"; x = \"t\"; } x = 2; fun f(x: bool) { x = true; }"),
+        vec![]
+    );
+}
+
+#[test]
+fn globals_persist_between_checks() {
+    let mut checker = TypeChecker::new();
+
+    assert!(check_with(&mut checker, "int x = 1;").is_empty());
+    assert_eq!(
+        check_with(&mut checker, "x = true;")[0].kind,
+        mismatch(StaticType::Int, StaticType::Bool)
+    );
+}
@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use std::{error::Error, fmt};
+
+use super::static_type::StaticType;
+use crate::types::span::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeErrorKind {
+    Mismatch {
+        expected: StaticType,
+        found: StaticType,
+    },
+    /// A binary operator and the types of its operands.
+    InvalidOperands(&'static str, StaticType, StaticType),
+    InvalidOperand(&'static str, StaticType),
+    NotCallable(StaticType),
+    ArgumentCount {
+        expected: usize,
+        found: usize,
+    },
+    ReturnValueInVoid,
+    /// `return;` in a function declared to return the type.
+    MissingReturnValue(StaticType),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeError {
+    pub kind: TypeErrorKind,
+    pub span: Span,
+}
+
+impl TypeError {
+    pub fn new(kind: TypeErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    pub fn message(&self) -> String {
+        match &self.kind {
+            TypeErrorKind::Mismatch { expected, found } => {
+                format!("mismatched types: expected {}, found {}", expected, found)
+            }
+            TypeErrorKind::InvalidOperands(operator, left, right) => {
+                format!(
+                    "operator '{}' cannot be applied to {} and {}",
+                    operator, left, right
+                )
+            }
+            TypeErrorKind::InvalidOperand(operator, operand) => {
+                format!("operator '{}' cannot be applied to {}", operator, operand)
+            }
+            TypeErrorKind::NotCallable(callee) => {
+                format!("cannot call a value of type {}", callee)
+            }
+            TypeErrorKind::ArgumentCount { expected, found } => {
+                format!("function expects {} arguments but got {}", expected, found)
+            }
+            TypeErrorKind::ReturnValueInVoid => "void function cannot return a value".to_owned(),
+            TypeErrorKind::MissingReturnValue(expected) => {
+                format!("missing return value of type {}", expected)
+            }
+        }
+    }
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}.", self.span, self.message())
+    }
+}
+
+impl Error for TypeError {}
@@ -0,0 +1,4 @@
+pub mod source_file;
+pub mod source_map;
+#[cfg(test)]
+mod source_map_test;
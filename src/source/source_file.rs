@@ -0,0 +1,34 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::span::Span;
+
+/// Index of a file in its `SourceMap`, stored in every `Span`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct FileId(pub usize);
+
+/// A source text and the name diagnostics refer to it by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    pub id: FileId,
+    /// Path as given on the command line, `<stdin>`, or `None` for input
+    /// that has no name such as REPL lines.
+    pub name: Option<String>,
+    pub text: String,
+}
+
+impl SourceFile {
+    pub fn new(id: FileId, name: Option<String>, text: String) -> Self {
+        Self { id, name, text }
+    }
+
+    /// `name:line:column` of a span in this file, or `line:column` when the
+    /// file has no name.
+    pub fn location(&self, span: Span) -> String {
+        match &self.name {
+            Some(name) => format!("{}:{}", name, span),
+            None => span.to_string(),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+};
+
+use super::source_file::{FileId, SourceFile};
+use crate::types::span::Span;
+
+pub const STDIN_NAME: &str = "<stdin>";
+
+/// Every source text of a compilation, indexed by `FileId`.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self { files: vec![] }
+    }
+
+    pub fn add(&mut self, name: &str, text: String) -> FileId {
+        self.push(Some(name.to_owned()), text)
+    }
+
+    /// Adds text without a name, its locations print as `line:column`.
+    pub fn add_anonymous(&mut self, text: String) -> FileId {
+        self.push(None, text)
+    }
+
+    /// Reads a file, naming it by `path` as given.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> io::Result<FileId> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Ok(self.add(&path.display().to_string(), text))
+    }
+
+    pub fn load_reader(&mut self, name: &str, mut reader: impl Read) -> io::Result<FileId> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        Ok(self.add(name, text))
+    }
+
+    pub fn load_stdin(&mut self) -> io::Result<FileId> {
+        self.load_reader(STDIN_NAME, io::stdin().lock())
+    }
+
+    fn push(&mut self, name: Option<String>, text: String) -> FileId {
+        let id = FileId(self.files.len());
+        self.files.push(SourceFile::new(id, name, text));
+        id
+    }
+
+    /// Panics for an id from another map.
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// `name:line:column` of a span, in the file the span refers to.
+    pub fn location(&self, span: Span) -> String {
+        self.get(span.file).location(span)
+    }
+}
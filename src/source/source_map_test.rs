@@ -0,0 +1,83 @@
+#![allow(unused_imports, dead_code)]
+
+use super::{
+    source_file::FileId,
+    source_map::{SourceMap, STDIN_NAME},
+};
+use crate::{
+    diagnostics::{diagnostic::Diagnostic, renderer::Renderer},
+    lexer::scanner::Scanner,
+    types::span::Span,
+};
+
+#[test]
+fn assigns_file_ids() {
+    let mut sources = SourceMap::new();
+    let first = sources.add("a.lox", "var a = 1;".to_owned());
+    let second = sources.add("b.lox", "var b = 2;".to_owned());
+
+    assert_eq!(first, FileId(0));
+    assert_eq!(second, FileId(1));
+    assert_eq!(sources.get(second).name.as_deref(), Some("b.lox"));
+    assert_eq!(sources.files().len(), 2);
+}
+
+#[test]
+fn loads_files_and_readers() {
+    let mut sources = SourceMap::new();
+    let file = sources.load("source_files/file1.lox").unwrap();
+    assert_eq!(
+        sources.get(file).name.as_deref(),
+        Some("source_files/file1.lox")
+    );
+    assert!(sources.get(file).text.starts_with("var a = 1;"));
+
+    let stdin = sources
+        .load_reader(STDIN_NAME, "print 1;".as_bytes())
+        .unwrap();
+    assert_eq!(sources.get(stdin).text, "print 1;");
+
+    assert!(sources.load("source_files/missing.lox").is_err());
+}
+
+#[test]
+fn spans_refer_to_their_file() {
+    let mut sources = SourceMap::new();
+    sources.add("a.lox", "var a = 1;".to_owned());
+    let second = sources.add("b.lox", "\n\n    var b;".to_owned());
+
+    let tokens = Scanner::for_file(sources.get(second)).get_tokens().unwrap();
+
+    assert_eq!(tokens[1].span, Span::new(10, 11, 3, 9).in_file(second));
+    assert_eq!(sources.location(tokens[1].span), "b.lox:3:9");
+}
+
+#[test]
+fn anonymous_locations() {
+    let mut sources = SourceMap::new();
+    let file = sources.add_anonymous("x".to_owned());
+
+    assert_eq!(sources.location(Span::new(0, 1, 1, 1).in_file(file)), "1:1");
+}
+
+#[test]
+fn diagnostics_name_the_file() {
+    let mut sources = SourceMap::new();
+    sources.add("file0.lox", "print 0;".to_owned());
+    let file = sources.add("file1.lox", "var a = 1;\nprint a;\nprint @;".to_owned());
+
+    let errors = Scanner::for_file(sources.get(file))
+        .get_tokens()
+        .unwrap_err();
+    let diagnostics = errors.iter().map(Diagnostic::from).collect::<Vec<_>>();
+
+    assert_eq!(
+        Renderer::plain().render_all_in(&diagnostics, &sources),
+        "error[E0001]: unexpected character '@'
+ --> file1.lox:3:7
+  |
+3 | print @;
+  |       ^
+"
+    );
+}
@@ -0,0 +1,120 @@
+//! Helpers shared by the tests of several modules.
+
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    ast::{tree::AbstractSyntaxTree, tree_types::Statement},
+    bytecode::{chunk::CompiledFunction, compiler::Compiler},
+    codegen::toolchain,
+    interpreter::{evaluator::Interpreter, runtime_error::RuntimeError},
+    lexer::scanner::Scanner,
+    semantic::{
+        resolution::Resolution, resolve_error::ResolveError, resolver::Resolver,
+        type_checker::TypeChecker,
+    },
+};
+
+/// Runs `f` on a thread with the stack the compiler runs on, for programs
+/// the interpreter recurses deeply into.
+pub fn with_compiler_stack<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(crate::STACK_SIZE)
+            .spawn_scoped(scope, f)
+            .unwrap()
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// Statements of the program, which must scan and parse.
+pub fn parse(input: &str) -> Vec<Statement> {
+    let tokens = Scanner::new(input).get_tokens().unwrap();
+
+    let mut ast = AbstractSyntaxTree::new();
+    assert!(ast.accept_tokens(&tokens).is_ok());
+    ast.get().to_vec()
+}
+
+/// Resolves the program and marks what the type checker widens. Programs
+/// it rejects still run, to test the runtime errors they raise.
+fn resolve(resolver: &mut Resolver, statements: &[Statement]) -> (Resolution, Vec<ResolveError>) {
+    let (mut resolution, errors) = resolver.resolve(statements);
+    if let Ok(widened) = TypeChecker::new().check(statements) {
+        resolution.widen(widened);
+    }
+    (resolution, errors)
+}
+
+/// Compiles the program to bytecode, the resolver may only warn about it.
+pub fn compile(input: &str) -> CompiledFunction {
+    compile_with(&mut Resolver::new(), input)
+}
+
+/// Compiles the program with a resolver that may know earlier inputs.
+pub fn compile_with(resolver: &mut Resolver, input: &str) -> CompiledFunction {
+    let statements = parse(input);
+
+    let (resolution, errors) = resolve(resolver, &statements);
+    assert!(errors.iter().all(|e| e.is_warning()), "{:?}", errors);
+
+    Compiler::new(&resolution).compile(&statements).unwrap()
+}
+
+/// Resolves and runs the program with the tree-walking interpreter.
+pub fn interpret(input: &str) -> Result<String, RuntimeError> {
+    let (output, result) = interpret_output(input);
+    result.map(|()| output)
+}
+
+/// What the tree-walking interpreter prints before the program ends,
+/// with or without a runtime error.
+pub fn interpret_output(input: &str) -> (String, Result<(), RuntimeError>) {
+    let statements = parse(input);
+
+    let resolution = resolve(&mut Resolver::new(), &statements).0;
+    with_compiler_stack(|| {
+        let mut interpreter = Interpreter::new(Vec::new());
+        interpreter.resolve(resolution);
+        let result = interpreter.interpret(&statements);
+
+        (
+            String::from_utf8(interpreter.into_output()).unwrap(),
+            result,
+        )
+    })
+}
+
+/// Runs the executable `build` writes to the path it is given, returns
+/// its exit code, stdout and stderr. `None` when no C compiler is installed.
+pub fn run_executable(prefix: &str, build: impl FnOnce(&Path)) -> Option<(i32, String, String)> {
+    let available = Command::new(toolchain::compiler())
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !available {
+        return None;
+    }
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let executable = std::env::temp_dir().join(format!(
+        "{}-{}-{}",
+        prefix,
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    build(&executable);
+    let output = Command::new(&executable).output().unwrap();
+    let _ = fs::remove_file(&executable);
+
+    Some((
+        output.status.code().unwrap_or(-1),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    ))
+}
@@ -1,2 +1,3 @@
+pub mod span;
 pub mod token_type;
 pub mod token_value;
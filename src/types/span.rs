@@ -0,0 +1,99 @@
+#![allow(dead_code)]
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::source::source_file::FileId;
+
+/// Source range of a token or syntax node.
+///
+/// `start` and `end` are byte offsets (end exclusive), `line` and `column`
+/// are 1-based and point at the first character. Columns count grapheme
+/// clusters, so `é` is one column whether or not it is precomposed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    /// The `SourceMap` file the offsets are in.
+    pub file: FileId,
+}
+
+impl Span {
+    /// Span in the first file of the source map.
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self {
+            start,
+            end,
+            line,
+            column,
+            file: FileId::default(),
+        }
+    }
+
+    pub fn in_file(self, file: FileId) -> Self {
+        Self { file, ..self }
+    }
+
+    /// Smallest span covering both `self` and `other`.
+    pub fn merge(self, other: Span) -> Self {
+        let first = if self.start <= other.start {
+            self
+        } else {
+            other
+        };
+
+        Self {
+            start: first.start,
+            end: self.end.max(other.end),
+            line: first.line,
+            column: first.column,
+            file: first.file,
+        }
+    }
+
+    /// Empty span right after `self`, whose source text is `text`.
+    pub fn after(self, text: &str) -> Self {
+        let (line, column) = match text.rfind('\n') {
+            Some(newline) => (
+                self.line + text.matches('\n').count(),
+                columns(&text[newline + 1..]) + 1,
+            ),
+            None => (self.line, self.column + columns(text)),
+        };
+
+        Self {
+            start: self.end,
+            end: self.end,
+            line,
+            column,
+            file: self.file,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Number of columns `text` takes up, counted in grapheme clusters.
+pub fn columns(text: &str) -> usize {
+    if text.is_ascii() {
+        text.len()
+    } else {
+        text.graphemes(true).count()
+    }
+}
@@ -20,7 +20,15 @@ pub enum TokenType {
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Question,
+    Colon,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
 
+    // One or two character tokens.
     Bang,
     BangEqual,
     Equal,
@@ -29,34 +37,112 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    LessLess,
+    GreaterGreater,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PlusPlus,
+    MinusMinus,
+    StarStar,
+    Arrow,
+    ColonColon,
+    /// `&&`.
+    And,
+    /// `||`.
+    Or,
 
     //Types
     Identifier,
     Int,
     Float,
     String,
+    Char,
+    Bool,
+    Void,
 
     // Literals.
     Str,
+    Character,
     Integer,
     Real,
-    Boolean,
 
     // Keywords.
-    And,
+    Break,
+    Class,
+    Continue,
     Else,
     False,
     Fun,
     For,
     If,
     Nil,
-    Or,
     Print,
     Return,
+    Super,
+    This,
     True,
+    Var,
     While,
 }
 
+/// Every reserved word and the token it is lexed to.
+pub const KEYWORDS: &[(&str, TokenType)] = &[
+    // Types, `bool` and `boolean` are both accepted.
+    ("int", TokenType::Int),
+    ("float", TokenType::Float),
+    ("string", TokenType::String),
+    ("char", TokenType::Char),
+    ("bool", TokenType::Bool),
+    ("boolean", TokenType::Bool),
+    ("void", TokenType::Void),
+    // Other keywords.
+    ("break", TokenType::Break),
+    ("class", TokenType::Class),
+    ("continue", TokenType::Continue),
+    ("else", TokenType::Else),
+    ("false", TokenType::False),
+    ("fun", TokenType::Fun),
+    ("for", TokenType::For),
+    ("if", TokenType::If),
+    ("nil", TokenType::Nil),
+    ("print", TokenType::Print),
+    ("return", TokenType::Return),
+    ("super", TokenType::Super),
+    ("this", TokenType::This),
+    ("true", TokenType::True),
+    ("var", TokenType::Var),
+    ("while", TokenType::While),
+];
+
+impl TokenType {
+    /// Token for a reserved word, `None` for ordinary identifiers.
+    pub fn keyword(word: &str) -> Option<TokenType> {
+        KEYWORDS
+            .iter()
+            .find(|(keyword, _)| *keyword == word)
+            .map(|(_, token_type)| *token_type)
+    }
+
+    pub fn is_keyword(self) -> bool {
+        KEYWORDS.iter().any(|(_, token_type)| *token_type == self)
+    }
+
+    /// Keywords naming a type, usable in declarations.
+    pub fn is_type(self) -> bool {
+        matches!(
+            self,
+            TokenType::Int
+                | TokenType::Float
+                | TokenType::String
+                | TokenType::Char
+                | TokenType::Bool
+                | TokenType::Void
+        )
+    }
+}
+
 impl fmt::Display for TokenType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
@@ -6,12 +6,23 @@ use std::fmt;
 pub enum TokenValue {
     #[default]
     None,
-    Int(i32),
+    Int(i64),
+    UInt(u64),
     Float(f64),
+    Float32(f32),
     String(String),
+    Char(char),
     Bool(bool),
 }
 
+impl TokenValue {
+    /// The `f64` closest to the shortest decimal form of an `f32`, so `3.14f`
+    /// is 3.14 and not 3.140000104904175.
+    pub fn widen_f32(value: f32) -> f64 {
+        value.to_string().parse().unwrap_or(value.into())
+    }
+}
+
 impl fmt::Display for TokenValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)